@@ -1,6 +1,6 @@
 use cosmwasm_std::{
-    attr, entry_point, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
+    attr, entry_point, to_binary, Addr, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint64, WasmMsg,
 };
 
 use crate::error::ContractError;
@@ -20,6 +20,9 @@ use astroport::factory::{
 };
 
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use astroport::generator::{
+    ExecuteMsg as GeneratorExecuteMsg, QueryMsg as GeneratorQueryMsg, RewardInfoResponse,
+};
 use astroport::pair::InstantiateMsg as PairInstantiateMsg;
 use cw2::set_contract_version;
 use protobuf::Message;
@@ -31,6 +34,8 @@ const CONTRACT_NAME: &str = "astroport-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID of sub-message.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
+/// A `reply` call code ID of the generator pool deactivation sub-message.
+const DEACTIVATE_POOL_REPLY_ID: u64 = 2;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in the `msg` variable.
@@ -127,7 +132,9 @@ pub struct UpdateConfig {
 ///             pair_type,
 ///             asset_infos,
 ///             init_params,
-///         }** Creates a new pair with the specified input parameters
+///             initial_alloc_point,
+///         }** Creates a new pair with the specified input parameters and optionally registers
+/// its LP token in the generator
 ///
 /// * **ExecuteMsg::Deregister { asset_infos }** Removes a exists pair with the specified input parameters.
 ///
@@ -163,7 +170,16 @@ pub fn execute(
             pair_type,
             asset_infos,
             init_params,
-        } => execute_create_pair(deps, env, pair_type, asset_infos, init_params),
+            initial_alloc_point,
+        } => execute_create_pair(
+            deps,
+            env,
+            info,
+            pair_type,
+            asset_infos,
+            init_params,
+            initial_alloc_point,
+        ),
         ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
@@ -297,17 +313,24 @@ pub fn execute_update_pair_config(
 ///
 /// * **env** is the object of type [`Env`].
 ///
+/// * **info** is the object of type [`MessageInfo`].
+///
 /// * **pair_type** is the object of type [`PairType`].
 ///
 /// * **asset_infos** is an array with two items the type of [`AssetInfo`].
 ///
 /// * **init_params** is an [`Option`] type. Receive a binary data.
+///
+/// * **initial_alloc_point** is an [`Option`] field object of type [`Uint64`]. A non-zero value
+/// registers the LP token in the generator and can only be set by the owner.
 pub fn execute_create_pair(
     deps: DepsMut,
     env: Env,
+    info: MessageInfo,
     pair_type: PairType,
     asset_infos: [AssetInfo; 2],
     init_params: Option<Binary>,
+    initial_alloc_point: Option<Uint64>,
 ) -> Result<Response, ContractError> {
     asset_infos[0].check(deps.api)?;
     asset_infos[1].check(deps.api)?;
@@ -318,6 +341,17 @@ pub fn execute_create_pair(
 
     let config = CONFIG.load(deps.storage)?;
 
+    let initial_alloc_point = initial_alloc_point.filter(|alloc_point| !alloc_point.is_zero());
+    if initial_alloc_point.is_some() {
+        if info.sender != config.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        if config.generator_address.is_none() {
+            return Err(ContractError::GeneratorAddressNotSet {});
+        }
+    }
+
     if PAIRS
         .may_load(deps.storage, &pair_key(&asset_infos))?
         .is_some()
@@ -336,7 +370,13 @@ pub fn execute_create_pair(
     }
 
     let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            initial_alloc_point,
+        },
+    )?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         id: INSTANTIATE_PAIR_REPLY_ID,
@@ -375,6 +415,27 @@ pub fn execute_create_pair(
 /// * **msg** is the object of type [`Reply`].
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // the pair is already deregistered, so a failed deactivation must not revert it
+        DEACTIVATE_POOL_REPLY_ID => {
+            let error = msg.result.unwrap_err();
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "deactivate_pool_failed"),
+                attr("error", error),
+            ]))
+        }
+        _ => register_pair(deps, msg),
+    }
+}
+
+/// ## Description
+/// Saves the address of the instantiated pair and adds its LP token to the generator
+/// if an initial allocation point is specified. Returns an [`ContractError`] on failure.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **msg** is the object of type [`Reply`].
+fn register_pair(deps: DepsMut, msg: Reply) -> Result<Response, ContractError> {
     let tmp = TMP_PAIR_INFO.load(deps.storage)?;
     if PAIRS.may_load(deps.storage, &tmp.pair_key)?.is_some() {
         return Err(ContractError::PairWasRegistered {});
@@ -390,15 +451,35 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 
     PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
 
-    Ok(Response::new().add_attributes(vec![
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let config = CONFIG.load(deps.storage)?;
+    if let (Some(alloc_point), Some(generator)) =
+        (tmp.initial_alloc_point, config.generator_address)
+    {
+        let pair_info = query_pair_info(deps.as_ref(), &pair_contract)?;
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: generator.to_string(),
+                msg: to_binary(&GeneratorExecuteMsg::Add {
+                    lp_token: pair_info.liquidity_token,
+                    alloc_point,
+                    reward_proxy: None,
+                })?,
+                funds: vec![],
+            }
+            .into(),
+        );
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
         attr("action", "register"),
         attr("pair_contract_addr", pair_contract),
     ]))
 }
 
 /// ## Description
-/// Removes a exists pair with the specified parameters in the `asset_infos` variable.
-/// Returns an [`ContractError`] on failure or returns the [`Response`] with the specified attributes
+/// Removes a exists pair with the specified parameters in the `asset_infos` variable and deactivates
+/// the pool of its LP token in the generator. Returns an [`ContractError`] on failure or returns the [`Response`] with the specified attributes
 /// if the operation was successful.
 ///
 /// ## Params
@@ -427,10 +508,42 @@ pub fn deregister(
     let pair_addr: Addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
     PAIRS.remove(deps.storage, &pair_key(&asset_infos));
 
-    Ok(Response::new().add_attributes(vec![
-        attr("action", "deregister"),
-        attr("pair_contract_addr", pair_addr),
-    ]))
+    let mut messages: Vec<SubMsg> = vec![];
+    if let Some(generator) = config.generator_address {
+        let pair_info = query_pair_info(deps.as_ref(), &pair_addr)?;
+
+        // deactivates the pool only if it is registered in the generator and still active
+        let pool_is_active = deps
+            .querier
+            .query_wasm_smart::<RewardInfoResponse>(
+                &generator,
+                &GeneratorQueryMsg::RewardInfo {
+                    lp_token: pair_info.liquidity_token.clone(),
+                },
+            )
+            .map(|reward_info| !reward_info.deactivated)
+            .unwrap_or(false);
+
+        if pool_is_active {
+            messages.push(SubMsg::reply_on_error(
+                WasmMsg::Execute {
+                    contract_addr: generator.to_string(),
+                    msg: to_binary(&GeneratorExecuteMsg::DeactivatePool {
+                        lp_token: pair_info.liquidity_token,
+                    })?,
+                    funds: vec![],
+                },
+                DEACTIVATE_POOL_REPLY_ID,
+            ));
+        }
+    }
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            attr("action", "deregister"),
+            attr("pair_contract_addr", pair_addr),
+        ]))
 }
 
 /// ## Description
@@ -1,8 +1,9 @@
 use astroport::asset::PairInfo;
+use astroport::generator::{QueryMsg as GeneratorQueryMsg, RewardInfoResponse};
 use astroport::pair::QueryMsg;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Coin, Empty, OwnedDeps, Querier, QuerierResult,
+    from_binary, from_slice, to_binary, Addr, Coin, Empty, OwnedDeps, Querier, QuerierResult,
     QueryRequest, SystemError, SystemResult, WasmQuery,
 };
 use std::collections::HashMap;
@@ -25,6 +26,8 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     astroport_pair_querier: AstroportPairQuerier,
+    generator_pools: Vec<Addr>,
+    deactivated_generator_pools: Vec<Addr>,
 }
 
 #[derive(Clone, Default)]
@@ -67,6 +70,32 @@ impl Querier for WasmMockQuerier {
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg })
+                if contract_addr == "generator" =>
+            {
+                match from_binary(&msg).unwrap() {
+                    GeneratorQueryMsg::RewardInfo { lp_token } => {
+                        if !self.generator_pools.contains(&lp_token) {
+                            return SystemResult::Err(SystemError::InvalidRequest {
+                                error: format!("No pool for {}", lp_token),
+                                request: msg.clone(),
+                            });
+                        }
+
+                        SystemResult::Ok(
+                            to_binary(&RewardInfoResponse {
+                                base_reward_token: Addr::unchecked("astro"),
+                                proxy_reward_token: None,
+                                deactivated: self
+                                    .deactivated_generator_pools
+                                    .contains(&lp_token),
+                            })
+                            .into(),
+                        )
+                    }
+                    _ => panic!("DO NOT ENTER HERE"),
+                }
+            }
             QueryRequest::Wasm(WasmQuery::Smart {contract_addr, msg})// => {
                 => match from_binary(&msg).unwrap() {
                     QueryMsg::Pair {} => {
@@ -94,9 +123,21 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             astroport_pair_querier: AstroportPairQuerier::default(),
+            generator_pools: vec![],
+            deactivated_generator_pools: vec![],
         }
     }
 
+    // configure the LP tokens registered in the generator
+    pub fn with_generator_pools(&mut self, lp_tokens: &[&str]) {
+        self.generator_pools = lp_tokens.iter().map(|v| Addr::unchecked(*v)).collect();
+    }
+
+    // configure the LP tokens deactivated in the generator
+    pub fn with_deactivated_generator_pools(&mut self, lp_tokens: &[&str]) {
+        self.deactivated_generator_pools = lp_tokens.iter().map(|v| Addr::unchecked(*v)).collect();
+    }
+
     // configure the astroport pair
     pub fn with_astroport_pairs(&mut self, pairs: &[(&String, &PairInfo)]) {
         self.astroport_pair_querier = AstroportPairQuerier::new(pairs);
@@ -2,7 +2,7 @@ use cw_storage_plus::{Bound, Item, Map};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Deps, Order};
+use cosmwasm_std::{Addr, Deps, Order, Uint64};
 
 use astroport::asset::AssetInfo;
 
@@ -28,6 +28,8 @@ pub struct Config {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    /// the allocation point used to register the LP token in the generator
+    pub initial_alloc_point: Option<Uint64>,
 }
 
 /// Saves a key of pair
@@ -1,6 +1,6 @@
 use cosmwasm_std::{
     attr, from_binary, to_binary, Addr, ContractResult, Reply, ReplyOn, SubMsg,
-    SubMsgExecutionResponse, WasmMsg,
+    SubMsgExecutionResponse, Uint64, WasmMsg,
 };
 
 use crate::mock_querier::mock_dependencies;
@@ -17,6 +17,7 @@ use astroport::factory::{
 
 use crate::contract::reply;
 use crate::response::MsgInstantiateContractResponse;
+use astroport::generator::ExecuteMsg as GeneratorExecuteMsg;
 use astroport::pair::InstantiateMsg as PairInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use protobuf::Message;
@@ -414,6 +415,7 @@ fn create_pair() {
             pair_type: PairType::Stable {},
             asset_infos: asset_infos.clone(),
             init_params: None,
+            initial_alloc_point: None,
         },
     )
     .unwrap_err();
@@ -427,6 +429,7 @@ fn create_pair() {
             pair_type: PairType::Xyk {},
             asset_infos: asset_infos.clone(),
             init_params: None,
+            initial_alloc_point: None,
         },
     )
     .unwrap();
@@ -498,6 +501,7 @@ fn register() {
         pair_type: PairType::Xyk {},
         asset_infos: asset_infos.clone(),
         init_params: None,
+        initial_alloc_point: None,
     };
 
     let env = mock_env();
@@ -574,6 +578,7 @@ fn register() {
         pair_type: PairType::Xyk {},
         asset_infos: asset_infos_2.clone(),
         init_params: None,
+        initial_alloc_point: None,
     };
 
     let env = mock_env();
@@ -718,3 +723,180 @@ fn register() {
         },]
     );
 }
+
+#[test]
+fn create_pair_with_generator_registration() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: 123u64,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 100,
+            maker_fee_bps: 10,
+            is_disabled: None,
+        }],
+        token_code_id: 123u64,
+        fee_address: None,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreatePair {
+        pair_type: PairType::Xyk {},
+        asset_infos: asset_infos.clone(),
+        init_params: None,
+        initial_alloc_point: Some(Uint64::from(100u64)),
+    };
+
+    // Only owner can register the pair in the generator
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info(owner, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let pair0_addr = "pair0000".to_string();
+    let pair0_info = PairInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        pair_type: PairType::Xyk {},
+    };
+    deps.querier
+        .with_astroport_pairs(&[(&pair0_addr, &pair0_info)]);
+
+    let data = MsgInstantiateContractResponse {
+        contract_address: String::from("pair0000"),
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("generator"),
+            msg: to_binary(&GeneratorExecuteMsg::Add {
+                lp_token: Addr::unchecked("liquidity0000"),
+                alloc_point: Uint64::from(100u64),
+                reward_proxy: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+
+    // Deregistration deactivates the pool in the generator
+    deps.querier.with_generator_pools(&["liquidity0000"]);
+
+    let info = mock_info(owner, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Deregister {
+            asset_infos: asset_infos.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: String::from("generator"),
+                msg: to_binary(&GeneratorExecuteMsg::DeactivatePool {
+                    lp_token: Addr::unchecked("liquidity0000"),
+                })
+                .unwrap(),
+                funds: vec![],
+            },
+            2
+        )]
+    );
+
+    // A failed deactivation doesn't revert the deregistration
+    let reply_msg = Reply {
+        id: 2,
+        result: ContractResult::Err("Unauthorized".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "deactivate_pool_failed"),
+            attr("error", "Unauthorized")
+        ]
+    );
+
+    // An already deactivated pool isn't deactivated again
+    deps.querier
+        .with_astroport_pairs(&[(&pair0_addr, &pair0_info)]);
+    deps.querier
+        .with_deactivated_generator_pools(&["liquidity0000"]);
+    let info = mock_info(owner, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+            initial_alloc_point: None,
+        },
+    )
+    .unwrap();
+    let data = MsgInstantiateContractResponse {
+        contract_address: String::from("pair0000"),
+        data: vec![],
+        unknown_fields: Default::default(),
+        cached_size: Default::default(),
+    }
+    .write_to_bytes()
+    .unwrap();
+    let reply_msg = Reply {
+        id: 1,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(data.into()),
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::Deregister { asset_infos },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+}
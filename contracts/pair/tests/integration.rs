@@ -358,6 +358,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         ],
         pair_type: PairType::Xyk {},
         init_params: None,
+        initial_alloc_point: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
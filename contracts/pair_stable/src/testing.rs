@@ -889,14 +889,20 @@ fn try_token_to_native() {
     let expected_return_amount = expected_ret_amount
         .checked_sub(expected_commission_amount)
         .unwrap();
-    let expected_tax_amount = std::cmp::min(
-        Uint128::new(1000000u128),
-        expected_return_amount
-            .checked_sub(
-                expected_return_amount.multiply_ratio(Uint128::new(100u128), Uint128::new(101u128)),
-            )
-            .unwrap(),
-    );
+    // No tax is charged without the terra feature
+    let expected_tax_amount = if cfg!(feature = "terra") {
+        std::cmp::min(
+            Uint128::new(1000000u128),
+            expected_return_amount
+                .checked_sub(
+                    expected_return_amount
+                        .multiply_ratio(Uint128::new(100u128), Uint128::new(101u128)),
+                )
+                .unwrap(),
+        )
+    } else {
+        Uint128::zero()
+    };
     // check simulation res
     // return asset token balance as normal
     deps.querier.with_token_balances(&[
@@ -1059,10 +1065,15 @@ fn test_deduct() {
     );
 
     let amount = Uint128::new(1000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
-    );
+    // No tax is charged without the terra feature
+    let expected_after_amount = if cfg!(feature = "terra") {
+        std::cmp::max(
+            amount.checked_sub(amount * tax_rate).unwrap(),
+            amount.checked_sub(tax_cap).unwrap(),
+        )
+    } else {
+        amount
+    };
 
     let after_amount = (Asset {
         info: AssetInfo::NativeToken {
@@ -390,6 +390,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             },
         ],
         init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+        initial_alloc_point: None,
     };
 
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
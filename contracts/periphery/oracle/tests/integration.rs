@@ -251,6 +251,7 @@ fn create_pair(
                 pair_type: PairType::Xyk {},
                 asset_infos: asset_infos.clone(),
                 init_params: None,
+                initial_alloc_point: None,
             },
             &[],
         )
@@ -356,6 +357,7 @@ fn create_pair_stable(
                 pair_type: PairType::Stable {},
                 asset_infos: asset_infos.clone(),
                 init_params: Some(to_binary(&StablePoolParams { amp: 100 }).unwrap()),
+                initial_alloc_point: None,
             },
             &[],
         )
@@ -1,29 +1,49 @@
+#[cfg(feature = "terra")]
+use cosmwasm_std::Coin;
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Api, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
-    MessageInfo, QueryRequest, Response, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
+    entry_point, from_binary, to_binary, Addr, Api, BankMsg, Binary, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
 };
 
 use crate::error::ContractError;
 use crate::operations::execute_swap_operation;
-use crate::querier::compute_tax;
-use crate::state::{Config, CONFIG};
+use crate::querier::{compute_tax, reverse_simulate_astro_swap, simulate_astro_swap};
+use crate::routes::find_best_route;
+use crate::state::{Config, CONFIG, DEFAULT_MAX_REFERRAL_COMMISSION_BPS, LEGACY_CONFIG};
+use crate::zap::{
+    execute_swap_and_provide, execute_withdraw_and_swap, get_zap_offer, provide_swapped_liquidity,
+    send_liquidity, swap_withdrawn_liquidity,
+};
+use crate::CustomMsg;
 
 use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo, PairInfo};
-use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
-use astroport::querier::query_pair_info;
+use astroport::factory::PairType;
+use astroport::querier::{query_pair_info, simulate};
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg, Referral,
+    SimulateReverseSwapOperationsResponse, SimulateSplitSwapResponse,
+    SimulateSwapOperationsDetailedResponse, SimulateSwapOperationsResponse, SwapOperation,
+    SwapOperationSimulation, MAX_REFERRAL_COMMISSION_BPS, MAX_ROUTE_CANDIDATES, MAX_ROUTE_HOPS,
+    MAX_SWAP_OPERATIONS,
 };
 use cw2::set_contract_version;
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use std::collections::HashMap;
-use terra_cosmwasm::{SwapResponse, TerraMsgWrapper, TerraQuerier};
+#[cfg(feature = "terra")]
+use terra_cosmwasm::{SwapResponse, TerraQuerier};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-router";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The default maximum number of hops in a route found by the `FindBestRoute` query
+const DEFAULT_MAX_HOPS: u32 = 3;
+/// The default maximum number of factory pairs considered by the `FindBestRoute` query
+const DEFAULT_MAX_ROUTE_CANDIDATES: u32 = 30;
+/// The basis points denominator
+const BPS_DENOMINATOR: u64 = 10_000;
+/// The share of the offer pool swapped by the probe which measures the spot price of non-XYK pairs
+const STABLE_PROBE_POOL_SHARE: u128 = 10_000;
 
 /// ## Description
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
@@ -42,19 +62,61 @@ pub fn instantiate(
     _env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
+) -> Result<Response<CustomMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    CONFIG.save(
-        deps.storage,
-        &Config {
-            astroport_factory: addr_validate_to_lower(deps.api, &msg.astroport_factory)?,
-        },
-    )?;
+    let config = Config {
+        astroport_factory: addr_validate_to_lower(deps.api, &msg.astroport_factory)?,
+        owner: addr_validate_to_lower(deps.api, &msg.owner)?,
+        max_hops: msg.max_hops.unwrap_or(DEFAULT_MAX_HOPS),
+        max_route_candidates: msg
+            .max_route_candidates
+            .unwrap_or(DEFAULT_MAX_ROUTE_CANDIDATES),
+        max_referral_commission_bps: msg
+            .max_referral_commission_bps
+            .unwrap_or(DEFAULT_MAX_REFERRAL_COMMISSION_BPS),
+    };
+
+    assert_route_search_limits(&config)?;
+    assert_max_referral_commission(&config)?;
+    CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::default())
 }
 
+/// ## Description
+/// Validates the limits of the route search. Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **config** is the object of type [`Config`].
+fn assert_route_search_limits(config: &Config) -> Result<(), ContractError> {
+    if config.max_hops == 0
+        || config.max_hops > MAX_ROUTE_HOPS
+        || config.max_route_candidates == 0
+        || config.max_route_candidates > MAX_ROUTE_CANDIDATES
+    {
+        return Err(ContractError::InvalidRouteSearchLimits {
+            max_hops: MAX_ROUTE_HOPS,
+            max_route_candidates: MAX_ROUTE_CANDIDATES,
+        });
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Validates the maximum referral commission. Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **config** is the object of type [`Config`].
+fn assert_max_referral_commission(config: &Config) -> Result<(), ContractError> {
+    if config.max_referral_commission_bps > MAX_REFERRAL_COMMISSION_BPS {
+        return Err(ContractError::ReferralCommissionTooHigh {
+            max_bps: MAX_REFERRAL_COMMISSION_BPS,
+        });
+    }
+
+    Ok(())
+}
+
 /// ## Description
 /// Available the execute messages of the contract.
 /// ## Params
@@ -70,14 +132,71 @@ pub fn instantiate(
 /// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
 /// it depending on the received template.
 ///
+/// * **ExecuteMsg::UpdateConfig {
+///             max_hops,
+///             max_route_candidates,
+///             max_referral_commission_bps,
+///         }** Updates the limits of the route search and the maximum referral commission.
+///
 /// * **ExecuteMsg::ExecuteSwapOperations {
 ///             operations,
 ///             minimum_receive,
-///             to
+///             to,
+///             referral
 ///         }** Performs swap operations with the specified parameters.
 ///
-/// * **ExecuteMsg::ExecuteSwapOperation { operation, to }** Execute swap operation.
-/// Swap all offer asset to ask asset.
+/// * **ExecuteMsg::ExecuteSplitSwap {
+///             routes,
+///             minimum_receive,
+///             to
+///         }** Splits the offer asset across several routes that end in the same asset.
+///
+/// * **ExecuteMsg::ExecuteSwapOperationsExactOut {
+///             operations,
+///             ask_amount,
+///             max_offer,
+///             to
+///         }** Buys the exact amount of the ask asset and refunds the unused offer amount.
+///
+/// * **ExecuteMsg::SwapAndProvide {
+///             operations_per_side,
+///             pair,
+///             min_lp_out,
+///             auto_stake
+///         }** Swaps the offer asset into both assets of the pair and provides them as liquidity.
+///
+/// * **ExecuteMsg::ExecuteSwapOperation { operation, to, amount }** Execute swap operation.
+/// Swap all offer asset (or the specified amount of it) to ask asset.
+///
+/// * **ExecuteMsg::DistributeReferralCommission {
+///             asset_info,
+///             prev_balance,
+///             referral,
+///             receiver
+///         }** Sends the referral commission from the swap output and the rest of it to the receiver.
+///
+/// * **ExecuteMsg::ProvideSwappedLiquidity {
+///             pair,
+///             prev_balances,
+///             min_lp_out,
+///             auto_stake,
+///             receiver
+///         }** Provides the assets received by the router as liquidity.
+///
+/// * **ExecuteMsg::SendLiquidity {
+///             liquidity_token,
+///             prev_balance,
+///             min_lp_out,
+///             auto_stake,
+///             receiver
+///         }** Sends the LP tokens received by the router to the receiver or stakes them in the generator.
+///
+/// * **ExecuteMsg::SwapWithdrawnLiquidity {
+///             prev_balances,
+///             operations,
+///             minimum_receive,
+///             receiver
+///         }** Swaps the assets withdrawn from the pair into one asset.
 ///
 /// * **ExecuteMsg::AssertMinimumReceive {
 ///             asset_info,
@@ -91,13 +210,25 @@ pub fn execute(
     env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
+) -> Result<Response<CustomMsg>, ContractError> {
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateConfig {
+            max_hops,
+            max_route_candidates,
+            max_referral_commission_bps,
+        } => execute_update_config(
+            deps,
+            info,
+            max_hops,
+            max_route_candidates,
+            max_referral_commission_bps,
+        ),
         ExecuteMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            referral,
         } => execute_swap_operations(
             deps,
             env,
@@ -106,9 +237,137 @@ pub fn execute(
             operations,
             minimum_receive,
             to,
+            referral,
         ),
-        ExecuteMsg::ExecuteSwapOperation { operation, to } => {
-            execute_swap_operation(deps, env, info, operation, to)
+        ExecuteMsg::ExecuteSplitSwap {
+            routes,
+            minimum_receive,
+            to,
+        } => {
+            assert_native_offer(&get_split_swap_offer(&routes)?, &info)?;
+
+            execute_split_swap(deps, env, info.sender, routes, minimum_receive, to)
+        }
+        ExecuteMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount,
+            max_offer,
+            to,
+        } => {
+            assert_native_offer(
+                &Asset {
+                    info: get_offer_asset_info(&operations)?,
+                    amount: max_offer,
+                },
+                &info,
+            )?;
+
+            execute_swap_operations_exact_out(
+                deps,
+                env,
+                info.sender,
+                operations,
+                ask_amount,
+                max_offer,
+                to,
+            )
+        }
+        ExecuteMsg::SwapAndProvide {
+            operations_per_side,
+            pair,
+            min_lp_out,
+            auto_stake,
+        } => {
+            assert_native_offer(&get_zap_offer(&operations_per_side)?, &info)?;
+
+            execute_swap_and_provide(
+                deps,
+                env,
+                info.sender,
+                operations_per_side,
+                pair,
+                min_lp_out,
+                auto_stake,
+            )
+        }
+        ExecuteMsg::ExecuteSwapOperation {
+            operation,
+            to,
+            amount,
+        } => execute_swap_operation(deps, env, info, operation, to, amount),
+        ExecuteMsg::DistributeReferralCommission {
+            asset_info,
+            prev_balance,
+            referral,
+            receiver,
+        } => {
+            let receiver = addr_validate_to_lower(deps.api, &receiver)?;
+            distribute_referral_commission(
+                deps,
+                env,
+                info,
+                asset_info,
+                prev_balance,
+                referral,
+                receiver,
+            )
+        }
+        ExecuteMsg::ProvideSwappedLiquidity {
+            pair,
+            prev_balances,
+            min_lp_out,
+            auto_stake,
+            receiver,
+        } => {
+            let pair = addr_validate_to_lower(deps.api, &pair)?;
+            let receiver = addr_validate_to_lower(deps.api, &receiver)?;
+            provide_swapped_liquidity(
+                deps,
+                env,
+                info,
+                pair,
+                prev_balances,
+                min_lp_out,
+                auto_stake,
+                receiver,
+            )
+        }
+        ExecuteMsg::SendLiquidity {
+            liquidity_token,
+            prev_balance,
+            min_lp_out,
+            auto_stake,
+            receiver,
+        } => {
+            let liquidity_token = addr_validate_to_lower(deps.api, &liquidity_token)?;
+            let receiver = addr_validate_to_lower(deps.api, &receiver)?;
+            send_liquidity(
+                deps,
+                env,
+                info,
+                liquidity_token,
+                prev_balance,
+                min_lp_out,
+                auto_stake,
+                receiver,
+            )
+        }
+        ExecuteMsg::SwapWithdrawnLiquidity {
+            prev_balances,
+            operations,
+            minimum_receive,
+            receiver,
+        } => {
+            let receiver = addr_validate_to_lower(deps.api, &receiver)?;
+            swap_withdrawn_liquidity(
+                deps,
+                env,
+                info,
+                prev_balances,
+                operations,
+                minimum_receive,
+                receiver,
+            )
         }
         ExecuteMsg::AssertMinimumReceive {
             asset_info,
@@ -125,6 +384,56 @@ pub fn execute(
     }
 }
 
+/// ## Description
+/// Updates the limits of the route search and the maximum referral commission. Returns an [`ContractError`]
+/// on failure or the following [`Config`] data will be updated if successful.
+///
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **max_hops** is an [`Option`] field of type [`u32`].
+///
+/// * **max_route_candidates** is an [`Option`] field of type [`u32`].
+///
+/// * **max_referral_commission_bps** is an [`Option`] field of type [`u64`].
+///
+/// ## Executor
+/// Only owner can execute it
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_hops: Option<u32>,
+    max_route_candidates: Option<u32>,
+    max_referral_commission_bps: Option<u64>,
+) -> Result<Response<CustomMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(max_hops) = max_hops {
+        config.max_hops = max_hops;
+    }
+
+    if let Some(max_route_candidates) = max_route_candidates {
+        config.max_route_candidates = max_route_candidates;
+    }
+
+    if let Some(max_referral_commission_bps) = max_referral_commission_bps {
+        config.max_referral_commission_bps = max_referral_commission_bps;
+    }
+
+    assert_route_search_limits(&config)?;
+    assert_max_referral_commission(&config)?;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "update_config"))
+}
+
 /// ## Description
 /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
 /// If the template is not found in the received message, then an [`ContractError`] is returned,
@@ -142,13 +451,14 @@ pub fn receive_cw20(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
+) -> Result<Response<CustomMsg>, ContractError> {
     let sender = addr_validate_to_lower(deps.api, &cw20_msg.sender)?;
     match from_binary(&cw20_msg.msg)? {
         Cw20HookMsg::ExecuteSwapOperations {
             operations,
             minimum_receive,
             to,
+            referral,
         } => {
             let to_addr = if let Some(to_addr) = to {
                 Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
@@ -164,14 +474,123 @@ pub fn receive_cw20(
                 operations,
                 minimum_receive,
                 to_addr,
+                referral,
             )
         }
+        Cw20HookMsg::ExecuteSplitSwap {
+            routes,
+            minimum_receive,
+            to,
+        } => {
+            let offer_asset = get_split_swap_offer(&routes)?;
+            if offer_asset.info
+                != (AssetInfo::Token {
+                    contract_addr: info.sender,
+                })
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if offer_asset.amount != cw20_msg.amount {
+                return Err(ContractError::InvalidSplitAmounts {
+                    total: offer_asset.amount,
+                    offer_amount: cw20_msg.amount,
+                });
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            execute_split_swap(deps, env, sender, routes, minimum_receive, to_addr)
+        }
+        Cw20HookMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount,
+            to,
+        } => {
+            if get_offer_asset_info(&operations)?
+                != (AssetInfo::Token {
+                    contract_addr: info.sender,
+                })
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(addr_validate_to_lower(deps.api, to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            execute_swap_operations_exact_out(
+                deps,
+                env,
+                sender,
+                operations,
+                ask_amount,
+                cw20_msg.amount,
+                to_addr,
+            )
+        }
+        Cw20HookMsg::SwapAndProvide {
+            operations_per_side,
+            pair,
+            min_lp_out,
+            auto_stake,
+        } => {
+            let offer_asset = get_zap_offer(&operations_per_side)?;
+            if offer_asset.info
+                != (AssetInfo::Token {
+                    contract_addr: info.sender,
+                })
+            {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if offer_asset.amount != cw20_msg.amount {
+                return Err(ContractError::InvalidSplitAmounts {
+                    total: offer_asset.amount,
+                    offer_amount: cw20_msg.amount,
+                });
+            }
+
+            execute_swap_and_provide(
+                deps,
+                env,
+                sender,
+                operations_per_side,
+                pair,
+                min_lp_out,
+                auto_stake,
+            )
+        }
+        Cw20HookMsg::WithdrawAndSwap {
+            pair,
+            operations,
+            minimum_receive,
+        } => execute_withdraw_and_swap(
+            deps,
+            env,
+            sender,
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount: cw20_msg.amount,
+            },
+            pair,
+            operations,
+            minimum_receive,
+        ),
     }
 }
 
 /// ## Description
 /// Performs swap operations with the specified parameters.
-/// Returns an [`ContractError`] on failureб otherwise returns [`Response`] with the specified messages of type [`TerraMsgWrapper`] to execute if the operation is successful.
+/// Returns an [`ContractError`] on failureб otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
 /// ## Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
@@ -186,6 +605,10 @@ pub fn receive_cw20(
 /// * **minimum_receive** is the object of type [`Option<Uint128>`]. Used to minimum amount assertion.
 ///
 /// * **to** is the object of type [`Option<Addr>`]. Sets the recipient of the swap operation.
+///
+/// * **referral** is the object of type [`Option<Referral>`]. Sets the referral which receives a
+/// commission from the output of the swap.
+#[allow(clippy::too_many_arguments)]
 pub fn execute_swap_operations(
     deps: DepsMut,
     env: Env,
@@ -194,7 +617,8 @@ pub fn execute_swap_operations(
     operations: Vec<SwapOperation>,
     minimum_receive: Option<Uint128>,
     to: Option<Addr>,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    referral: Option<Referral>,
+) -> Result<Response<CustomMsg>, ContractError> {
     let operations_len = operations.len();
     if operations_len == 0 {
         return Err(ContractError::MustProvideOperations {});
@@ -215,95 +639,500 @@ pub fn execute_swap_operations(
 
     let target_asset_info = operations.last().unwrap().get_target_asset_info();
 
-    let mut operation_index = 0;
-    let mut messages: Vec<CosmosMsg<TerraMsgWrapper>> = operations
-        .into_iter()
-        .map(|op| {
-            operation_index += 1;
-            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: env.contract.address.to_string(),
-                funds: vec![],
-                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
-                    operation: op,
-                    to: if operation_index == operations_len {
-                        Some(to.to_string())
-                    } else {
-                        None
-                    },
-                })?,
-            }))
-        })
-        .collect::<StdResult<Vec<CosmosMsg<TerraMsgWrapper>>>>()?;
+    let mut attributes = vec![];
+    let mut messages = if let Some(referral) = referral {
+        let referral = validate_referral(deps.as_ref(), referral)?;
+        attributes.push(("referral", referral.address.clone()));
 
-    // Execute minimum amount assertion
-    if let Some(minimum_receive) = minimum_receive {
-        let receiver_balance = target_asset_info.query_pool(&deps.querier, to.clone())?;
+        // The output of the swap is received by the router to take the referral commission
+        let router_balance =
+            target_asset_info.query_pool(&deps.querier, env.contract.address.clone())?;
+        let mut messages = swap_operation_messages(&env, operations, None, &env.contract.address)?;
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
             contract_addr: env.contract.address.to_string(),
             funds: vec![],
-            msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
-                asset_info: target_asset_info,
-                prev_balance: receiver_balance,
-                minimum_receive,
+            msg: to_binary(&ExecuteMsg::DistributeReferralCommission {
+                asset_info: target_asset_info.clone(),
+                prev_balance: router_balance,
+                referral,
                 receiver: to.to_string(),
             })?,
         }));
+        messages
+    } else {
+        swap_operation_messages(&env, operations, None, &to)?
+    };
+
+    // Execute minimum amount assertion
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(assert_minimum_receive_message(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
     }
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
 }
 
 /// ## Description
-/// Performs minimum receive amount assertion.
-/// Returns an [`ContractError`] on failure, otherwise returns default object of type [`Response`]
-/// if the operation is successful.
+/// Validates the referral address and checks the referral commission against the config.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Referral`] with the normalized address.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **referral** is the object of type [`Referral`].
+fn validate_referral(deps: Deps, referral: Referral) -> Result<Referral, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if referral.commission_bps > config.max_referral_commission_bps {
+        return Err(ContractError::ReferralCommissionTooHigh {
+            max_bps: config.max_referral_commission_bps,
+        });
+    }
+
+    Ok(Referral {
+        address: addr_validate_to_lower(deps.api, &referral.address)?.to_string(),
+        commission_bps: referral.commission_bps,
+    })
+}
+
+/// ## Description
+/// Sends the referral commission from the swap output received by the router and the rest of the
+/// output to the receiver. Returns an [`ContractError`] on failure, otherwise returns the [`Response`]
+/// with the specified attributes if the operation was successful.
 /// ## Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
 /// * **asset_info** is the object of type [`AssetInfo`].
 ///
-/// * **prev_balance** is the object of type [`Uint128`].
+/// * **prev_balance** is the object of type [`Uint128`]. Sets the balance of the router before the swap.
 ///
-/// * **minimum_receive** is the object of type [`Uint128`].
+/// * **referral** is the object of type [`Referral`].
 ///
-/// * **receiver** is the object of type [`Addr`]. Sets recipient for which the receive minimum amount assertion will be performed.
-fn assert_minimum_receive(
-    deps: Deps,
+/// * **receiver** is the object of type [`Addr`].
+fn distribute_referral_commission(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
     asset_info: AssetInfo,
     prev_balance: Uint128,
-    minimum_receive: Uint128,
+    referral: Referral,
     receiver: Addr,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
-    asset_info.check(deps.api)?;
-    let receiver_balance = asset_info.query_pool(&deps.querier, receiver)?;
-    let swap_amount = receiver_balance.checked_sub(prev_balance)?;
+) -> Result<Response<CustomMsg>, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    if swap_amount < minimum_receive {
-        return Err(ContractError::AssertionMinimumReceive {
-            receive: minimum_receive,
-            amount: swap_amount,
-        });
+    let balance = asset_info.query_pool(&deps.querier, env.contract.address.clone())?;
+    let swap_amount = balance.checked_sub(prev_balance)?;
+    let commission_amount = swap_amount.multiply_ratio(referral.commission_bps, BPS_DENOMINATOR);
+    let return_amount = swap_amount.checked_sub(commission_amount)?;
+
+    let mut messages = vec![];
+    if !commission_amount.is_zero() {
+        messages.push(transfer_asset_message(
+            deps.as_ref(),
+            Asset {
+                info: asset_info.clone(),
+                amount: commission_amount,
+            },
+            &Addr::unchecked(referral.address.as_str()),
+        )?);
     }
 
-    Ok(Response::default())
+    if !return_amount.is_zero() {
+        messages.push(transfer_asset_message(
+            deps.as_ref(),
+            Asset {
+                info: asset_info,
+                amount: return_amount,
+            },
+            &receiver,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "distribute_referral_commission"),
+        ("referral", referral.address.as_str()),
+        ("commission_amount", &commission_amount.to_string()),
+        ("return_amount", &return_amount.to_string()),
+    ]))
 }
 
 /// ## Description
-/// Available the query messages of the contract.
+/// Checks that the offer asset is a native token sent along with the message. Cw20 offer assets
+/// must be sent via the [`Cw20HookMsg`], otherwise the router would spend its own balance.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
 /// ## Params
-/// * **deps** is the object of type [`Deps`].
+/// * **offer_asset** is the object of type [`Asset`].
 ///
-/// * **_env** is the object of type [`Env`].
+/// * **info** is the object of type [`MessageInfo`].
+fn assert_native_offer(offer_asset: &Asset, info: &MessageInfo) -> Result<(), ContractError> {
+    if !offer_asset.is_native_token() {
+        return Err(ContractError::Cw20OfferNotAllowed {});
+    }
+    offer_asset.assert_sent_native_token_balance(info)?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Performs swap operations split across several routes that start with the same offer asset and
+/// end with the same ask asset. The routes are executed one by one and the minimum receive amount
+/// assertion is performed for all of them together.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
 ///
-/// * **msg** is the object of type [`QueryMsg`].
+/// * **env** is the object of type [`Env`].
+///
+/// * **sender** is the object of type [`Addr`]. Sets the default recipient of the swap operations.
+///
+/// * **routes** is a vector that contains the routes of [`SwapOperation`] with the offer amount for each of them.
+///
+/// * **minimum_receive** is the object of type [`Option<Uint128>`]. Used to minimum amount assertion.
+///
+/// * **to** is the object of type [`Option<Addr>`]. Sets the recipient of the swap operations.
+pub fn execute_split_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    routes: Vec<(Vec<SwapOperation>, Uint128)>,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+) -> Result<Response<CustomMsg>, ContractError> {
+    assert_split_routes(deps.api, &routes)?;
+
+    let to = if let Some(to) = to {
+        addr_validate_to_lower(deps.api, to.as_str())?
+    } else {
+        sender
+    };
+
+    let target_asset_info = routes[0].0.last().unwrap().get_target_asset_info();
+
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    for (operations, amount) in routes {
+        messages.extend(swap_operation_messages(
+            &env,
+            operations,
+            Some(amount),
+            &to,
+        )?);
+    }
+
+    // Execute minimum amount assertion
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(assert_minimum_receive_message(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "split_swap"))
+}
+
+/// ## Description
+/// Performs swap operations which buy the exact amount of the ask asset. The offer amount of every
+/// operation is calculated by the reverse simulation of the operations, and the rest of the offer
+/// asset is refunded to the sender.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **sender** is the object of type [`Addr`]. Sets the default recipient of the swap operations and the recipient of the refund.
+///
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+///
+/// * **ask_amount** is the object of type [`Uint128`]. Sets the exact amount of the ask asset to receive.
+///
+/// * **max_offer** is the object of type [`Uint128`]. Sets the maximum amount of the offer asset to spend.
+///
+/// * **to** is the object of type [`Option<Addr>`]. Sets the recipient of the swap operations.
+pub fn execute_swap_operations_exact_out(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    operations: Vec<SwapOperation>,
+    ask_amount: Uint128,
+    max_offer: Uint128,
+    to: Option<Addr>,
+) -> Result<Response<CustomMsg>, ContractError> {
+    let offer_asset_info = get_offer_asset_info(&operations)?;
+
+    // The rest of the offer asset is refunded after the operations, so intermediate operations
+    // must not touch it
+    if operations[1..]
+        .iter()
+        .any(|op| op.get_offer_asset_info() == offer_asset_info)
+    {
+        return Err(StdError::generic_err(
+            "invalid operations; the offer asset is swapped more than once",
+        )
+        .into());
+    }
+
+    let offer_amounts =
+        reverse_simulate_offer_amounts(deps.as_ref(), ask_amount, operations.clone())?;
+    let offer_amount = offer_amounts[0];
+    if offer_amount > max_offer {
+        return Err(ContractError::AssertionMaximumOffer {
+            max_offer,
+            amount: offer_amount,
+        });
+    }
+
+    let to = if let Some(to) = to {
+        addr_validate_to_lower(deps.api, to.as_str())?
+    } else {
+        sender.clone()
+    };
+
+    let target_asset_info = operations.last().unwrap().get_target_asset_info();
+
+    // Every operation swaps only the simulated amount, so the surplus of an intermediate operation
+    // doesn't increase the output
+    let operations_len = operations.len();
+    let mut messages = operations
+        .into_iter()
+        .zip(offer_amounts)
+        .enumerate()
+        .map(|(operation_index, (op, amount))| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: op,
+                    to: if operation_index == operations_len - 1 {
+                        Some(to.to_string())
+                    } else {
+                        None
+                    },
+                    amount: Some(amount),
+                })?,
+            }))
+        })
+        .collect::<StdResult<Vec<CosmosMsg<CustomMsg>>>>()?;
+    messages.push(assert_minimum_receive_message(
+        deps.as_ref(),
+        &env,
+        target_asset_info,
+        ask_amount,
+        &to,
+    )?);
+
+    let refund_amount = max_offer.checked_sub(offer_amount)?;
+    if !refund_amount.is_zero() {
+        messages.push(transfer_asset_message(
+            deps.as_ref(),
+            Asset {
+                info: offer_asset_info,
+                amount: refund_amount,
+            },
+            &sender,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap_exact_out"),
+        ("offer_amount", &offer_amount.to_string()),
+        ("refund_amount", &refund_amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Returns a message of type [`CosmosMsg`] which sends the asset to the recipient.
+/// The tax is deducted from native tokens.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **asset** is the object of type [`Asset`].
+///
+/// * **recipient** is the object of type [`Addr`].
+pub(crate) fn transfer_asset_message(
+    deps: Deps,
+    asset: Asset,
+    recipient: &Addr,
+) -> StdResult<CosmosMsg<CustomMsg>> {
+    match &asset.info {
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: asset.amount,
+            })?,
+        })),
+        AssetInfo::NativeToken { .. } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![asset.deduct_tax(&deps.querier)?],
+        })),
+    }
+}
+
+/// ## Description
+/// Returns messages of type [`CosmosMsg`] which execute the swap operations one by one.
+/// Only the last operation sends the ask asset to the recipient.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+///
+/// * **amount** is the object of type [`Option<Uint128>`]. Sets the amount of the offer asset for the first operation.
+///
+/// * **to** is the object of type [`Addr`]. Sets the recipient of the swap operations.
+pub(crate) fn swap_operation_messages(
+    env: &Env,
+    operations: Vec<SwapOperation>,
+    amount: Option<Uint128>,
+    to: &Addr,
+) -> StdResult<Vec<CosmosMsg<CustomMsg>>> {
+    let operations_len = operations.len();
+
+    operations
+        .into_iter()
+        .enumerate()
+        .map(|(operation_index, op)| {
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: env.contract.address.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: op,
+                    to: if operation_index == operations_len - 1 {
+                        Some(to.to_string())
+                    } else {
+                        None
+                    },
+                    amount: if operation_index == 0 { amount } else { None },
+                })?,
+            }))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns a message of type [`CosmosMsg`] which performs minimum receive amount assertion
+/// against the current balance of the recipient.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **asset_info** is the object of type [`AssetInfo`].
+///
+/// * **minimum_receive** is the object of type [`Uint128`].
+///
+/// * **to** is the object of type [`Addr`]. Sets the recipient of the swap operations.
+pub(crate) fn assert_minimum_receive_message(
+    deps: Deps,
+    env: &Env,
+    asset_info: AssetInfo,
+    minimum_receive: Uint128,
+    to: &Addr,
+) -> StdResult<CosmosMsg<CustomMsg>> {
+    let receiver_balance = asset_info.query_pool(&deps.querier, to.clone())?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+            asset_info,
+            prev_balance: receiver_balance,
+            minimum_receive,
+            receiver: to.to_string(),
+        })?,
+    }))
+}
+
+/// ## Description
+/// Performs minimum receive amount assertion.
+/// Returns an [`ContractError`] on failure, otherwise returns default object of type [`Response`]
+/// if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **asset_info** is the object of type [`AssetInfo`].
+///
+/// * **prev_balance** is the object of type [`Uint128`].
+///
+/// * **minimum_receive** is the object of type [`Uint128`].
+///
+/// * **receiver** is the object of type [`Addr`]. Sets recipient for which the receive minimum amount assertion will be performed.
+fn assert_minimum_receive(
+    deps: Deps,
+    asset_info: AssetInfo,
+    prev_balance: Uint128,
+    minimum_receive: Uint128,
+    receiver: Addr,
+) -> Result<Response<CustomMsg>, ContractError> {
+    asset_info.check(deps.api)?;
+    let receiver_balance = asset_info.query_pool(&deps.querier, receiver)?;
+    let swap_amount = receiver_balance.checked_sub(prev_balance)?;
+
+    if swap_amount < minimum_receive {
+        return Err(ContractError::AssertionMinimumReceive {
+            receive: minimum_receive,
+            amount: swap_amount,
+        });
+    }
+
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Available the query messages of the contract.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`QueryMsg`].
 ///
 /// ## Queries
 /// * **QueryMsg::Config {}** Returns information about the controls settings in a [`ConfigResponse`] object.
 /// * **QueryMsg::SimulateSwapOperations {
 ///             offer_amount,
 ///             operations,
+///             referral,
 ///         }** Returns information about the simulation of the swap operations in a
 /// [`SimulateSwapOperationsResponse`] object.
+///
+/// * **QueryMsg::SimulateSwapOperationsDetailed {
+///             offer_amount,
+///             operations,
+///         }** Returns the simulation of the swap operations with the breakdown for each operation
+/// in a [`SimulateSwapOperationsDetailedResponse`] object.
+///
+/// * **QueryMsg::SimulateReverseSwapOperations {
+///             ask_amount,
+///             operations,
+///         }** Returns the offer amount required to receive the ask amount through the swap
+/// operations in a [`SimulateReverseSwapOperationsResponse`] object.
+///
+/// * **QueryMsg::SimulateSplitSwap { routes }** Returns information about the simulation of
+/// the swaps split across several routes in a [`SimulateSplitSwapResponse`] object.
+///
+/// * **QueryMsg::FindBestRoute {
+///             offer_asset,
+///             ask_asset_info,
+///             max_hops,
+///         }** Returns the route through the factory pairs with the best output in a
+/// [`FindBestRouteResponse`](astroport::router::FindBestRouteResponse) object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -311,10 +1140,39 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
         QueryMsg::SimulateSwapOperations {
             offer_amount,
             operations,
+            referral,
         } => Ok(to_binary(&simulate_swap_operations(
             deps,
             offer_amount,
             operations,
+            referral,
+        )?)?),
+        QueryMsg::SimulateSwapOperationsDetailed {
+            offer_amount,
+            operations,
+        } => Ok(to_binary(&simulate_swap_operations_detailed(
+            deps,
+            offer_amount,
+            operations,
+        )?)?),
+        QueryMsg::SimulateReverseSwapOperations {
+            ask_amount,
+            operations,
+        } => Ok(to_binary(&simulate_reverse_swap_operations(
+            deps, ask_amount, operations,
+        )?)?),
+        QueryMsg::SimulateSplitSwap { routes } => {
+            Ok(to_binary(&simulate_split_swap(deps, routes)?)?)
+        }
+        QueryMsg::FindBestRoute {
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+        } => Ok(to_binary(&find_best_route(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            max_hops,
         )?)?),
     }
 }
@@ -328,21 +1186,53 @@ pub fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let state = CONFIG.load(deps.storage)?;
     let resp = ConfigResponse {
         astroport_factory: state.astroport_factory.into_string(),
+        owner: state.owner.into_string(),
+        max_hops: state.max_hops,
+        max_route_candidates: state.max_route_candidates,
+        max_referral_commission_bps: state.max_referral_commission_bps,
     };
 
     Ok(resp)
 }
 
 /// ## Description
-/// Used for migration of contract. Returns the default object of type [`Response`].
+/// Used for migration of contract. Fills the fields missing in the stored config: the owner is taken
+/// from the message and the limits get the default values. Returns a [`ContractError`] on failure,
+/// otherwise returns the default object of type [`Response`].
 /// ## Params
-/// * **_deps** is the object of type [`DepsMut`].
+/// * **deps** is the object of type [`DepsMut`].
 ///
 /// * **_env** is the object of type [`Env`].
 ///
-/// * **_msg** is the object of type [`MigrateMsg`].
+/// * **msg** is the object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+
+    let owner = match msg.owner {
+        Some(owner) => addr_validate_to_lower(deps.api, &owner)?,
+        None => legacy_config
+            .owner
+            .ok_or(ContractError::MigrationOwnerRequired {})?,
+    };
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            astroport_factory: legacy_config.astroport_factory,
+            owner,
+            max_hops: legacy_config.max_hops.unwrap_or(DEFAULT_MAX_HOPS),
+            max_route_candidates: legacy_config
+                .max_route_candidates
+                .unwrap_or(DEFAULT_MAX_ROUTE_CANDIDATES),
+            max_referral_commission_bps: legacy_config
+                .max_referral_commission_bps
+                .unwrap_or(DEFAULT_MAX_REFERRAL_COMMISSION_BPS),
+        },
+    )?;
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
@@ -355,14 +1245,17 @@ pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Respons
 /// * **offer_amount** is the object of type [`Uint128`]. Sets a offer amount.
 ///
 /// * **operations** is a vector that contains object of type [`SwapOperation`].
+///
+/// * **referral** is the object of type [`Option<Referral>`]. The referral commission is deducted
+/// from the output of the swap.
 fn simulate_swap_operations(
     deps: Deps,
     offer_amount: Uint128,
     operations: Vec<SwapOperation>,
+    referral: Option<Referral>,
 ) -> Result<SimulateSwapOperationsResponse, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let astroport_factory = config.astroport_factory;
-    let terra_querier = TerraQuerier::new(&deps.querier);
 
     let operations_len = operations.len();
     if operations_len == 0 {
@@ -375,19 +1268,20 @@ fn simulate_swap_operations(
 
     assert_operations(deps.api, &operations)?;
 
-    let mut operation_index = 0;
-    let mut offer_amount = offer_amount;
-    for operation in operations.into_iter() {
-        operation_index += 1;
+    let target_asset_info = operations.last().unwrap().get_target_asset_info();
 
+    let mut offer_amount = offer_amount;
+    let mut operations = operations.into_iter().peekable();
+    while let Some(operation) = operations.next() {
         match operation {
+            #[cfg(feature = "terra")]
             SwapOperation::NativeSwap {
                 offer_denom,
                 ask_denom,
             } => {
                 // Deduct tax before query simulation
                 // because last swap is swap_send
-                if operation_index == operations_len {
+                if operations.peek().is_none() {
                     offer_amount = offer_amount.checked_sub(compute_tax(
                         deps,
                         offer_amount,
@@ -395,7 +1289,7 @@ fn simulate_swap_operations(
                     )?)?;
                 }
 
-                let res: SwapResponse = terra_querier.query_swap(
+                let res: SwapResponse = TerraQuerier::new(&deps.querier).query_swap(
                     Coin {
                         denom: offer_denom,
                         amount: offer_amount,
@@ -415,68 +1309,460 @@ fn simulate_swap_operations(
                     &[offer_asset_info.clone(), ask_asset_info.clone()],
                 )?;
 
-                // Deduct tax before querying simulation
-                if let AssetInfo::NativeToken { denom } = offer_asset_info.clone() {
-                    offer_amount =
-                        offer_amount.checked_sub(compute_tax(deps, offer_amount, denom)?)?;
-                }
+                offer_amount = simulate_astro_swap(
+                    deps,
+                    pair_info.contract_addr,
+                    offer_asset_info,
+                    &ask_asset_info,
+                    offer_amount,
+                )?
+                .return_amount;
+            }
+        }
+    }
 
-                let mut res: SimulationResponse =
-                    deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-                        contract_addr: pair_info.contract_addr.to_string(),
-                        msg: to_binary(&PairQueryMsg::Simulation {
-                            offer_asset: Asset {
-                                info: offer_asset_info,
-                                amount: offer_amount,
-                            },
-                        })?,
-                    }))?;
-
-                // Deduct tax after querying simulation
-                if let AssetInfo::NativeToken { denom } = ask_asset_info.clone() {
-                    res.return_amount = res.return_amount.checked_sub(compute_tax(
-                        deps,
-                        res.return_amount,
-                        denom,
-                    )?)?;
-                }
+    let mut referral_amount = Uint128::zero();
+    if let Some(referral) = referral {
+        let referral = validate_referral(deps, referral)?;
+        referral_amount = offer_amount.multiply_ratio(referral.commission_bps, BPS_DENOMINATOR);
+        offer_amount = offer_amount.checked_sub(referral_amount)?;
 
-                offer_amount = res.return_amount;
-            }
+        // The rest of the output is sent by the router to the receiver once again
+        if let AssetInfo::NativeToken { denom } = target_asset_info {
+            offer_amount = offer_amount.checked_sub(compute_tax(deps, offer_amount, denom)?)?;
         }
     }
 
     Ok(SimulateSwapOperationsResponse {
         amount: offer_amount,
+        referral_amount,
     })
 }
 
 /// ## Description
-/// Validates assets in operations. Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// Returns an [`ContractError`] on failure, otherwise returns the simulation of the swap operations
+/// with the pair, spread, commission and price impact of each operation in a
+/// [`SimulateSwapOperationsDetailedResponse`] object. The price impact is measured against the spot
+/// price of the pools, see [`query_spot_rate`]. The total spread and commission are converted to the
+/// ask asset at the rates of the following operations.
 /// ## Params
-/// * **api** is the object of type [`Api`].
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **offer_amount** is the object of type [`Uint128`]. Sets a offer amount.
 ///
 /// * **operations** is a vector that contains object of type [`SwapOperation`].
-fn assert_operations(api: &dyn Api, operations: &[SwapOperation]) -> Result<(), ContractError> {
-    let mut ask_asset_map: HashMap<String, bool> = HashMap::new();
-    for operation in operations.iter() {
-        let (offer_asset, ask_asset) = match operation {
+fn simulate_swap_operations_detailed(
+    deps: Deps,
+    offer_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateSwapOperationsDetailedResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let astroport_factory = config.astroport_factory;
+
+    let operations_len = operations.len();
+    if operations_len == 0 {
+        return Err(ContractError::MustProvideOperations {});
+    }
+
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    assert_operations(deps.api, &operations)?;
+
+    let mut amount = offer_amount;
+    let mut spot_amount = offer_amount;
+    let mut total_spread_amount = Uint128::zero();
+    let mut total_commission_amount = Uint128::zero();
+    let mut simulations: Vec<SwapOperationSimulation> = vec![];
+    let mut operations = operations.into_iter().peekable();
+    while let Some(operation) = operations.next() {
+        let simulation = match operation.clone() {
+            #[cfg(feature = "terra")]
             SwapOperation::NativeSwap {
                 offer_denom,
                 ask_denom,
-            } => (
-                AssetInfo::NativeToken {
-                    denom: offer_denom.clone(),
-                },
-                AssetInfo::NativeToken {
-                    denom: ask_denom.clone(),
-                },
-            ),
+            } => {
+                let mut swap_amount = amount;
+                // Deduct tax before query simulation
+                // because last swap is swap_send
+                if operations.peek().is_none() {
+                    swap_amount = swap_amount.checked_sub(compute_tax(
+                        deps,
+                        swap_amount,
+                        offer_denom.clone(),
+                    )?)?;
+                }
+
+                let res: SwapResponse = TerraQuerier::new(&deps.querier).query_swap(
+                    Coin {
+                        denom: offer_denom,
+                        amount: swap_amount,
+                    },
+                    ask_denom,
+                )?;
+
+                // The market swap has no pool, so its rate is taken as the spot price
+                if !amount.is_zero() {
+                    spot_amount = spot_amount.multiply_ratio(res.receive.amount, amount);
+                }
+
+                SwapOperationSimulation {
+                    operation,
+                    pair_contract: None,
+                    pair_type: None,
+                    offer_amount: amount,
+                    return_amount: res.receive.amount,
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                    price_impact: Decimal::zero(),
+                }
+            }
             SwapOperation::AstroSwap {
                 offer_asset_info,
                 ask_asset_info,
-            } => (offer_asset_info.clone(), ask_asset_info.clone()),
+            } => {
+                let pair_info: PairInfo = query_pair_info(
+                    &deps.querier,
+                    astroport_factory.clone(),
+                    &[offer_asset_info.clone(), ask_asset_info.clone()],
+                )?;
+
+                let res = simulate_astro_swap(
+                    deps,
+                    pair_info.contract_addr.clone(),
+                    offer_asset_info.clone(),
+                    &ask_asset_info,
+                    amount,
+                )?;
+
+                let mut price_impact = Decimal::zero();
+                if let Some((ask_rate, offer_rate)) =
+                    query_spot_rate(deps, &pair_info, &offer_asset_info)?
+                {
+                    spot_amount = spot_amount.multiply_ratio(ask_rate, offer_rate);
+                    price_impact = compute_price_impact(
+                        res.return_amount,
+                        amount.multiply_ratio(ask_rate, offer_rate),
+                    );
+                }
+
+                SwapOperationSimulation {
+                    operation,
+                    pair_contract: Some(pair_info.contract_addr),
+                    pair_type: Some(pair_info.pair_type),
+                    offer_amount: amount,
+                    return_amount: res.return_amount,
+                    spread_amount: res.spread_amount,
+                    commission_amount: res.commission_amount,
+                    price_impact,
+                }
+            }
         };
+
+        // Convert the totals of the previous operations to the ask asset of this one
+        if !simulation.offer_amount.is_zero() {
+            total_spread_amount = total_spread_amount
+                .multiply_ratio(simulation.return_amount, simulation.offer_amount);
+            total_commission_amount = total_commission_amount
+                .multiply_ratio(simulation.return_amount, simulation.offer_amount);
+        }
+        total_spread_amount = total_spread_amount.checked_add(simulation.spread_amount)?;
+        total_commission_amount =
+            total_commission_amount.checked_add(simulation.commission_amount)?;
+
+        amount = simulation.return_amount;
+        simulations.push(simulation);
+    }
+
+    Ok(SimulateSwapOperationsDetailedResponse {
+        amount,
+        spot_amount,
+        price_impact: compute_price_impact(amount, spot_amount),
+        total_spread_amount,
+        total_commission_amount,
+        operations: simulations,
+    })
+}
+
+/// ## Description
+/// Returns the spot rate of the pair as the ask and offer amounts, or [`None`] if the offer pool is
+/// empty. The spot rate of XYK pairs is the ratio of the pool reserves. The reserves don't give the
+/// price of stable and custom pairs, so their spot rate is measured by the simulation of a swap of a
+/// tiny share of the offer pool, with the commission added back.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **pair_info** is the object of type [`PairInfo`].
+///
+/// * **offer_asset_info** is the object of type [`AssetInfo`].
+fn query_spot_rate(
+    deps: Deps,
+    pair_info: &PairInfo,
+    offer_asset_info: &AssetInfo,
+) -> StdResult<Option<(Uint128, Uint128)>> {
+    let pools = pair_info.query_pools(&deps.querier, pair_info.contract_addr.clone())?;
+    let (offer_pool, ask_pool) = if pools[0].info.equal(offer_asset_info) {
+        (pools[0].amount, pools[1].amount)
+    } else {
+        (pools[1].amount, pools[0].amount)
+    };
+
+    if offer_pool.is_zero() {
+        return Ok(None);
+    }
+
+    if !matches!(pair_info.pair_type, PairType::Xyk {}) {
+        let probe_amount = offer_pool.multiply_ratio(1u128, STABLE_PROBE_POOL_SHARE);
+        if !probe_amount.is_zero() {
+            let res = simulate(
+                &deps.querier,
+                pair_info.contract_addr.clone(),
+                &Asset {
+                    info: offer_asset_info.clone(),
+                    amount: probe_amount,
+                },
+            )?;
+            return Ok(Some((
+                res.return_amount.checked_add(res.commission_amount)?,
+                probe_amount,
+            )));
+        }
+    }
+
+    Ok(Some((ask_pool, offer_pool)))
+}
+
+/// ## Description
+/// Returns the price impact of receiving the return amount instead of the amount at the spot price.
+/// The price impact is zero if the return amount isn't less than the spot amount.
+/// ## Params
+/// * **return_amount** is the object of type [`Uint128`].
+///
+/// * **spot_amount** is the object of type [`Uint128`].
+fn compute_price_impact(return_amount: Uint128, spot_amount: Uint128) -> Decimal {
+    if spot_amount.is_zero() || return_amount >= spot_amount {
+        Decimal::zero()
+    } else {
+        Decimal::one() - Decimal::from_ratio(return_amount, spot_amount)
+    }
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the offer amount required to receive
+/// the ask amount through the swap operations in a [`SimulateReverseSwapOperationsResponse`] object.
+/// The operations are simulated backwards from the last one. Native swaps can't be reverse simulated.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **ask_amount** is the object of type [`Uint128`]. Sets the ask amount of the last operation.
+///
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+fn simulate_reverse_swap_operations(
+    deps: Deps,
+    ask_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<SimulateReverseSwapOperationsResponse, ContractError> {
+    let amount = reverse_simulate_offer_amounts(deps, ask_amount, operations)?[0];
+
+    Ok(SimulateReverseSwapOperationsResponse { amount })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the offer amount of every swap
+/// operation required to receive the ask amount through the operations. The operations are
+/// simulated backwards from the last one. Native swaps can't be reverse simulated.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **ask_amount** is the object of type [`Uint128`]. Sets the ask amount of the last operation.
+///
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+fn reverse_simulate_offer_amounts(
+    deps: Deps,
+    ask_amount: Uint128,
+    operations: Vec<SwapOperation>,
+) -> Result<Vec<Uint128>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let astroport_factory = config.astroport_factory;
+
+    let operations_len = operations.len();
+    if operations_len == 0 {
+        return Err(ContractError::MustProvideOperations {});
+    }
+
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    assert_operations(deps.api, &operations)?;
+
+    let mut offer_amounts = Vec::with_capacity(operations_len);
+    let mut amount = ask_amount;
+    for operation in operations.into_iter().rev() {
+        match operation {
+            #[cfg(feature = "terra")]
+            SwapOperation::NativeSwap { .. } => {
+                return Err(ContractError::ReverseNativeSwapNotSupported {})
+            }
+            SwapOperation::AstroSwap {
+                offer_asset_info,
+                ask_asset_info,
+            } => {
+                let pair_info: PairInfo = query_pair_info(
+                    &deps.querier,
+                    astroport_factory.clone(),
+                    &[offer_asset_info.clone(), ask_asset_info.clone()],
+                )?;
+
+                amount = reverse_simulate_astro_swap(
+                    deps,
+                    pair_info.contract_addr,
+                    &offer_asset_info,
+                    ask_asset_info,
+                    amount,
+                )?
+                .offer_amount;
+            }
+        }
+        offer_amounts.push(amount);
+    }
+    offer_amounts.reverse();
+
+    Ok(offer_amounts)
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the simulation of
+/// the swaps split across several routes in a [`SimulateSplitSwapResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **routes** is a vector that contains the routes of [`SwapOperation`] with the offer amount for each of them.
+fn simulate_split_swap(
+    deps: Deps,
+    routes: Vec<(Vec<SwapOperation>, Uint128)>,
+) -> Result<SimulateSplitSwapResponse, ContractError> {
+    assert_split_routes(deps.api, &routes)?;
+
+    let route_amounts = routes
+        .into_iter()
+        .map(|(operations, offer_amount)| {
+            Ok(simulate_swap_operations(deps, offer_amount, operations, None)?.amount)
+        })
+        .collect::<Result<Vec<Uint128>, ContractError>>()?;
+
+    let amount = route_amounts
+        .iter()
+        .try_fold(Uint128::zero(), |acc, amount| acc.checked_add(*amount))?;
+
+    Ok(SimulateSplitSwapResponse {
+        amount,
+        route_amounts,
+    })
+}
+
+/// ## Description
+/// Returns the offer asset info of the first swap operation.
+/// Returns an [`ContractError`] if no operations are provided.
+/// ## Params
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+fn get_offer_asset_info(operations: &[SwapOperation]) -> Result<AssetInfo, ContractError> {
+    operations
+        .first()
+        .map(|operation| operation.get_offer_asset_info())
+        .ok_or(ContractError::MustProvideOperations {})
+}
+
+/// ## Description
+/// Returns the offer asset of the split swap with the total amount of all routes.
+/// Returns an [`ContractError`] if no routes are provided.
+/// ## Params
+/// * **routes** is a vector that contains the routes of [`SwapOperation`] with the offer amount for each of them.
+fn get_split_swap_offer(routes: &[(Vec<SwapOperation>, Uint128)]) -> Result<Asset, ContractError> {
+    let info = routes
+        .first()
+        .and_then(|(operations, _)| operations.first())
+        .map(|operation| operation.get_offer_asset_info())
+        .ok_or(ContractError::MustProvideRoutes {})?;
+
+    let amount = routes
+        .iter()
+        .try_fold(Uint128::zero(), |acc, (_, amount)| acc.checked_add(*amount))?;
+
+    Ok(Asset { info, amount })
+}
+
+/// ## Description
+/// Validates the routes of the split swap. Every route must be valid on its own, start with the
+/// same offer asset, end with the same ask asset and never swap back into the offer asset.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **api** is the object of type [`Api`].
+///
+/// * **routes** is a vector that contains the routes of [`SwapOperation`] with the offer amount for each of them.
+fn assert_split_routes(
+    api: &dyn Api,
+    routes: &[(Vec<SwapOperation>, Uint128)],
+) -> Result<(), ContractError> {
+    if routes.is_empty() {
+        return Err(ContractError::MustProvideRoutes {});
+    }
+
+    let operations_len: usize = routes.iter().map(|(operations, _)| operations.len()).sum();
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    let mut offer_asset_info: Option<AssetInfo> = None;
+    let mut target_asset_info: Option<AssetInfo> = None;
+    for (operations, amount) in routes {
+        if operations.is_empty() {
+            return Err(ContractError::MustProvideOperations {});
+        }
+
+        if amount.is_zero() {
+            return Err(StdError::generic_err("Route amount must be greater than zero").into());
+        }
+
+        assert_operations(api, operations)?;
+
+        let route_offer = operations[0].get_offer_asset_info();
+        let route_target = operations.last().unwrap().get_target_asset_info();
+
+        // Intermediate operations swap the whole balance of the contract, so they must not touch
+        // the offer asset of the remaining routes
+        if operations[1..]
+            .iter()
+            .any(|op| op.get_offer_asset_info() == route_offer)
+        {
+            return Err(ContractError::InvalidSplitRoutes {});
+        }
+
+        if *offer_asset_info.get_or_insert_with(|| route_offer.clone()) != route_offer
+            || *target_asset_info.get_or_insert_with(|| route_target.clone()) != route_target
+        {
+            return Err(ContractError::InvalidSplitRoutes {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Validates assets in operations. Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **api** is the object of type [`Api`].
+///
+/// * **operations** is a vector that contains object of type [`SwapOperation`].
+pub(crate) fn assert_operations(
+    api: &dyn Api,
+    operations: &[SwapOperation],
+) -> Result<(), ContractError> {
+    let mut ask_asset_map: HashMap<String, bool> = HashMap::new();
+    for operation in operations.iter() {
+        let offer_asset = operation.get_offer_asset_info();
+        let ask_asset = operation.get_target_asset_info();
         offer_asset.check(api)?;
         ask_asset.check(api)?;
 
@@ -491,6 +1777,7 @@ fn assert_operations(api: &dyn Api, operations: &[SwapOperation]) -> Result<(),
     Ok(())
 }
 
+#[cfg(feature = "terra")]
 #[test]
 fn test_invalid_operations() {
     use cosmwasm_std::testing::mock_dependencies;
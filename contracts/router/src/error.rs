@@ -17,8 +17,61 @@ pub enum ContractError {
     #[error("Assertion failed; minimum receive amount: {receive}, swap amount: {amount}")]
     AssertionMinimumReceive { receive: Uint128, amount: Uint128 },
 
+    #[error(
+        "Assertion failed; maximum offer amount: {max_offer}, required offer amount: {amount}"
+    )]
+    AssertionMaximumOffer { max_offer: Uint128, amount: Uint128 },
+
+    #[error("Reverse simulation is not supported for native swaps!")]
+    ReverseNativeSwapNotSupported {},
+
     #[error("The limit exceeded of swap operations!")]
     SwapLimitExceeded {},
+
+    #[error("Route search limits must be positive and not exceed {max_hops} hops and {max_route_candidates} candidates!")]
+    InvalidRouteSearchLimits {
+        max_hops: u32,
+        max_route_candidates: u32,
+    },
+
+    #[error("Referral commission must not exceed {max_bps} bps!")]
+    ReferralCommissionTooHigh { max_bps: u64 },
+
+    #[error("No route found!")]
+    NoRouteFound {},
+
+    #[error("Cw20 offer assets must be sent via the Receive hook!")]
+    Cw20OfferNotAllowed {},
+
+    #[error("Must provide routes!")]
+    MustProvideRoutes {},
+
+    #[error("Routes must start with the same offer asset and end with the same ask asset!")]
+    InvalidSplitRoutes {},
+
+    #[error("The sum of route amounts {total} doesn't match the offer amount {offer_amount}!")]
+    InvalidSplitAmounts {
+        total: Uint128,
+        offer_amount: Uint128,
+    },
+
+    #[error("The pair is not registered in the factory!")]
+    PairNotRegistered {},
+
+    #[error("Operations must swap between the offer asset and each asset of the pair without passing through them!")]
+    InvalidZapOperations {},
+
+    #[error("Assertion failed; minimum LP amount: {min_lp_out}, received LP amount: {amount}")]
+    AssertionMinimumLiquidity {
+        min_lp_out: Uint128,
+        amount: Uint128,
+    },
+
+    #[error("GeneratorAddress is not set in factory. Cannot autostake")]
+    AutoStakeError {},
+
+    #[error("The owner must be specified to migrate the config!")]
+    MigrationOwnerRequired {},
 }
 
 impl From<OverflowError> for ContractError {
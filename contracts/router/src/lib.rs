@@ -5,6 +5,15 @@ mod error;
 
 mod operations;
 mod querier;
+mod routes;
+mod zap;
 
 #[cfg(test)]
 mod testing;
+
+/// The custom message type of the contract responses, Terra messages are used for the native swaps
+#[cfg(feature = "terra")]
+pub type CustomMsg = terra_cosmwasm::TerraMsgWrapper;
+/// The custom message type of the contract responses
+#[cfg(not(feature = "terra"))]
+pub type CustomMsg = cosmwasm_std::Empty;
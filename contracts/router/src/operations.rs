@@ -1,20 +1,23 @@
 use cosmwasm_std::{
-    to_binary, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult, WasmMsg,
+    to_binary, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdResult, Uint128,
+    WasmMsg,
 };
 
 use crate::error::ContractError;
 use crate::querier::compute_tax;
 use crate::state::{Config, CONFIG};
+use crate::CustomMsg;
 
 use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::pair::ExecuteMsg as PairExecuteMsg;
 use astroport::querier::{query_balance, query_pair_info, query_token_balance};
 use astroport::router::SwapOperation;
 use cw20::Cw20ExecuteMsg;
-use terra_cosmwasm::{create_swap_msg, create_swap_send_msg, TerraMsgWrapper};
+#[cfg(feature = "terra")]
+use terra_cosmwasm::{create_swap_msg, create_swap_send_msg};
 
 /// ## Description
-/// Execute swap operation. Swap all offer asset to ask asset.
+/// Execute swap operation. Swap all offer asset (or the specified `amount` of it) to ask asset.
 /// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the
 /// specified attributes if the operation was successful.
 /// ## Params
@@ -27,24 +30,33 @@ use terra_cosmwasm::{create_swap_msg, create_swap_send_msg, TerraMsgWrapper};
 /// * **operation** is the object of type [`SwapOperation`].
 ///
 /// * **to** is the object of type [`Option<String>`].
+///
+/// * **amount** is the object of type [`Option<Uint128>`]. Sets the amount of the offer asset
+/// to swap, the whole balance of the contract is swapped if it is not specified.
 pub fn execute_swap_operation(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     operation: SwapOperation,
     to: Option<String>,
-) -> Result<Response<TerraMsgWrapper>, ContractError> {
+    amount: Option<Uint128>,
+) -> Result<Response<CustomMsg>, ContractError> {
     if env.contract.address != info.sender {
         return Err(ContractError::Unauthorized {});
     }
 
-    let messages: Vec<CosmosMsg<TerraMsgWrapper>> = match operation {
+    let messages: Vec<CosmosMsg<CustomMsg>> = match operation {
+        #[cfg(feature = "terra")]
         SwapOperation::NativeSwap {
             offer_denom,
             ask_denom,
         } => {
-            let amount =
-                query_balance(&deps.querier, env.contract.address, offer_denom.to_string())?;
+            let amount = match amount {
+                Some(amount) => amount,
+                None => {
+                    query_balance(&deps.querier, env.contract.address, offer_denom.to_string())?
+                }
+            };
             if let Some(to) = to {
                 // if the opeation is last, and requires send
                 // deduct tax from the offer_coin
@@ -80,11 +92,12 @@ pub fn execute_swap_operation(
                 &[offer_asset_info.clone(), ask_asset_info],
             )?;
 
-            let amount = match offer_asset_info.clone() {
-                AssetInfo::NativeToken { denom } => {
+            let amount = match (amount, offer_asset_info.clone()) {
+                (Some(amount), _) => amount,
+                (None, AssetInfo::NativeToken { denom }) => {
                     query_balance(&deps.querier, env.contract.address, denom)?
                 }
-                AssetInfo::Token { contract_addr } => {
+                (None, AssetInfo::Token { contract_addr }) => {
                     query_token_balance(&deps.querier, contract_addr, env.contract.address)?
                 }
             };
@@ -108,7 +121,7 @@ pub fn execute_swap_operation(
 
 /// ## Description
 /// Creates a message with an exchange operation of type CosmosMsg for each asset.
-/// Returns the [`CosmosMsg<TerraMsgWrapper>`] with the specified attributes if the operation was successful.
+/// Returns the [`CosmosMsg<CustomMsg>`] with the specified attributes if the operation was successful.
 /// ## Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
@@ -125,7 +138,7 @@ pub fn asset_into_swap_msg(
     offer_asset: Asset,
     max_spread: Option<Decimal>,
     to: Option<String>,
-) -> StdResult<CosmosMsg<TerraMsgWrapper>> {
+) -> StdResult<CosmosMsg<CustomMsg>> {
     match offer_asset.info.clone() {
         AssetInfo::NativeToken { denom } => {
             // deduct tax first
@@ -1,8 +1,15 @@
-use cosmwasm_std::{Decimal, Deps, StdResult, Uint128};
+use astroport::asset::{Asset, AssetInfo};
+use astroport::pair::{ReverseSimulationResponse, SimulationResponse};
+use astroport::querier::{reverse_simulate, simulate};
+#[cfg(feature = "terra")]
+use cosmwasm_std::Decimal;
+use cosmwasm_std::{Addr, Deps, StdResult, Uint128};
+#[cfg(feature = "terra")]
 use terra_cosmwasm::TerraQuerier;
 
 /// ## Description
 /// Sets the default decimal fraction
+#[cfg(feature = "terra")]
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
 /// ## Description
@@ -13,6 +20,7 @@ static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 /// * **amount** is the object of type [`Uint128`].
 ///
 /// * **denom** is the object of type [`String`].
+#[cfg(feature = "terra")]
 pub fn compute_tax(deps: Deps, amount: Uint128, denom: String) -> StdResult<Uint128> {
     if denom == "uluna" {
         return Ok(Uint128::zero());
@@ -29,3 +37,151 @@ pub fn compute_tax(deps: Deps, amount: Uint128, denom: String) -> StdResult<Uint
         tax_cap,
     ))
 }
+
+/// ## Description
+/// Returns zero as no tax is charged without the `terra` feature.
+/// ## Params
+/// * **_deps** is the object of type [`Deps`].
+///
+/// * **_amount** is the object of type [`Uint128`].
+///
+/// * **_denom** is the object of type [`String`].
+#[cfg(not(feature = "terra"))]
+pub fn compute_tax(_deps: Deps, _amount: Uint128, _denom: String) -> StdResult<Uint128> {
+    Ok(Uint128::zero())
+}
+
+/// ## Description
+/// If **denom* is a **uluna** returns the same amount, otherwise returns the amount which has to be
+/// sent so that **amount** is left after the tax is deducted.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **amount** is the object of type [`Uint128`].
+///
+/// * **denom** is the object of type [`String`].
+#[cfg(feature = "terra")]
+pub fn add_tax(deps: Deps, amount: Uint128, denom: String) -> StdResult<Uint128> {
+    if denom == "uluna" {
+        return Ok(amount);
+    }
+
+    let terra_querier = TerraQuerier::new(&deps.querier);
+    let tax_rate: Decimal = (terra_querier.query_tax_rate()?).rate;
+    if tax_rate.is_zero() {
+        return Ok(amount);
+    }
+
+    let tax_cap: Uint128 = (terra_querier.query_tax_cap(denom)?).cap;
+    // The deducted tax is rounded up, so one more unit is added to cover it
+    let tax = std::cmp::min(amount * tax_rate + Uint128::new(1), tax_cap);
+    Ok(amount.checked_add(tax)?)
+}
+
+/// ## Description
+/// Returns the same amount as no tax is charged without the `terra` feature.
+/// ## Params
+/// * **_deps** is the object of type [`Deps`].
+///
+/// * **amount** is the object of type [`Uint128`].
+///
+/// * **_denom** is the object of type [`String`].
+#[cfg(not(feature = "terra"))]
+pub fn add_tax(_deps: Deps, amount: Uint128, _denom: String) -> StdResult<Uint128> {
+    Ok(amount)
+}
+
+/// ## Description
+/// Returns information about the simulation of the swap in the pair in a [`SimulationResponse`] object.
+/// The tax is deducted from the native offer amount before the simulation and from the native
+/// return amount after it.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **pair_contract** is the object of type [`Addr`].
+///
+/// * **offer_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **ask_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **offer_amount** is the object of type [`Uint128`].
+pub fn simulate_astro_swap(
+    deps: Deps,
+    pair_contract: Addr,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: &AssetInfo,
+    offer_amount: Uint128,
+) -> StdResult<SimulationResponse> {
+    let mut offer_amount = offer_amount;
+
+    // Deduct tax before querying simulation
+    if let AssetInfo::NativeToken { denom } = &offer_asset_info {
+        offer_amount = offer_amount.checked_sub(compute_tax(deps, offer_amount, denom.clone())?)?;
+    }
+
+    let mut res = simulate(
+        &deps.querier,
+        pair_contract,
+        &Asset {
+            info: offer_asset_info,
+            amount: offer_amount,
+        },
+    )?;
+
+    // Deduct tax after querying simulation
+    if let AssetInfo::NativeToken { denom } = ask_asset_info {
+        res.return_amount =
+            res.return_amount
+                .checked_sub(compute_tax(deps, res.return_amount, denom.clone())?)?;
+    }
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns information about the reverse simulation of the swap in the pair in a [`ReverseSimulationResponse`] object.
+/// The tax is added to the native ask amount before the simulation and to the native offer
+/// amount after it, so the returned offer amount is the amount which has to be sent by the router.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **pair_contract** is the object of type [`Addr`].
+///
+/// * **offer_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **ask_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **ask_amount** is the object of type [`Uint128`].
+pub fn reverse_simulate_astro_swap(
+    deps: Deps,
+    pair_contract: Addr,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: AssetInfo,
+    ask_amount: Uint128,
+) -> StdResult<ReverseSimulationResponse> {
+    let mut ask_amount = ask_amount;
+
+    // Add tax before querying simulation
+    if let AssetInfo::NativeToken { denom } = &ask_asset_info {
+        ask_amount = add_tax(deps, ask_amount, denom.clone())?;
+    }
+
+    let mut res = reverse_simulate(
+        &deps.querier,
+        &pair_contract,
+        &Asset {
+            info: ask_asset_info,
+            amount: ask_amount,
+        },
+    )?;
+
+    // The pair rounds the offer amount down, so one more unit is required to receive the whole ask amount
+    res.offer_amount = res.offer_amount.checked_add(Uint128::new(1))?;
+
+    // Add tax after querying simulation
+    if let AssetInfo::NativeToken { denom } = offer_asset_info {
+        res.offer_amount = add_tax(deps, res.offer_amount, denom.clone())?;
+    }
+
+    Ok(res)
+}
@@ -0,0 +1,222 @@
+use cosmwasm_std::{Addr, Deps, StdError, Uint128};
+use std::collections::BTreeMap;
+
+use crate::error::ContractError;
+use crate::querier::simulate_astro_swap;
+use crate::state::{Config, CONFIG};
+
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::querier::query_pairs_info;
+use astroport::router::{FindBestRouteResponse, SwapOperation};
+
+/// ## Description
+/// The maximum number of pairs read from the factory at once
+const FACTORY_PAIRS_PAGE_LIMIT: u32 = 30;
+
+/// ## Description
+/// This structure describes a single hop of a route through the factory pairs.
+#[derive(Clone, Debug, PartialEq)]
+struct RouteHop {
+    /// the pair contract address
+    pair_contract: Addr,
+    /// the offer asset info
+    offer_asset_info: AssetInfo,
+    /// the asks asset info
+    ask_asset_info: AssetInfo,
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the route through the factory
+/// pairs with the best output in a [`FindBestRouteResponse`] object.
+/// Only the first `max_route_candidates` pairs of the factory are considered and the routes are
+/// simulated hop by hop with the current state of the pairs.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **offer_asset** is the object of type [`Asset`].
+///
+/// * **ask_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **max_hops** is an [`Option`] field of type [`u32`]. It is bounded by the config.
+pub fn find_best_route(
+    deps: Deps,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    max_hops: Option<u32>,
+) -> Result<FindBestRouteResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    offer_asset.info.check(deps.api)?;
+    ask_asset_info.check(deps.api)?;
+
+    if offer_asset.info == ask_asset_info {
+        return Err(StdError::generic_err("Offer and ask assets must be different").into());
+    }
+
+    let max_hops = max_hops.unwrap_or(config.max_hops).min(config.max_hops);
+
+    let pairs = load_candidate_pairs(deps, &config)?;
+
+    let best = search_best_route(
+        deps,
+        &pairs,
+        &offer_asset,
+        &ask_asset_info,
+        max_hops as usize,
+    );
+    let (route, amount) = best.ok_or(ContractError::NoRouteFound {})?;
+
+    Ok(FindBestRouteResponse {
+        operations: route
+            .into_iter()
+            .map(|hop| SwapOperation::AstroSwap {
+                offer_asset_info: hop.offer_asset_info,
+                ask_asset_info: hop.ask_asset_info,
+            })
+            .collect(),
+        amount,
+    })
+}
+
+/// ## Description
+/// Returns the first `max_route_candidates` pairs registered in the factory.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **config** is the object of type [`Config`].
+fn load_candidate_pairs(deps: Deps, config: &Config) -> Result<Vec<PairInfo>, ContractError> {
+    let mut pairs: Vec<PairInfo> = vec![];
+    let mut start_after: Option<[AssetInfo; 2]> = None;
+
+    while pairs.len() < config.max_route_candidates as usize {
+        let limit = FACTORY_PAIRS_PAGE_LIMIT.min(config.max_route_candidates - pairs.len() as u32);
+
+        let page = query_pairs_info(
+            &deps.querier,
+            config.astroport_factory.clone(),
+            start_after,
+            Some(limit),
+        )?
+        .pairs;
+
+        let page_len = page.len();
+        start_after = page.last().map(|pair| pair.asset_infos.clone());
+        pairs.extend(page);
+
+        if page_len < limit as usize {
+            break;
+        }
+    }
+
+    Ok(pairs)
+}
+
+/// ## Description
+/// Searches the route from the offer asset to the `ask_asset_info` with the best output by a
+/// breadth-first search over the pairs of each asset. Each hop level keeps only the best route to
+/// every asset, so the number of simulated swaps is bounded by `max_hops` times the number of pair
+/// sides. Routes don't pass through the same asset twice.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **pairs** is a slice that contains items of type [`PairInfo`].
+///
+/// * **offer_asset** is the object of type [`Asset`].
+///
+/// * **ask_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **max_hops** is the object of type [`usize`].
+fn search_best_route(
+    deps: Deps,
+    pairs: &[PairInfo],
+    offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
+    max_hops: usize,
+) -> Option<(Vec<RouteHop>, Uint128)> {
+    // The pairs of each asset with the other asset of the pair
+    let mut adjacency: BTreeMap<String, Vec<(&PairInfo, &AssetInfo)>> = BTreeMap::new();
+    for pair in pairs {
+        let [first, second] = &pair.asset_infos;
+        adjacency
+            .entry(first.to_string())
+            .or_default()
+            .push((pair, second));
+        adjacency
+            .entry(second.to_string())
+            .or_default()
+            .push((pair, first));
+    }
+
+    let mut frontier: Vec<(AssetInfo, Vec<RouteHop>, Uint128)> =
+        vec![(offer_asset.info.clone(), vec![], offer_asset.amount)];
+    let mut best: Option<(Vec<RouteHop>, Uint128)> = None;
+
+    for _ in 0..max_hops {
+        // The best route to each asset reached with one more hop
+        let mut next: BTreeMap<String, (AssetInfo, Vec<RouteHop>, Uint128)> = BTreeMap::new();
+
+        for (asset_info, route, amount) in &frontier {
+            let asset_pairs = match adjacency.get(&asset_info.to_string()) {
+                Some(asset_pairs) => asset_pairs,
+                None => continue,
+            };
+
+            for (pair, next_asset_info) in asset_pairs {
+                let next_asset_info = *next_asset_info;
+
+                // Don't pass through the same asset twice
+                if *next_asset_info == offer_asset.info
+                    || route
+                        .iter()
+                        .any(|hop| hop.ask_asset_info == *next_asset_info)
+                {
+                    continue;
+                }
+
+                // Skip the pairs that can't be simulated, e.g. pairs without liquidity
+                let return_amount = match simulate_astro_swap(
+                    deps,
+                    pair.contract_addr.clone(),
+                    asset_info.clone(),
+                    next_asset_info,
+                    *amount,
+                ) {
+                    Ok(res) => res.return_amount,
+                    Err(_) => continue,
+                };
+
+                let mut next_route = route.clone();
+                next_route.push(RouteHop {
+                    pair_contract: pair.contract_addr.clone(),
+                    offer_asset_info: asset_info.clone(),
+                    ask_asset_info: next_asset_info.clone(),
+                });
+
+                // Shorter routes are found first, so a longer route must give strictly more
+                if next_asset_info == ask_asset_info {
+                    if best
+                        .as_ref()
+                        .map(|(_, best_amount)| return_amount > *best_amount)
+                        .unwrap_or(true)
+                    {
+                        best = Some((next_route, return_amount));
+                    }
+                    continue;
+                }
+
+                let key = next_asset_info.to_string();
+                if next
+                    .get(&key)
+                    .map(|(_, _, next_amount)| return_amount > *next_amount)
+                    .unwrap_or(true)
+                {
+                    next.insert(key, (next_asset_info.clone(), next_route, return_amount));
+                }
+            }
+        }
+
+        frontier = next.into_values().collect();
+    }
+
+    best
+}
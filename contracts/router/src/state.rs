@@ -4,6 +4,10 @@ use serde::{Deserialize, Serialize};
 
 use cosmwasm_std::Addr;
 
+/// ## Description
+/// The default maximum referral commission in basis points
+pub const DEFAULT_MAX_REFERRAL_COMMISSION_BPS: u64 = 100;
+
 /// ## Description
 /// Stores config at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -14,4 +18,41 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub struct Config {
     /// The factory contract address
     pub astroport_factory: Addr,
+    /// The contract address that used for controls settings
+    pub owner: Addr,
+    /// The maximum number of hops in a route found by the `FindBestRoute` query
+    pub max_hops: u32,
+    /// The maximum number of factory pairs considered by the `FindBestRoute` query
+    pub max_route_candidates: u32,
+    /// The maximum referral commission in basis points. Configs stored before referrals were added
+    /// get the default value.
+    #[serde(default = "default_max_referral_commission_bps")]
+    pub max_referral_commission_bps: u64,
+}
+
+/// ## Description
+/// Stores the config of previous versions at the same key. Used for migrations only.
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
+/// ## Description
+/// This structure describes the config stored by previous versions of the router, which may lack
+/// the owner and the route search limits
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyConfig {
+    /// The factory contract address
+    pub astroport_factory: Addr,
+    /// The contract address that used for controls settings
+    pub owner: Option<Addr>,
+    /// The maximum number of hops in a route found by the `FindBestRoute` query
+    pub max_hops: Option<u32>,
+    /// The maximum number of factory pairs considered by the `FindBestRoute` query
+    pub max_route_candidates: Option<u32>,
+    /// The maximum referral commission in basis points
+    pub max_referral_commission_bps: Option<u64>,
+}
+
+/// ## Description
+/// Returns the default maximum referral commission in basis points.
+fn default_max_referral_commission_bps() -> u64 {
+    DEFAULT_MAX_REFERRAL_COMMISSION_BPS
 }
@@ -8,8 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use astroport::asset::{Asset, AssetInfo, PairInfo};
-use astroport::factory::PairType;
-use astroport::pair::SimulationResponse;
+use astroport::factory::{ConfigResponse as FactoryConfigResponse, PairType, PairsResponse};
+use astroport::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
 use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 use terra_cosmwasm::{
     SwapResponse, TaxCapResponse, TaxRateResponse, TerraQuery, TerraQueryWrapper, TerraRoute,
@@ -18,8 +18,20 @@ use terra_cosmwasm::{
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    Pair { asset_infos: [AssetInfo; 2] },
-    Simulation { offer_asset: Asset },
+    Pair {
+        asset_infos: [AssetInfo; 2],
+    },
+    Pairs {
+        start_after: Option<[AssetInfo; 2]>,
+        limit: Option<u32>,
+    },
+    Simulation {
+        offer_asset: Asset,
+    },
+    ReverseSimulation {
+        ask_asset: Asset,
+    },
+    Config {},
 }
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
@@ -100,12 +112,18 @@ pub(crate) fn caps_to_map(caps: &[(&String, &Uint128)]) -> HashMap<String, Uint1
 #[derive(Clone, Default)]
 pub struct AstroportFactoryQuerier {
     pairs: HashMap<String, String>,
+    pair_infos: Vec<PairInfo>,
+    simulation_rates: HashMap<String, Decimal>,
+    commission_rates: HashMap<String, Decimal>,
+    pair_types: HashMap<String, PairType>,
+    generator_address: Option<Addr>,
 }
 
 impl AstroportFactoryQuerier {
     pub fn new(pairs: &[(&String, &String)]) -> Self {
         AstroportFactoryQuerier {
             pairs: pairs_to_map(pairs),
+            ..AstroportFactoryQuerier::default()
         }
     }
 }
@@ -184,17 +202,40 @@ impl WasmMockQuerier {
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
                 if contract_addr.to_string().starts_with("token")
                     || contract_addr.to_string().starts_with("asset")
+                    || contract_addr.to_string().starts_with("liquidity")
                 {
                     self.handle_cw20(contract_addr, msg)
+                } else if contract_addr.to_string().starts_with("pair") {
+                    self.handle_pair(contract_addr, msg)
                 } else {
-                    self.handle_default(msg)
+                    self.handle_default(contract_addr, msg)
                 }
             }
             _ => self.base.handle_query(request),
         }
     }
 
-    fn handle_default(&self, msg: &Binary) -> QuerierResult {
+    fn handle_pair(&self, contract_addr: &String, msg: &Binary) -> QuerierResult {
+        match from_binary(&msg) {
+            Ok(PairQueryMsg::Pair {}) => {
+                match self
+                    .astroport_factory_querier
+                    .pair_infos
+                    .iter()
+                    .find(|pair| pair.contract_addr == contract_addr.as_str())
+                {
+                    Some(pair_info) => SystemResult::Ok(ContractResult::from(to_binary(pair_info))),
+                    None => SystemResult::Err(SystemError::InvalidRequest {
+                        error: "No pair info exists".to_string(),
+                        request: msg.as_slice().into(),
+                    }),
+                }
+            }
+            _ => self.handle_default(contract_addr, msg),
+        }
+    }
+
+    fn handle_default(&self, contract_addr: &String, msg: &Binary) -> QuerierResult {
         match from_binary(&msg).unwrap() {
             QueryMsg::Pair { asset_infos } => {
                 let key = asset_infos[0].to_string() + asset_infos[1].to_string().as_str();
@@ -202,15 +243,13 @@ impl WasmMockQuerier {
                     Some(v) => SystemResult::Ok(ContractResult::from(to_binary(&PairInfo {
                         contract_addr: Addr::unchecked(v),
                         liquidity_token: Addr::unchecked("liquidity"),
-                        asset_infos: [
-                            AssetInfo::NativeToken {
-                                denom: "uusd".to_string(),
-                            },
-                            AssetInfo::NativeToken {
-                                denom: "uusd".to_string(),
-                            },
-                        ],
-                        pair_type: PairType::Xyk {},
+                        asset_infos,
+                        pair_type: self
+                            .astroport_factory_querier
+                            .pair_types
+                            .get(v)
+                            .cloned()
+                            .unwrap_or(PairType::Xyk {}),
                     }))),
                     None => SystemResult::Err(SystemError::InvalidRequest {
                         error: "No pair info exists".to_string(),
@@ -218,13 +257,75 @@ impl WasmMockQuerier {
                     }),
                 }
             }
+            QueryMsg::Pairs { start_after, limit } => {
+                let pair_infos = &self.astroport_factory_querier.pair_infos;
+                let start = start_after
+                    .and_then(|start_after| {
+                        pair_infos
+                            .iter()
+                            .position(|pair| pair.asset_infos == start_after)
+                    })
+                    .map_or(0, |position| position + 1);
+
+                let pairs: Vec<PairInfo> = pair_infos
+                    .iter()
+                    .skip(start)
+                    .take(limit.unwrap_or(10) as usize)
+                    .cloned()
+                    .collect();
+
+                SystemResult::Ok(ContractResult::from(to_binary(&PairsResponse { pairs })))
+            }
             QueryMsg::Simulation { offer_asset } => {
+                let rate = self
+                    .astroport_factory_querier
+                    .simulation_rates
+                    .get(contract_addr)
+                    .copied()
+                    .unwrap_or_else(Decimal::one);
+                let commission_rate = self
+                    .astroport_factory_querier
+                    .commission_rates
+                    .get(contract_addr)
+                    .copied()
+                    .unwrap_or_else(Decimal::zero);
+
+                let return_amount = offer_asset.amount * rate;
+                let commission_amount = return_amount * commission_rate;
                 SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
-                    return_amount: offer_asset.amount,
-                    commission_amount: Uint128::zero(),
+                    return_amount: return_amount - commission_amount,
+                    commission_amount,
                     spread_amount: Uint128::zero(),
                 })))
             }
+            QueryMsg::ReverseSimulation { ask_asset } => {
+                let rate = self
+                    .astroport_factory_querier
+                    .simulation_rates
+                    .get(contract_addr)
+                    .copied()
+                    .unwrap_or_else(Decimal::one);
+
+                SystemResult::Ok(ContractResult::from(to_binary(
+                    &ReverseSimulationResponse {
+                        offer_amount: ask_asset.amount.multiply_ratio(
+                            Uint128::new(1_000_000u128),
+                            Uint128::new(1_000_000u128) * rate,
+                        ),
+                        commission_amount: Uint128::zero(),
+                        spread_amount: Uint128::zero(),
+                    },
+                )))
+            }
+            QueryMsg::Config {} => {
+                SystemResult::Ok(ContractResult::from(to_binary(&FactoryConfigResponse {
+                    owner: Addr::unchecked("owner"),
+                    pair_configs: vec![],
+                    token_code_id: 0,
+                    fee_address: None,
+                    generator_address: self.astroport_factory_querier.generator_address.clone(),
+                })))
+            }
         }
     }
 
@@ -304,4 +405,41 @@ impl WasmMockQuerier {
     pub fn with_astroport_pairs(&mut self, pairs: &[(&String, &String)]) {
         self.astroport_factory_querier = AstroportFactoryQuerier::new(pairs);
     }
+
+    // configure the pairs returned by the factory `Pairs` query
+    pub fn with_astroport_pair_infos(&mut self, pair_infos: &[PairInfo]) {
+        self.astroport_factory_querier.pair_infos = pair_infos.to_vec();
+    }
+
+    // configure the generator address returned by the factory `Config` query
+    pub fn with_generator(&mut self, generator: &str) {
+        self.astroport_factory_querier.generator_address = Some(Addr::unchecked(generator));
+    }
+
+    // configure the rate used by the pair simulation, the default rate is one
+    pub fn with_simulation_rates(&mut self, rates: &[(&str, Decimal)]) {
+        for (pair, rate) in rates {
+            self.astroport_factory_querier
+                .simulation_rates
+                .insert(pair.to_string(), *rate);
+        }
+    }
+
+    // configure the share of the simulated return taken as the commission, the default is zero
+    pub fn with_commission_rates(&mut self, rates: &[(&str, Decimal)]) {
+        for (pair, rate) in rates {
+            self.astroport_factory_querier
+                .commission_rates
+                .insert(pair.to_string(), *rate);
+        }
+    }
+
+    // configure the type of the pairs returned by the factory `Pair` query, the default is XYK
+    pub fn with_pair_types(&mut self, pair_types: &[(&str, PairType)]) {
+        for (pair, pair_type) in pair_types {
+            self.astroport_factory_querier
+                .pair_types
+                .insert(pair.to_string(), pair_type.clone());
+        }
+    }
 }
@@ -1,21 +1,31 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Coin, Decimal, ReplyOn, SubMsg, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, StdError, Storage,
+    SubMsg, Uint128, WasmMsg,
 };
 
-use crate::contract::{execute, instantiate, query};
+use crate::contract::{execute, instantiate, migrate, query};
 use crate::error::ContractError;
 use crate::testing::mock_querier::mock_dependencies;
 
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
-use astroport::asset::{Asset, AssetInfo};
-use astroport::pair::ExecuteMsg as PairExecuteMsg;
+use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::factory::PairType;
+use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation, MAX_SWAP_OPERATIONS,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FindBestRouteResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, Referral, SimulateReverseSwapOperationsResponse, SimulateSplitSwapResponse,
+    SimulateSwapOperationsDetailedResponse, SimulateSwapOperationsResponse, SwapOperation,
+    SwapOperationSimulation, MAX_REFERRAL_COMMISSION_BPS, MAX_ROUTE_CANDIDATES, MAX_ROUTE_HOPS,
+};
+#[cfg(feature = "terra")]
+use {
+    astroport::router::MAX_SWAP_OPERATIONS,
+    cosmwasm_std::ReplyOn,
+    terra_cosmwasm::{create_swap_msg, create_swap_send_msg},
 };
-use terra_cosmwasm::{create_swap_msg, create_swap_send_msg};
 
 #[test]
 fn proper_initialization() {
@@ -23,6 +33,10 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
     };
 
     let env = mock_env();
@@ -35,13 +49,171 @@ fn proper_initialization() {
     let config: ConfigResponse =
         from_binary(&query(deps.as_ref(), env, QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!("astroportfactory", config.astroport_factory.as_str());
+    assert_eq!("owner", config.owner.as_str());
+    assert_eq!(3, config.max_hops);
+    assert_eq!(30, config.max_route_candidates);
+    assert_eq!(100, config.max_referral_commission_bps);
+}
+
+#[test]
+fn config_without_referral_commission() {
+    let mut deps = mock_dependencies(&[]);
+
+    // The config stored before referral commissions were added
+    deps.storage.set(
+        b"config",
+        br#"{"astroport_factory":"astroportfactory","owner":"owner","max_hops":3,"max_route_candidates":30}"#,
+    );
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(100, config.max_referral_commission_bps);
+}
+
+#[test]
+fn migrate_legacy_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    // The config stored before the owner and the route search limits were added
+    deps.storage
+        .set(b"config", br#"{"astroport_factory":"astroportfactory"}"#);
+
+    let err = migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap_err();
+    assert_eq!(err, ContractError::MigrationOwnerRequired {});
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: Some(String::from("owner")),
+        },
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!("astroportfactory", config.astroport_factory.as_str());
+    assert_eq!("owner", config.owner.as_str());
+    assert_eq!(3, config.max_hops);
+    assert_eq!(30, config.max_route_candidates);
+    assert_eq!(100, config.max_referral_commission_bps);
+
+    // The migration of an up-to-date config keeps it as is
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { owner: None }).unwrap();
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!("owner", config.owner.as_str());
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: Some(MAX_ROUTE_HOPS + 1),
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InvalidRouteSearchLimits {
+            max_hops: MAX_ROUTE_HOPS,
+            max_route_candidates: MAX_ROUTE_CANDIDATES,
+        }
+    );
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::UpdateConfig {
+        max_hops: Some(2),
+        max_route_candidates: Some(50),
+        max_referral_commission_bps: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info("owner", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateConfig {
+            max_hops: Some(0),
+            max_route_candidates: None,
+            max_referral_commission_bps: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::InvalidRouteSearchLimits {
+            max_hops: MAX_ROUTE_HOPS,
+            max_route_candidates: MAX_ROUTE_CANDIDATES,
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateConfig {
+            max_hops: None,
+            max_route_candidates: None,
+            max_referral_commission_bps: Some(MAX_REFERRAL_COMMISSION_BPS + 1),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::ReferralCommissionTooHigh {
+            max_bps: MAX_REFERRAL_COMMISSION_BPS,
+        }
+    );
+
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::UpdateConfig {
+            max_hops: None,
+            max_route_candidates: None,
+            max_referral_commission_bps: Some(200),
+        },
+    )
+    .unwrap();
+
+    let config: ConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+    assert_eq!(2, config.max_hops);
+    assert_eq!(50, config.max_route_candidates);
+    assert_eq!(200, config.max_referral_commission_bps);
 }
 
+#[cfg(feature = "terra")]
 #[test]
 fn execute_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
     };
 
     let env = mock_env();
@@ -54,6 +226,7 @@ fn execute_swap_operations() {
         operations: vec![],
         minimum_receive: None,
         to: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -94,6 +267,7 @@ fn execute_swap_operations() {
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
         to: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -112,6 +286,7 @@ fn execute_swap_operations() {
                             ask_denom: "ukrw".to_string(),
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -134,6 +309,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -156,6 +332,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -178,6 +355,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: Some(String::from("addr0000")),
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -244,6 +422,7 @@ fn execute_swap_operations() {
             ],
             minimum_receive: None,
             to: Some(String::from("addr0002")),
+            referral: None,
         })
         .unwrap(),
     });
@@ -264,6 +443,7 @@ fn execute_swap_operations() {
                             ask_denom: "ukrw".to_string(),
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -286,6 +466,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -308,6 +489,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: None,
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -330,6 +512,7 @@ fn execute_swap_operations() {
                             },
                         },
                         to: Some(String::from("addr0002")),
+                        amount: None,
                     })
                     .unwrap(),
                 }
@@ -342,11 +525,16 @@ fn execute_swap_operations() {
     );
 }
 
+#[cfg(feature = "terra")]
 #[test]
 fn execute_swap_operation() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
     };
 
     let env = mock_env();
@@ -374,6 +562,7 @@ fn execute_swap_operation() {
             ask_denom: "uluna".to_string(),
         },
         to: None,
+        amount: None,
     };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -407,6 +596,7 @@ fn execute_swap_operation() {
             ask_denom: "uluna".to_string(),
         },
         to: Some(String::from("addr0000")),
+        amount: None,
     };
     let env = mock_env();
     let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
@@ -447,6 +637,7 @@ fn execute_swap_operation() {
             },
         },
         to: Some(String::from("addr0000")),
+        amount: None,
     };
     let env = mock_env();
     let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
@@ -483,12 +674,17 @@ fn execute_swap_operation() {
     );
 }
 
+#[cfg(feature = "terra")]
 #[test]
 fn query_buy_with_routes() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
     };
 
     let env = mock_env();
@@ -530,6 +726,7 @@ fn query_buy_with_routes() {
                 },
             },
         ],
+        referral: None,
     };
     deps.querier.with_astroport_pairs(&[
         (&"ukrwasset0000".to_string(), &String::from("pair0000")),
@@ -542,6 +739,7 @@ fn query_buy_with_routes() {
         res,
         SimulateSwapOperationsResponse {
             amount: Uint128::from(952380u128), // tax charged 1 times uusd => ukrw, ukrw => asset0000, asset0000 => uluna
+            referral_amount: Uint128::zero(),
         }
     );
 
@@ -557,6 +755,7 @@ fn query_buy_with_routes() {
                 ask_denom: "uluna".to_string(),
             },
         ],
+        referral: None,
     };
 
     let res: SimulateSwapOperationsResponse =
@@ -565,6 +764,7 @@ fn query_buy_with_routes() {
         res,
         SimulateSwapOperationsResponse {
             amount: Uint128::from(952380u128), // tax charged 1 times uusd => ukrw, ukrw => uluna
+            referral_amount: Uint128::zero(),
         }
     );
 }
@@ -653,11 +853,16 @@ fn assert_minimum_receive_token() {
     );
 }
 
+#[cfg(feature = "terra")]
 #[test]
 fn assert_maximum_receive_swap_operations() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
     };
 
     let env = mock_env();
@@ -676,6 +881,7 @@ fn assert_maximum_receive_swap_operations() {
         ],
         minimum_receive: None,
         to: None,
+        referral: None,
     };
 
     let env = mock_env();
@@ -684,3 +890,1597 @@ fn assert_maximum_receive_swap_operations() {
 
     assert_eq!(res, ContractError::SwapLimitExceeded {});
 }
+
+#[test]
+fn execute_swap_operations_with_referral() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[
+            (&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero()),
+            (&String::from("addr0000"), &Uint128::zero()),
+        ],
+    )]);
+
+    let operations = vec![SwapOperation::AstroSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    }];
+
+    // The commission can't exceed the maximum from the config
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: None,
+        to: None,
+        referral: Some(Referral {
+            address: String::from("referral"),
+            commission_bps: 101,
+        }),
+    };
+    let info = mock_info("addr0000", &[Coin::new(1000u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::ReferralCommissionTooHigh { max_bps: 100 }
+    );
+
+    let referral = Referral {
+        address: String::from("referral"),
+        commission_bps: 50,
+    };
+    let msg = ExecuteMsg::ExecuteSwapOperations {
+        operations: operations.clone(),
+        minimum_receive: Some(Uint128::from(990u128)),
+        to: None,
+        referral: Some(referral.clone()),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("referral", "referral")]);
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: operations[0].clone(),
+                    to: Some(String::from(MOCK_CONTRACT_ADDR)),
+                    amount: None,
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::DistributeReferralCommission {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0001"),
+                    },
+                    prev_balance: Uint128::zero(),
+                    referral: referral.clone(),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0001"),
+                    },
+                    prev_balance: Uint128::zero(),
+                    minimum_receive: Uint128::from(990u128),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+
+    // The router received the output of the swap
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::from(1000u128))],
+    )]);
+
+    let msg = ExecuteMsg::DistributeReferralCommission {
+        asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        prev_balance: Uint128::zero(),
+        referral,
+        receiver: String::from("addr0000"),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("referral"),
+                    amount: Uint128::from(5u128),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: String::from("addr0000"),
+                    amount: Uint128::from(995u128),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "distribute_referral_commission"),
+            attr("referral", "referral"),
+            attr("commission_amount", "5"),
+            attr("return_amount", "995"),
+        ]
+    );
+}
+
+#[test]
+fn query_swap_operations_with_referral() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdasset0001".to_string(), &String::from("pair0000"))]);
+
+    let msg = QueryMsg::SimulateSwapOperations {
+        offer_amount: Uint128::from(1000u128),
+        operations: vec![SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0001"),
+            },
+        }],
+        referral: Some(Referral {
+            address: String::from("referral"),
+            commission_bps: 50,
+        }),
+    };
+
+    let res: SimulateSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSwapOperationsResponse {
+            amount: Uint128::from(995u128),
+            referral_amount: Uint128::from(5u128),
+        }
+    );
+}
+
+#[test]
+fn query_swap_operations_detailed() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_astroport_pairs(&[
+        (&"asset0000asset0001".to_string(), &String::from("pair0000")),
+        (&"asset0001asset0002".to_string(), &String::from("pair0001")),
+    ]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from("pair0000"), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[
+                (&String::from("pair0000"), &Uint128::from(2_000_000u128)),
+                (&String::from("pair0001"), &Uint128::from(1_000_000u128)),
+            ],
+        ),
+        (
+            &String::from("asset0002"),
+            &[(&String::from("pair0001"), &Uint128::from(1_000_000u128))],
+        ),
+    ]);
+    deps.querier.with_simulation_rates(&[
+        ("pair0000", Decimal::from_ratio(19u128, 10u128)),
+        ("pair0001", Decimal::percent(99)),
+    ]);
+
+    let first_operation = SwapOperation::AstroSwap {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    };
+    let second_operation = SwapOperation::AstroSwap {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0002"),
+        },
+    };
+
+    let msg = QueryMsg::SimulateSwapOperationsDetailed {
+        offer_amount: Uint128::from(1000u128),
+        operations: vec![first_operation.clone(), second_operation.clone()],
+    };
+
+    let res: SimulateSwapOperationsDetailedResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSwapOperationsDetailedResponse {
+            amount: Uint128::from(1881u128),
+            spot_amount: Uint128::from(2000u128),
+            price_impact: Decimal::from_ratio(595u128, 10000u128),
+            total_spread_amount: Uint128::zero(),
+            total_commission_amount: Uint128::zero(),
+            operations: vec![
+                SwapOperationSimulation {
+                    operation: first_operation,
+                    pair_contract: Some(Addr::unchecked("pair0000")),
+                    pair_type: Some(PairType::Xyk {}),
+                    offer_amount: Uint128::from(1000u128),
+                    return_amount: Uint128::from(1900u128),
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                    price_impact: Decimal::percent(5),
+                },
+                SwapOperationSimulation {
+                    operation: second_operation,
+                    pair_contract: Some(Addr::unchecked("pair0001")),
+                    pair_type: Some(PairType::Xyk {}),
+                    offer_amount: Uint128::from(1900u128),
+                    return_amount: Uint128::from(1881u128),
+                    spread_amount: Uint128::zero(),
+                    commission_amount: Uint128::zero(),
+                    price_impact: Decimal::percent(1),
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+fn query_swap_operations_detailed_stable() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_astroport_pairs(&[
+        (&"asset0000asset0001".to_string(), &String::from("pair0000")),
+        (&"asset0001asset0002".to_string(), &String::from("pair0001")),
+    ]);
+    deps.querier
+        .with_pair_types(&[("pair0001", PairType::Stable {})]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from("pair0000"), &Uint128::from(1_000_000u128))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[
+                (&String::from("pair0000"), &Uint128::from(2_000_000u128)),
+                (&String::from("pair0001"), &Uint128::from(1_000_000u128)),
+            ],
+        ),
+        (
+            &String::from("asset0002"),
+            &[(&String::from("pair0001"), &Uint128::from(3_000_000u128))],
+        ),
+    ]);
+    deps.querier.with_simulation_rates(&[
+        ("pair0000", Decimal::from_ratio(19u128, 10u128)),
+        ("pair0001", Decimal::percent(99)),
+    ]);
+    deps.querier.with_commission_rates(&[
+        ("pair0000", Decimal::percent(1)),
+        ("pair0001", Decimal::percent(1)),
+    ]);
+
+    let msg = QueryMsg::SimulateSwapOperationsDetailed {
+        offer_amount: Uint128::from(1000u128),
+        operations: vec![
+            SwapOperation::AstroSwap {
+                offer_asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0001"),
+                },
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0001"),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0002"),
+                },
+            },
+        ],
+    };
+
+    let res: SimulateSwapOperationsDetailedResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+
+    // 1000 asset0000 -> 1881 asset0001 (19 commission) -> 1844 asset0002 (18 commission)
+    assert_eq!(res.amount, Uint128::from(1844u128));
+    assert_eq!(res.operations[1].pair_type, Some(PairType::Stable {}));
+
+    // The spot price of the stable pair is measured by the probe swap, not by the 1:3 reserves
+    assert_eq!(res.spot_amount, Uint128::from(1980u128));
+    assert_eq!(
+        res.operations[1].price_impact,
+        Decimal::one() - Decimal::from_ratio(1844u128, 1862u128)
+    );
+    assert_eq!(
+        res.price_impact,
+        Decimal::one() - Decimal::from_ratio(1844u128, 1980u128)
+    );
+
+    // The commission of the first operation is converted to asset0002 at the rate of the second one
+    assert_eq!(res.total_spread_amount, Uint128::zero());
+    assert_eq!(res.total_commission_amount, Uint128::from(36u128));
+}
+
+#[test]
+fn execute_split_swap() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from("addr0000"), &Uint128::zero())],
+    )]);
+
+    let direct_route = vec![SwapOperation::AstroSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    }];
+    let luna_route = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0001"),
+            },
+        },
+    ];
+
+    // Routes must be provided
+    let msg = ExecuteMsg::ExecuteSplitSwap {
+        routes: vec![],
+        minimum_receive: None,
+        to: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::MustProvideRoutes {});
+
+    // Routes must end with the same asset
+    let msg = ExecuteMsg::ExecuteSplitSwap {
+        routes: vec![
+            (direct_route.clone(), Uint128::from(600u128)),
+            (vec![luna_route[0].clone()], Uint128::from(400u128)),
+        ],
+        minimum_receive: None,
+        to: None,
+    };
+    let info = mock_info("addr0000", &[Coin::new(1000u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::InvalidSplitRoutes {});
+
+    let msg = ExecuteMsg::ExecuteSplitSwap {
+        routes: vec![
+            (direct_route.clone(), Uint128::from(600u128)),
+            (luna_route.clone(), Uint128::from(400u128)),
+        ],
+        minimum_receive: Some(Uint128::from(900u128)),
+        to: None,
+    };
+
+    // The sum of route amounts must match the sent funds
+    let info = mock_info("addr0000", &[Coin::new(900u128, "uusd")]);
+    execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+
+    let info = mock_info("addr0000", &[Coin::new(1000u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: direct_route[0].clone(),
+                    to: Some(String::from("addr0000")),
+                    amount: Some(Uint128::from(600u128)),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: luna_route[0].clone(),
+                    to: None,
+                    amount: Some(Uint128::from(400u128)),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: luna_route[1].clone(),
+                    to: Some(String::from("addr0000")),
+                    amount: None,
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0001"),
+                    },
+                    prev_balance: Uint128::zero(),
+                    minimum_receive: Uint128::from(900u128),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ]
+    );
+
+    // Cw20 offer assets can't be swapped without the Receive hook
+    let token_route = vec![SwapOperation::AstroSwap {
+        offer_asset_info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        ask_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    }];
+    let msg = ExecuteMsg::ExecuteSplitSwap {
+        routes: vec![(token_route, Uint128::from(1000u128))],
+        minimum_receive: None,
+        to: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Cw20OfferNotAllowed {});
+
+    // The offer token must be the received token
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteSplitSwap {
+            routes: vec![
+                (direct_route, Uint128::from(600u128)),
+                (luna_route, Uint128::from(400u128)),
+            ],
+            minimum_receive: None,
+            to: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+}
+
+#[test]
+fn query_split_swap() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_astroport_pairs(&[
+        (&"uusdasset0001".to_string(), &String::from("pair0000")),
+        (&"uusduluna".to_string(), &String::from("pair0001")),
+        (&"ulunaasset0001".to_string(), &String::from("pair0002")),
+    ]);
+
+    let msg = QueryMsg::SimulateSplitSwap {
+        routes: vec![
+            (
+                vec![SwapOperation::AstroSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0001"),
+                    },
+                }],
+                Uint128::from(600u128),
+            ),
+            (
+                vec![
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                    },
+                    SwapOperation::AstroSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::Token {
+                            contract_addr: Addr::unchecked("asset0001"),
+                        },
+                    },
+                ],
+                Uint128::from(400u128),
+            ),
+        ],
+    };
+
+    let res: SimulateSplitSwapResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSplitSwapResponse {
+            amount: Uint128::from(1000u128),
+            route_amounts: vec![Uint128::from(600u128), Uint128::from(400u128)],
+        }
+    );
+}
+
+#[test]
+fn query_reverse_swap_operations() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    deps.querier.with_astroport_pairs(&[
+        (&"uusduluna".to_string(), &String::from("pair0000")),
+        (&"ulunaasset0001".to_string(), &String::from("pair0001")),
+    ]);
+    deps.querier.with_simulation_rates(&[
+        ("pair0000", Decimal::from_ratio(2u128, 1u128)),
+        ("pair0001", Decimal::percent(50)),
+    ]);
+
+    // set tax rate as 5%
+    deps.querier.with_tax(
+        Decimal::percent(5),
+        &[(&"uusd".to_string(), &Uint128::new(1000000u128))],
+    );
+
+    let msg = QueryMsg::SimulateReverseSwapOperations {
+        ask_amount: Uint128::from(1000u128),
+        operations: vec![
+            SwapOperation::AstroSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                ask_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0001"),
+                },
+            },
+        ],
+    };
+
+    // 1000 asset0001 <- 2001 uluna <- 1001 uusd, plus 5% tax on the sent uusd
+    let res: SimulateReverseSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), env, msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateReverseSwapOperationsResponse {
+            amount: if cfg!(feature = "terra") {
+                Uint128::from(1052u128)
+            } else {
+                Uint128::from(1001u128)
+            },
+        }
+    );
+}
+
+#[cfg(feature = "terra")]
+#[test]
+fn query_reverse_native_swap_operations() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // Native swaps can't be reverse simulated
+    let msg = QueryMsg::SimulateReverseSwapOperations {
+        ask_amount: Uint128::from(1000u128),
+        operations: vec![SwapOperation::NativeSwap {
+            offer_denom: "uusd".to_string(),
+            ask_denom: "uluna".to_string(),
+        }],
+    };
+    let res = query(deps.as_ref(), env, msg).unwrap_err();
+    assert_eq!(res, ContractError::ReverseNativeSwapNotSupported {});
+}
+
+#[test]
+fn execute_swap_operations_exact_out() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    deps.querier.with_astroport_pairs(&[
+        (&"uusduluna".to_string(), &String::from("pair0000")),
+        (&"ulunaasset0001".to_string(), &String::from("pair0001")),
+    ]);
+    deps.querier.with_simulation_rates(&[
+        ("pair0000", Decimal::from_ratio(2u128, 1u128)),
+        ("pair0001", Decimal::percent(50)),
+    ]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from("addr0000"), &Uint128::zero())],
+    )]);
+
+    // set tax rate as 5%
+    deps.querier.with_tax(
+        Decimal::percent(5),
+        &[(&"uusd".to_string(), &Uint128::new(1000000u128))],
+    );
+
+    let operations = vec![
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        },
+        SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0001"),
+            },
+        },
+    ];
+
+    // Every operation swaps exactly the reverse simulated amount
+    let res: SimulateReverseSwapOperationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateReverseSwapOperations {
+                ask_amount: Uint128::from(1000u128),
+                operations: operations[1..].to_vec(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    let intermediate_amount = res.amount;
+
+    // 1000 asset0001 <- 2001 uluna <- 1001 uusd, plus 5% tax on the sent uusd
+    let (offer_amount, refund_amount) = if cfg!(feature = "terra") {
+        // 48 uusd are refunded minus the tax
+        (Uint128::from(1052u128), Uint128::from(45u128))
+    } else {
+        (Uint128::from(1001u128), Uint128::from(99u128))
+    };
+
+    // The required offer amount exceeds the maximum offer
+    let msg = ExecuteMsg::ExecuteSwapOperationsExactOut {
+        operations: operations.clone(),
+        ask_amount: Uint128::from(1000u128),
+        max_offer: Uint128::from(1000u128),
+        to: None,
+    };
+    let info = mock_info("addr0000", &[Coin::new(1000u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::AssertionMaximumOffer {
+            max_offer: Uint128::from(1000u128),
+            amount: offer_amount,
+        }
+    );
+
+    let msg = ExecuteMsg::ExecuteSwapOperationsExactOut {
+        operations: operations.clone(),
+        ask_amount: Uint128::from(1000u128),
+        max_offer: Uint128::from(1100u128),
+        to: None,
+    };
+
+    // The maximum offer must match the sent funds
+    let info = mock_info("addr0000", &[Coin::new(1000u128, "uusd")]);
+    execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+
+    let info = mock_info("addr0000", &[Coin::new(1100u128, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: operations[0].clone(),
+                    to: None,
+                    amount: Some(offer_amount),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: operations[1].clone(),
+                    to: Some(String::from("addr0000")),
+                    amount: Some(intermediate_amount),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("asset0001"),
+                    },
+                    prev_balance: Uint128::zero(),
+                    minimum_receive: Uint128::from(1000u128),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: refund_amount,
+                }],
+            }),
+        ]
+    );
+
+    // Cw20 offers must be sent via the Receive hook
+    let msg = ExecuteMsg::ExecuteSwapOperationsExactOut {
+        operations: vec![SwapOperation::AstroSwap {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0001"),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        }],
+        ask_amount: Uint128::from(1000u128),
+        max_offer: Uint128::from(1100u128),
+        to: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Cw20OfferNotAllowed {});
+
+    // The offer token must be the received token
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: Uint128::from(1100u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteSwapOperationsExactOut {
+            operations,
+            ask_amount: Uint128::from(1000u128),
+            to: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+}
+
+#[test]
+fn find_best_route() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let uluna = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    let asset0000 = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0000"),
+    };
+    let asset0001 = AssetInfo::Token {
+        contract_addr: Addr::unchecked("asset0001"),
+    };
+
+    let pair_info = |asset_infos: [AssetInfo; 2], contract_addr: &str| PairInfo {
+        asset_infos,
+        contract_addr: Addr::unchecked(contract_addr),
+        liquidity_token: Addr::unchecked("liquidity"),
+        pair_type: PairType::Xyk {},
+    };
+
+    deps.querier.with_astroport_pair_infos(&[
+        pair_info([uusd.clone(), asset0000.clone()], "pair0000"),
+        pair_info([uusd.clone(), uluna.clone()], "pair0001"),
+        pair_info([uluna.clone(), asset0000.clone()], "pair0002"),
+        pair_info([uluna.clone(), asset0001.clone()], "pair0003"),
+    ]);
+    deps.querier.with_simulation_rates(&[
+        ("pair0000", Decimal::percent(50)),
+        ("pair0001", Decimal::percent(80)),
+    ]);
+
+    let query_msg = |max_hops: Option<u32>| QueryMsg::FindBestRoute {
+        offer_asset: Asset {
+            info: uusd.clone(),
+            amount: Uint128::from(1000u128),
+        },
+        ask_asset_info: asset0000.clone(),
+        max_hops,
+    };
+
+    // The route through LUNA gives more than the direct pair
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg(None)).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FindBestRouteResponse {
+            operations: vec![
+                SwapOperation::AstroSwap {
+                    offer_asset_info: uusd.clone(),
+                    ask_asset_info: uluna.clone(),
+                },
+                SwapOperation::AstroSwap {
+                    offer_asset_info: uluna.clone(),
+                    ask_asset_info: asset0000.clone(),
+                },
+            ],
+            amount: Uint128::from(800u128),
+        }
+    );
+
+    // Only the direct pair is available with a single hop
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg(Some(1))).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FindBestRouteResponse {
+            operations: vec![SwapOperation::AstroSwap {
+                offer_asset_info: uusd.clone(),
+                ask_asset_info: asset0000.clone(),
+            }],
+            amount: Uint128::from(500u128),
+        }
+    );
+
+    // Only the first pair is considered with a single candidate
+    let info = mock_info("owner", &[]);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::UpdateConfig {
+            max_hops: None,
+            max_route_candidates: Some(1),
+            max_referral_commission_bps: None,
+        },
+    )
+    .unwrap();
+
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), env.clone(), query_msg(None)).unwrap()).unwrap();
+    assert_eq!(res.amount, Uint128::from(500u128));
+
+    let res = query(
+        deps.as_ref(),
+        env,
+        QueryMsg::FindBestRoute {
+            offer_asset: Asset {
+                info: uusd,
+                amount: Uint128::from(1000u128),
+            },
+            ask_asset_info: asset0001,
+            max_hops: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::NoRouteFound {});
+}
+
+#[test]
+fn execute_swap_and_provide() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000u128),
+    }]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdasset0001".to_string(), &String::from("pair0000"))]);
+    deps.querier.with_astroport_pair_infos(&[
+        PairInfo {
+            contract_addr: Addr::unchecked("pair0000"),
+            liquidity_token: Addr::unchecked("liquidity0000"),
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+        },
+        PairInfo {
+            contract_addr: Addr::unchecked("pair0001"),
+            liquidity_token: Addr::unchecked("liquidity0001"),
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+        },
+    ]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+    )]);
+
+    let operation = SwapOperation::AstroSwap {
+        offer_asset_info: asset_infos[0].clone(),
+        ask_asset_info: asset_infos[1].clone(),
+    };
+    let msg = ExecuteMsg::SwapAndProvide {
+        operations_per_side: [
+            (vec![], Uint128::from(400u128)),
+            (vec![operation.clone()], Uint128::from(600u128)),
+        ],
+        pair: String::from("pair0000"),
+        min_lp_out: Some(Uint128::from(100u128)),
+        auto_stake: None,
+    };
+    let funds = [Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(1000u128),
+    }];
+
+    // the sent funds must match the amounts of both sides
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(900u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
+    );
+
+    // cw20 offers must be sent via the Receive hook
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SwapAndProvide {
+            operations_per_side: [
+                (
+                    vec![SwapOperation::AstroSwap {
+                        offer_asset_info: asset_infos[1].clone(),
+                        ask_asset_info: asset_infos[0].clone(),
+                    }],
+                    Uint128::from(600u128),
+                ),
+                (vec![], Uint128::from(400u128)),
+            ],
+            pair: String::from("pair0000"),
+            min_lp_out: None,
+            auto_stake: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Cw20OfferNotAllowed {});
+
+    // the pair must be registered in the factory
+    let info = mock_info("addr0000", &funds);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SwapAndProvide {
+            operations_per_side: [
+                (vec![], Uint128::from(400u128)),
+                (vec![operation.clone()], Uint128::from(600u128)),
+            ],
+            pair: String::from("pair0001"),
+            min_lp_out: None,
+            auto_stake: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::PairNotRegistered {});
+
+    // the sides must follow the order of the pair assets
+    let info = mock_info("addr0000", &funds);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SwapAndProvide {
+            operations_per_side: [
+                (vec![operation.clone()], Uint128::from(600u128)),
+                (vec![], Uint128::from(400u128)),
+            ],
+            pair: String::from("pair0000"),
+            min_lp_out: None,
+            auto_stake: None,
+        },
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidZapOperations {});
+
+    let info = mock_info("addr0000", &funds);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation,
+                    to: Some(String::from(MOCK_CONTRACT_ADDR)),
+                    amount: Some(Uint128::from(600u128)),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ProvideSwappedLiquidity {
+                    pair: String::from("pair0000"),
+                    prev_balances: [
+                        Asset {
+                            info: asset_infos[0].clone(),
+                            amount: Uint128::zero(),
+                        },
+                        Asset {
+                            info: asset_infos[1].clone(),
+                            amount: Uint128::zero(),
+                        },
+                    ],
+                    min_lp_out: Some(Uint128::from(100u128)),
+                    auto_stake: false,
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ],
+    );
+
+    // the swap output is received by the router
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::from(1200u128))],
+        ),
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+        ),
+    ]);
+
+    let msg = ExecuteMsg::ProvideSwappedLiquidity {
+        pair: String::from("pair0000"),
+        prev_balances: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: Uint128::zero(),
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: Uint128::zero(),
+            },
+        ],
+        min_lp_out: Some(Uint128::from(100u128)),
+        auto_stake: false,
+        receiver: String::from("addr0000"),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("asset0001"),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: String::from("pair0000"),
+                    amount: Uint128::from(1200u128),
+                    expires: None,
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("pair0000"),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+                msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+                    assets: [
+                        Asset {
+                            info: asset_infos[0].clone(),
+                            amount: Uint128::from(400u128),
+                        },
+                        Asset {
+                            info: asset_infos[1].clone(),
+                            amount: Uint128::from(1200u128),
+                        },
+                    ],
+                    slippage_tolerance: None,
+                    auto_stake: Some(false),
+                    receiver: Some(String::from(MOCK_CONTRACT_ADDR)),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::SendLiquidity {
+                    liquidity_token: String::from("liquidity0000"),
+                    prev_balance: Uint128::zero(),
+                    min_lp_out: Some(Uint128::from(100u128)),
+                    auto_stake: false,
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ],
+    );
+
+    // the LP tokens are received by the router
+    deps.querier.with_token_balances(&[(
+        &String::from("liquidity0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::from(99u128))],
+    )]);
+
+    let send_liquidity = |auto_stake: bool| ExecuteMsg::SendLiquidity {
+        liquidity_token: String::from("liquidity0000"),
+        prev_balance: Uint128::zero(),
+        min_lp_out: Some(Uint128::from(99u128)),
+        auto_stake,
+        receiver: String::from("addr0000"),
+    };
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::SendLiquidity {
+            liquidity_token: String::from("liquidity0000"),
+            prev_balance: Uint128::zero(),
+            min_lp_out: Some(Uint128::from(100u128)),
+            auto_stake: false,
+            receiver: String::from("addr0000"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::AssertionMinimumLiquidity {
+            min_lp_out: Uint128::from(100u128),
+            amount: Uint128::from(99u128),
+        }
+    );
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, send_liquidity(false)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: String::from("addr0000"),
+                amount: Uint128::from(99u128),
+            })
+            .unwrap(),
+        })],
+    );
+
+    // auto stake requires the generator in the factory config
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, send_liquidity(true)).unwrap_err();
+    assert_eq!(res, ContractError::AutoStakeError {});
+
+    deps.querier.with_generator("generator");
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, send_liquidity(true)).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("liquidity0000"),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: String::from("generator"),
+                amount: Uint128::from(99u128),
+                msg: to_binary(&GeneratorHookMsg::DepositFor(Addr::unchecked("addr0000"))).unwrap(),
+            })
+            .unwrap(),
+        })],
+    );
+}
+
+#[test]
+fn execute_withdraw_and_swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+    let msg = InstantiateMsg {
+        astroport_factory: String::from("astroportfactory"),
+        owner: String::from("owner"),
+        max_hops: None,
+        max_route_candidates: None,
+        max_referral_commission_bps: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+    ];
+    deps.querier
+        .with_astroport_pairs(&[(&"uusdasset0001".to_string(), &String::from("pair0000"))]);
+    deps.querier.with_astroport_pair_infos(&[PairInfo {
+        contract_addr: Addr::unchecked("pair0000"),
+        liquidity_token: Addr::unchecked("liquidity0000"),
+        asset_infos: asset_infos.clone(),
+        pair_type: PairType::Xyk {},
+    }]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::zero())],
+    )]);
+
+    let operations = [
+        vec![],
+        vec![SwapOperation::AstroSwap {
+            offer_asset_info: asset_infos[1].clone(),
+            ask_asset_info: asset_infos[0].clone(),
+        }],
+    ];
+    let receive_msg = |operations: [Vec<SwapOperation>; 2]| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: String::from("addr0000"),
+            amount: Uint128::from(100u128),
+            msg: to_binary(&Cw20HookMsg::WithdrawAndSwap {
+                pair: String::from("pair0000"),
+                operations,
+                minimum_receive: Some(Uint128::from(500u128)),
+            })
+            .unwrap(),
+        })
+    };
+
+    // only the LP token of the pair is accepted
+    let info = mock_info("liquidity0001", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        receive_msg(operations.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    // both assets must be swapped into the same asset
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        receive_msg([
+            vec![SwapOperation::AstroSwap {
+                offer_asset_info: asset_infos[0].clone(),
+                ask_asset_info: asset_infos[1].clone(),
+            }],
+            operations[1].clone(),
+        ]),
+    )
+    .unwrap_err();
+    assert_eq!(res, ContractError::InvalidZapOperations {});
+
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        receive_msg(operations.clone()),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from("liquidity0000"),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: String::from("pair0000"),
+                    amount: Uint128::from(100u128),
+                    msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {}).unwrap(),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::SwapWithdrawnLiquidity {
+                    prev_balances: [
+                        Asset {
+                            info: asset_infos[0].clone(),
+                            amount: Uint128::from(100u128),
+                        },
+                        Asset {
+                            info: asset_infos[1].clone(),
+                            amount: Uint128::zero(),
+                        },
+                    ],
+                    operations: operations.clone(),
+                    minimum_receive: Some(Uint128::from(500u128)),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ],
+    );
+
+    // the withdrawn assets are received by the router
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0001"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::from(250u128))],
+    )]);
+
+    let msg = ExecuteMsg::SwapWithdrawnLiquidity {
+        prev_balances: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: Uint128::zero(),
+            },
+        ],
+        operations: operations.clone(),
+        minimum_receive: Some(Uint128::from(500u128)),
+        receiver: String::from("addr0000"),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap_err();
+    assert_eq!(res, ContractError::Unauthorized {});
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: String::from("addr0000"),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(300u128),
+                }],
+            })),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: operations[1][0].clone(),
+                    to: Some(String::from("addr0000")),
+                    amount: Some(Uint128::from(250u128)),
+                })
+                .unwrap(),
+            }),
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: String::from(MOCK_CONTRACT_ADDR),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: asset_infos[0].clone(),
+                    prev_balance: Uint128::zero(),
+                    minimum_receive: Uint128::from(500u128),
+                    receiver: String::from("addr0000"),
+                })
+                .unwrap(),
+            }),
+        ],
+    );
+}
@@ -0,0 +1,641 @@
+use cosmwasm_std::{
+    to_binary, Addr, Api, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Response, Uint128,
+    WasmMsg,
+};
+
+use crate::contract::{
+    assert_minimum_receive_message, assert_operations, swap_operation_messages,
+    transfer_asset_message,
+};
+use crate::error::ContractError;
+use crate::state::CONFIG;
+use crate::CustomMsg;
+
+use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo, PairInfo};
+use astroport::generator::Cw20HookMsg as GeneratorHookMsg;
+use astroport::pair::{
+    Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg,
+};
+use astroport::querier::{query_factory_config, query_pair_info, query_token_balance};
+use astroport::router::{ExecuteMsg, SwapOperation, MAX_SWAP_OPERATIONS};
+use cw20::Cw20ExecuteMsg;
+
+/// ## Description
+/// Swaps the offer asset into both assets of the pair and provides them as liquidity. The swaps
+/// send their output to the router, then the received amounts are provided to the pair and the LP
+/// tokens are sent to the sender or staked in the generator on behalf of the sender.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **sender** is the object of type [`Addr`]. Sets the recipient of the LP tokens.
+///
+/// * **operations_per_side** is an array that contains the operations of [`SwapOperation`] with the
+/// offer amount for each asset of the pair.
+///
+/// * **pair** is the object of type [`String`]. Sets the pair contract address.
+///
+/// * **min_lp_out** is the object of type [`Option<Uint128>`]. Used to minimum LP amount assertion.
+///
+/// * **auto_stake** is the object of type [`Option<bool>`].
+pub fn execute_swap_and_provide(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    operations_per_side: [(Vec<SwapOperation>, Uint128); 2],
+    pair: String,
+    min_lp_out: Option<Uint128>,
+    auto_stake: Option<bool>,
+) -> Result<Response<CustomMsg>, ContractError> {
+    let pair_info = query_registered_pair(deps.as_ref(), &pair)?;
+    let offer_asset = get_zap_offer(&operations_per_side)?;
+    assert_zap_in_operations(
+        deps.api,
+        &offer_asset.info,
+        &pair_info.asset_infos,
+        &operations_per_side,
+    )?;
+
+    // The offer asset is already received by the router, so it is excluded from the previous
+    // balance of the side which is provided without swap
+    let prev_balance = |side: usize| -> Result<Asset, ContractError> {
+        let info = pair_info.asset_infos[side].clone();
+        let mut amount = info.query_pool(&deps.querier, env.contract.address.clone())?;
+        if operations_per_side[side].0.is_empty() {
+            amount = amount.checked_sub(offer_asset.amount)?;
+        }
+
+        Ok(Asset { info, amount })
+    };
+    let prev_balances = [prev_balance(0)?, prev_balance(1)?];
+
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    for (operations, amount) in operations_per_side {
+        if !operations.is_empty() {
+            messages.extend(swap_operation_messages(
+                &env,
+                operations,
+                Some(amount),
+                &env.contract.address,
+            )?);
+        }
+    }
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::ProvideSwappedLiquidity {
+            pair: pair_info.contract_addr.to_string(),
+            prev_balances,
+            min_lp_out,
+            auto_stake: auto_stake.unwrap_or(false),
+            receiver: sender.to_string(),
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap_and_provide"),
+        ("pair", pair_info.contract_addr.as_str()),
+    ]))
+}
+
+/// ## Description
+/// Provides the assets received by the router since the previous balances as liquidity to the pair.
+/// The LP tokens are minted to the router and sent further by the [`ExecuteMsg::SendLiquidity`] message.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **pair** is the object of type [`Addr`].
+///
+/// * **prev_balances** is an array that contains two objects of type [`Asset`]. Sets the balances of
+/// the router before the swaps.
+///
+/// * **min_lp_out** is the object of type [`Option<Uint128>`].
+///
+/// * **auto_stake** is the object of type [`bool`].
+///
+/// * **receiver** is the object of type [`Addr`].
+///
+/// ## Executor
+/// Only the router itself can execute it
+#[allow(clippy::too_many_arguments)]
+pub fn provide_swapped_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pair: Addr,
+    prev_balances: [Asset; 2],
+    min_lp_out: Option<Uint128>,
+    auto_stake: bool,
+    receiver: Addr,
+) -> Result<Response<CustomMsg>, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(pair.clone(), &PairQueryMsg::Pair {})?;
+
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    let mut assets: Vec<Asset> = vec![];
+    for prev_balance in prev_balances {
+        let balance = prev_balance
+            .info
+            .query_pool(&deps.querier, env.contract.address.clone())?;
+        let asset = Asset {
+            info: prev_balance.info,
+            amount: balance.checked_sub(prev_balance.amount)?,
+        };
+
+        match &asset.info {
+            AssetInfo::Token { contract_addr } => {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                        spender: pair.to_string(),
+                        amount: asset.amount,
+                        expires: None,
+                    })?,
+                }));
+                assets.push(asset);
+            }
+            AssetInfo::NativeToken { .. } => {
+                // The tax is paid by the router on top of the sent amount
+                let coin = asset.deduct_tax(&deps.querier)?;
+                assets.push(Asset {
+                    info: asset.info,
+                    amount: coin.amount,
+                });
+                funds.push(coin);
+            }
+        }
+    }
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    let provide_assets = [assets[0].clone(), assets[1].clone()];
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: pair.to_string(),
+        funds,
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+            assets: provide_assets,
+            slippage_tolerance: None,
+            auto_stake: Some(false),
+            receiver: Some(env.contract.address.to_string()),
+        })?,
+    }));
+
+    let prev_lp_balance = query_token_balance(
+        &deps.querier,
+        pair_info.liquidity_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::SendLiquidity {
+            liquidity_token: pair_info.liquidity_token.to_string(),
+            prev_balance: prev_lp_balance,
+            min_lp_out,
+            auto_stake,
+            receiver: receiver.to_string(),
+        })?,
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_swapped_liquidity".to_string()),
+        ("assets", format!("{}, {}", assets[0], assets[1])),
+    ]))
+}
+
+/// ## Description
+/// Sends the LP tokens received by the router since the previous balance to the receiver or stakes
+/// them in the generator on behalf of the receiver.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **liquidity_token** is the object of type [`Addr`].
+///
+/// * **prev_balance** is the object of type [`Uint128`]. Sets the LP balance of the router before
+/// providing liquidity.
+///
+/// * **min_lp_out** is the object of type [`Option<Uint128>`].
+///
+/// * **auto_stake** is the object of type [`bool`].
+///
+/// * **receiver** is the object of type [`Addr`].
+///
+/// ## Executor
+/// Only the router itself can execute it
+#[allow(clippy::too_many_arguments)]
+pub fn send_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    liquidity_token: Addr,
+    prev_balance: Uint128,
+    min_lp_out: Option<Uint128>,
+    auto_stake: bool,
+    receiver: Addr,
+) -> Result<Response<CustomMsg>, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let balance = query_token_balance(
+        &deps.querier,
+        liquidity_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    let amount = balance.checked_sub(prev_balance)?;
+
+    if let Some(min_lp_out) = min_lp_out {
+        if amount < min_lp_out {
+            return Err(ContractError::AssertionMinimumLiquidity { min_lp_out, amount });
+        }
+    }
+
+    let message = if auto_stake {
+        let config = CONFIG.load(deps.storage)?;
+        let generator = query_factory_config(&deps.querier, config.astroport_factory)?
+            .generator_address
+            .ok_or(ContractError::AutoStakeError {})?;
+
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: liquidity_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: generator.to_string(),
+                amount,
+                msg: to_binary(&GeneratorHookMsg::DepositFor(receiver.clone()))?,
+            })?,
+        })
+    } else {
+        transfer_asset_message(
+            deps.as_ref(),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: liquidity_token,
+                },
+                amount,
+            },
+            &receiver,
+        )?
+    };
+
+    Ok(Response::new().add_message(message).add_attributes(vec![
+        ("action", "send_liquidity"),
+        ("receiver", receiver.as_str()),
+        ("liquidity_amount", &amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Withdraws the LP tokens received by the router from the pair and swaps both withdrawn assets
+/// into one asset. The swaps are executed by the [`ExecuteMsg::SwapWithdrawnLiquidity`] message
+/// once the assets are received by the router.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **sender** is the object of type [`Addr`]. Sets the recipient of the swaps.
+///
+/// * **lp_token** is the object of type [`Asset`]. Sets the received LP tokens.
+///
+/// * **pair** is the object of type [`String`]. Sets the pair contract address.
+///
+/// * **operations** is an array that contains the operations of [`SwapOperation`] for each asset of the pair.
+///
+/// * **minimum_receive** is the object of type [`Option<Uint128>`]. Used to minimum amount assertion.
+pub fn execute_withdraw_and_swap(
+    deps: DepsMut,
+    env: Env,
+    sender: Addr,
+    lp_token: Asset,
+    pair: String,
+    operations: [Vec<SwapOperation>; 2],
+    minimum_receive: Option<Uint128>,
+) -> Result<Response<CustomMsg>, ContractError> {
+    let pair_info = query_registered_pair(deps.as_ref(), &pair)?;
+    if lp_token.info
+        != (AssetInfo::Token {
+            contract_addr: pair_info.liquidity_token.clone(),
+        })
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    assert_zap_out_operations(deps.api, &pair_info.asset_infos, &operations)?;
+
+    let prev_balances = [
+        Asset {
+            amount: pair_info.asset_infos[0]
+                .query_pool(&deps.querier, env.contract.address.clone())?,
+            info: pair_info.asset_infos[0].clone(),
+        },
+        Asset {
+            amount: pair_info.asset_infos[1]
+                .query_pool(&deps.querier, env.contract.address.clone())?,
+            info: pair_info.asset_infos[1].clone(),
+        },
+    ];
+
+    let messages: Vec<CosmosMsg<CustomMsg>> = vec![
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_info.liquidity_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_info.contract_addr.to_string(),
+                amount: lp_token.amount,
+                msg: to_binary(&PairCw20HookMsg::WithdrawLiquidity {})?,
+            })?,
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::SwapWithdrawnLiquidity {
+                prev_balances,
+                operations,
+                minimum_receive,
+                receiver: sender.to_string(),
+            })?,
+        }),
+    ];
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "withdraw_and_swap"),
+        ("pair", pair_info.contract_addr.as_str()),
+        ("withdrawn_share", &lp_token.amount.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Swaps the assets received by the router since the previous balances into one asset and sends
+/// it to the receiver. The asset which is the target asset itself is sent without swap.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Response`] with the specified messages of type [`CustomMsg`] to execute if the operation is successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **prev_balances** is an array that contains two objects of type [`Asset`]. Sets the balances of
+/// the router before the withdrawal.
+///
+/// * **operations** is an array that contains the operations of [`SwapOperation`] for each asset of the pair.
+///
+/// * **minimum_receive** is the object of type [`Option<Uint128>`].
+///
+/// * **receiver** is the object of type [`Addr`].
+///
+/// ## Executor
+/// Only the router itself can execute it
+pub fn swap_withdrawn_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    prev_balances: [Asset; 2],
+    operations: [Vec<SwapOperation>; 2],
+    minimum_receive: Option<Uint128>,
+    receiver: Addr,
+) -> Result<Response<CustomMsg>, ContractError> {
+    if env.contract.address != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let target_asset_info = get_zap_target_asset_info(
+        &[prev_balances[0].info.clone(), prev_balances[1].info.clone()],
+        &operations,
+    )?;
+
+    let mut messages: Vec<CosmosMsg<CustomMsg>> = vec![];
+    for (prev_balance, operations) in prev_balances.iter().zip(operations) {
+        let balance = prev_balance
+            .info
+            .query_pool(&deps.querier, env.contract.address.clone())?;
+        let amount = balance.checked_sub(prev_balance.amount)?;
+        if amount.is_zero() {
+            continue;
+        }
+
+        if operations.is_empty() {
+            messages.push(transfer_asset_message(
+                deps.as_ref(),
+                Asset {
+                    info: prev_balance.info.clone(),
+                    amount,
+                },
+                &receiver,
+            )?);
+        } else {
+            messages.extend(swap_operation_messages(
+                &env,
+                operations,
+                Some(amount),
+                &receiver,
+            )?);
+        }
+    }
+
+    // Execute minimum amount assertion
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(assert_minimum_receive_message(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &receiver,
+        )?);
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "swap_withdrawn_liquidity"))
+}
+
+/// ## Description
+/// Returns the information about the pair which is registered in the factory.
+/// Returns an [`ContractError`] if the pair is not registered.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **pair** is the object of type [`str`]. Sets the pair contract address.
+fn query_registered_pair(deps: Deps, pair: &str) -> Result<PairInfo, ContractError> {
+    let pair = addr_validate_to_lower(deps.api, pair)?;
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(pair.clone(), &PairQueryMsg::Pair {})?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let registered_pair_info = query_pair_info(
+        &deps.querier,
+        config.astroport_factory,
+        &pair_info.asset_infos,
+    )?;
+    if registered_pair_info.contract_addr != pair {
+        return Err(ContractError::PairNotRegistered {});
+    }
+
+    Ok(pair_info)
+}
+
+/// ## Description
+/// Returns the offer asset of the zap with the total amount of both sides.
+/// Returns an [`ContractError`] if no operations are provided.
+/// ## Params
+/// * **operations_per_side** is an array that contains the operations of [`SwapOperation`] with the
+/// offer amount for each asset of the pair.
+pub fn get_zap_offer(
+    operations_per_side: &[(Vec<SwapOperation>, Uint128); 2],
+) -> Result<Asset, ContractError> {
+    let info = operations_per_side
+        .iter()
+        .find_map(|(operations, _)| operations.first())
+        .map(|operation| operation.get_offer_asset_info())
+        .ok_or(ContractError::MustProvideOperations {})?;
+
+    let amount = operations_per_side[0]
+        .1
+        .checked_add(operations_per_side[1].1)?;
+
+    Ok(Asset { info, amount })
+}
+
+/// ## Description
+/// Returns the asset into which both assets of the pair are swapped.
+/// Returns an [`ContractError`] if no operations are provided.
+/// ## Params
+/// * **asset_infos** is an array that contains two objects of type [`AssetInfo`]. Sets the assets of the pair.
+///
+/// * **operations** is an array that contains the operations of [`SwapOperation`] for each asset of the pair.
+fn get_zap_target_asset_info(
+    asset_infos: &[AssetInfo; 2],
+    operations: &[Vec<SwapOperation>; 2],
+) -> Result<AssetInfo, ContractError> {
+    match (operations[0].last(), operations[1].last()) {
+        (Some(operation), _) => Ok(operation.get_target_asset_info()),
+        (None, Some(_)) => Ok(asset_infos[0].clone()),
+        (None, None) => Err(ContractError::MustProvideOperations {}),
+    }
+}
+
+/// ## Description
+/// Validates the operations of the zap into the pair. The operations of each side must swap the
+/// offer asset into the asset of the pair on that side, and they are empty only if the asset of the
+/// pair is the offer asset itself. Intermediate operations swap the whole balance of the router,
+/// so they must not offer the offer asset or the assets of the pair.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **api** is the object of type [`Api`].
+///
+/// * **offer_asset_info** is the object of type [`AssetInfo`].
+///
+/// * **asset_infos** is an array that contains two objects of type [`AssetInfo`]. Sets the assets of the pair.
+///
+/// * **operations_per_side** is an array that contains the operations of [`SwapOperation`] with the
+/// offer amount for each asset of the pair.
+fn assert_zap_in_operations(
+    api: &dyn Api,
+    offer_asset_info: &AssetInfo,
+    asset_infos: &[AssetInfo; 2],
+    operations_per_side: &[(Vec<SwapOperation>, Uint128); 2],
+) -> Result<(), ContractError> {
+    let operations_len: usize = operations_per_side
+        .iter()
+        .map(|(operations, _)| operations.len())
+        .sum();
+    if operations_len > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    for ((operations, amount), asset_info) in operations_per_side.iter().zip(asset_infos) {
+        if amount.is_zero() {
+            return Err(ContractError::InvalidZapOperations {});
+        }
+
+        if operations.is_empty() {
+            if asset_info != offer_asset_info {
+                return Err(ContractError::InvalidZapOperations {});
+            }
+            continue;
+        }
+
+        assert_operations(api, operations)?;
+
+        if operations[0].get_offer_asset_info() != *offer_asset_info
+            || operations.last().unwrap().get_target_asset_info() != *asset_info
+            || asset_info == offer_asset_info
+            || operations[1..].iter().any(|op| {
+                let info = op.get_offer_asset_info();
+                info == *offer_asset_info || asset_infos.contains(&info)
+            })
+        {
+            return Err(ContractError::InvalidZapOperations {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Validates the operations of the zap out of the pair. The operations of each asset of the pair
+/// must swap it into the same target asset, and they are empty only if the asset of the pair is
+/// the target asset itself. Intermediate operations swap the whole balance of the router, so they
+/// must not offer the assets of the pair.
+/// Returns an [`ContractError`] on failure, otherwise returns [`Ok`].
+/// ## Params
+/// * **api** is the object of type [`Api`].
+///
+/// * **asset_infos** is an array that contains two objects of type [`AssetInfo`]. Sets the assets of the pair.
+///
+/// * **operations** is an array that contains the operations of [`SwapOperation`] for each asset of the pair.
+fn assert_zap_out_operations(
+    api: &dyn Api,
+    asset_infos: &[AssetInfo; 2],
+    operations: &[Vec<SwapOperation>; 2],
+) -> Result<(), ContractError> {
+    if operations[0].len() + operations[1].len() > MAX_SWAP_OPERATIONS {
+        return Err(ContractError::SwapLimitExceeded {});
+    }
+
+    let target_asset_info = get_zap_target_asset_info(asset_infos, operations)?;
+    for (operations, asset_info) in operations.iter().zip(asset_infos) {
+        if operations.is_empty() {
+            if *asset_info != target_asset_info {
+                return Err(ContractError::InvalidZapOperations {});
+            }
+            continue;
+        }
+
+        assert_operations(api, operations)?;
+
+        if operations[0].get_offer_asset_info() != *asset_info
+            || operations.last().unwrap().get_target_asset_info() != target_asset_info
+            || *asset_info == target_asset_info
+            || operations[1..]
+                .iter()
+                .any(|op| asset_infos.contains(&op.get_offer_asset_info()))
+        {
+            return Err(ContractError::InvalidZapOperations {});
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,961 @@
+use cosmwasm_std::{
+    attr, entry_point, from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
+    WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::response::MsgInstantiateContractResponse;
+use crate::state::{Config, CONFIG, OWNERSHIP_PROPOSAL};
+use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo, PairInfo};
+use astroport::autocompounder::{
+    CallbackMsg, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    StateResponse,
+};
+use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
+use astroport::generator::{
+    Cw20HookMsg as GeneratorHookMsg, ExecuteMsg as GeneratorExecuteMsg, PriceSource,
+    QueryMsg as GeneratorQueryMsg,
+};
+use astroport::oracle::QueryMsg as OracleQueryMsg;
+use astroport::pair::{ExecuteMsg as PairExecuteMsg, PoolResponse, QueryMsg as PairQueryMsg};
+use astroport::querier::{query_supply, query_token_balance};
+use astroport::router::{
+    Cw20HookMsg as RouterHookMsg, ExecuteMsg as RouterExecuteMsg, FindBestRouteResponse,
+    QueryMsg as RouterQueryMsg,
+};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use protobuf::Message;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-autocompounder";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Share token information.
+const TOKEN_NAME: &str = "astroport-autocompounder-token";
+const TOKEN_SYMBOL: &str = "cLP";
+
+/// A `reply` call code ID of sub-message.
+const INSTANTIATE_TOKEN_REPLY_ID: u64 = 1;
+
+/// The default share of the compounded rewards paid to the keeper in percents
+const DEFAULT_BOUNTY: u64 = 1;
+/// The maximum share of the compounded rewards paid to the keeper in percents
+const MAX_BOUNTY: u64 = 10;
+/// The default max spread in percents
+const DEFAULT_MAX_SPREAD: u64 = 5;
+/// The maximum max spread in percents, bounded by the slippage tolerance of the pair
+const MAX_ALLOWED_SPREAD: u64 = 50;
+/// The amount of the ask asset which is priced to derive the minimum receive of a swap
+const PRICE_UNIT: u128 = 1_000000;
+/// The shares minted to the vault itself on the first deposit, so the share price can't be inflated
+const MINIMUM_SHARES: u128 = 1000;
+
+/// ## Description
+/// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
+/// Returns the [`Response`] with the specified attributes if the operation was successful,
+/// or the [`ContractError`] if the contract was not created.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **_info** is the object of type [`MessageInfo`].
+///
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for
+/// creating a contract
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let bounty = msg
+        .bounty
+        .unwrap_or_else(|| Decimal::percent(DEFAULT_BOUNTY));
+    validate_bounty(bounty)?;
+
+    let max_spread = msg
+        .max_spread
+        .unwrap_or_else(|| Decimal::percent(DEFAULT_MAX_SPREAD));
+    validate_max_spread(max_spread)?;
+
+    for asset in &msg.reward_assets {
+        asset.check(deps.api)?;
+    }
+    let price_sources = validate_price_sources(deps.as_ref(), msg.price_sources)?;
+
+    let pair = addr_validate_to_lower(deps.api, &msg.pair)?;
+    let pair_info: PairInfo = deps
+        .querier
+        .query_wasm_smart(&pair, &PairQueryMsg::Pair {})?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: addr_validate_to_lower(deps.api, &msg.owner)?,
+            generator: addr_validate_to_lower(deps.api, &msg.generator)?,
+            router: addr_validate_to_lower(deps.api, &msg.router)?,
+            pair,
+            lp_token: pair_info.liquidity_token,
+            share_token: Addr::unchecked(""),
+            pair_assets: pair_info.asset_infos,
+            reward_assets: msg.reward_assets,
+            price_sources,
+            bounty,
+            max_spread,
+        },
+    )?;
+
+    // Create the share token
+    let sub_msg: Vec<SubMsg> = vec![SubMsg {
+        msg: WasmMsg::Instantiate {
+            admin: None,
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: TOKEN_NAME.to_string(),
+                symbol: TOKEN_SYMBOL.to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            label: String::from("Astroport Autocompounder Token"),
+        }
+        .into(),
+        id: INSTANTIATE_TOKEN_REPLY_ID,
+        gas_limit: None,
+        reply_on: ReplyOn::Success,
+    }];
+
+    Ok(Response::new().add_submessages(sub_msg))
+}
+
+/// ## Description
+/// Available the execute messages of the contract.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **msg** is the object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::Compound {}** Claims the rewards of the vault and compounds them into LP tokens.
+///
+/// * **ExecuteMsg::UpdateConfig {
+///             reward_assets,
+///             price_sources,
+///             bounty,
+///             max_spread,
+///         }** Updates general settings that contains in the [`Config`].
+///
+/// * **ExecuteMsg::Callback(msg)** Private method used by contract to run the compounding steps.
+///
+/// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a new request to change ownership.
+///
+/// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change ownership.
+///
+/// * **ExecuteMsg::ClaimOwnership {}** Approves owner.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::Compound {} => compound(deps, env, info),
+        ExecuteMsg::UpdateConfig {
+            reward_assets,
+            price_sources,
+            bounty,
+            max_spread,
+        } => update_config(deps, info, reward_assets, price_sources, bounty, max_spread),
+        ExecuteMsg::Callback(msg) => {
+            if info.sender != env.contract.address {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            match msg {
+                CallbackMsg::SwapRewards { keeper } => swap_rewards(deps, env, keeper),
+                CallbackMsg::ProvideLiquidity {} => provide_liquidity(deps, env),
+                CallbackMsg::Stake { lp_balance_before } => stake(deps, env, lp_balance_before),
+            }
+        }
+        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            propose_new_owner(
+                deps,
+                info,
+                env,
+                owner,
+                expires_in,
+                config.owner,
+                OWNERSHIP_PROPOSAL,
+            )
+            .map_err(|e| e.into())
+        }
+        ExecuteMsg::DropOwnershipProposal {} => {
+            let config: Config = CONFIG.load(deps.storage)?;
+
+            drop_ownership_proposal(deps, info, config.owner, OWNERSHIP_PROPOSAL)
+                .map_err(|e| e.into())
+        }
+        ExecuteMsg::ClaimOwnership {} => {
+            claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
+                CONFIG.update::<_, StdError>(deps.storage, |mut v| {
+                    v.owner = new_owner;
+                    Ok(v)
+                })?;
+
+                Ok(())
+            })
+            .map_err(|e| e.into())
+        }
+    }
+}
+
+/// # Description
+/// The entry point to the contract for processing the reply from the submessage.
+/// Sets the share token contract address.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    if config.share_token != Addr::unchecked("") {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let data = msg.result.unwrap().data.unwrap();
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+        })?;
+
+    config.share_token = addr_validate_to_lower(deps.api, res.get_contract_address())?;
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new())
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise returns the [`Response`] with the specified attributes if the operation was successful
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is the object of type [`Cw20ReceiveMsg`].
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    let recipient = cw20_msg.sender;
+    let amount = cw20_msg.amount;
+
+    let total_deposit = query_total_deposit(deps.as_ref(), &env, &config)?;
+    let total_shares = query_supply(&deps.querier, config.share_token.clone())?;
+
+    match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Deposit {} => {
+            if info.sender != config.lp_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let mut messages = vec![stake_msg(&config, amount)?];
+
+            // The deposited LP tokens are staked after the shares are calculated,
+            // so the total deposit doesn't include them yet
+            let mint_amount = if total_shares.is_zero() || total_deposit.is_zero() {
+                // The minimum shares are locked in the vault forever
+                let minimum_shares = Uint128::from(MINIMUM_SHARES);
+                if total_shares.is_zero() {
+                    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                        contract_addr: config.share_token.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::Mint {
+                            recipient: env.contract.address.to_string(),
+                            amount: minimum_shares,
+                        })?,
+                        funds: vec![],
+                    }));
+                }
+
+                amount
+                    .checked_sub(minimum_shares)
+                    .map_err(|_| ContractError::DepositTooSmall {})?
+            } else {
+                amount
+                    .checked_mul(total_shares)?
+                    .checked_div(total_deposit)
+                    .map_err(|e| StdError::DivideByZero { source: e })?
+            };
+
+            if mint_amount.is_zero() {
+                return Err(ContractError::DepositTooSmall {});
+            }
+
+            Ok(Response::new()
+                .add_messages(messages)
+                .add_message(WasmMsg::Execute {
+                    contract_addr: config.share_token.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Mint {
+                        recipient: recipient.clone(),
+                        amount: mint_amount,
+                    })?,
+                    funds: vec![],
+                })
+                .add_attributes(vec![
+                    attr("action", "deposit"),
+                    attr("user", recipient),
+                    attr("amount", amount),
+                    attr("shares", mint_amount),
+                ]))
+        }
+        Cw20HookMsg::Withdraw {} => {
+            if info.sender != config.share_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let lp_amount = amount
+                .checked_mul(total_deposit)?
+                .checked_div(total_shares)
+                .map_err(|e| StdError::DivideByZero { source: e })?;
+
+            Ok(Response::new()
+                .add_message(WasmMsg::Execute {
+                    contract_addr: config.share_token.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+                    funds: vec![],
+                })
+                .add_message(WasmMsg::Execute {
+                    contract_addr: config.generator.to_string(),
+                    msg: to_binary(&GeneratorExecuteMsg::Withdraw {
+                        lp_token: config.lp_token.clone(),
+                        amount: lp_amount,
+                    })?,
+                    funds: vec![],
+                })
+                .add_message(WasmMsg::Execute {
+                    contract_addr: config.lp_token.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: recipient.clone(),
+                        amount: lp_amount,
+                    })?,
+                    funds: vec![],
+                })
+                .add_attributes(vec![
+                    attr("action", "withdraw"),
+                    attr("user", recipient),
+                    attr("amount", lp_amount),
+                    attr("shares", amount),
+                ]))
+        }
+    }
+}
+
+/// ## Description
+/// Claims the rewards of the vault from the generator and continues with
+/// [`CallbackMsg::SwapRewards`]. Returns an [`ContractError`] on failure, otherwise returns
+/// the [`Response`] with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// ##Executor
+/// Anyone can execute it
+fn compound(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if query_total_deposit(deps.as_ref(), &env, &config)?.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.generator.to_string(),
+            msg: to_binary(&GeneratorExecuteMsg::ClaimRewards {
+                lp_tokens: vec![config.lp_token.to_string()],
+            })?,
+            funds: vec![],
+        })
+        .add_message(callback_msg(
+            &env,
+            CallbackMsg::SwapRewards {
+                keeper: info.sender.clone(),
+            },
+        )?)
+        .add_attributes(vec![
+            attr("action", "compound"),
+            attr("keeper", info.sender),
+        ]))
+}
+
+/// ## Description
+/// Pays the bounty to the keeper and swaps the rest of each reward asset in halves into the pair
+/// assets, then continues with [`CallbackMsg::ProvideLiquidity`]. Returns an [`ContractError`]
+/// on failure, otherwise returns the [`Response`] with the specified attributes if the operation
+/// was successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **keeper** is the object of type [`Addr`]. The caller of `Compound`.
+///
+/// ##Executor
+/// Only the autocompounder itself can execute it
+fn swap_rewards(deps: DepsMut, env: Env, keeper: Addr) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut attributes = vec![attr("action", "swap_rewards")];
+
+    for reward in &config.reward_assets {
+        let balance = reward.query_pool(&deps.querier, env.contract.address.clone())?;
+        if balance.is_zero() {
+            continue;
+        }
+
+        let bounty = balance * config.bounty;
+        if !bounty.is_zero() {
+            messages.push(
+                Asset {
+                    info: reward.clone(),
+                    amount: bounty,
+                }
+                .into_msg(&deps.querier, keeper.clone())?,
+            );
+            attributes.push(attr("bounty", format!("{}{}", bounty, reward)));
+        }
+
+        let amount = balance.checked_sub(bounty)?;
+        let half = amount.multiply_ratio(1u128, 2u128);
+        let halves = vec![half, amount.checked_sub(half)?];
+
+        for (ask, offer_amount) in config.pair_assets.iter().zip(halves) {
+            // The half of the reward which is a pair asset itself is provided as it is
+            if reward.equal(ask) || offer_amount.is_zero() {
+                continue;
+            }
+
+            messages.push(build_swap_msg(
+                deps.as_ref(),
+                &config,
+                reward,
+                ask,
+                offer_amount,
+            )?);
+        }
+    }
+
+    messages.push(callback_msg(&env, CallbackMsg::ProvideLiquidity {})?);
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// ## Description
+/// Builds the message that swaps the offer asset into the ask asset by the best route of the
+/// router. The minimum receive is derived from the prices of the assets in the price sources
+/// rather than from the pools on the route, so a manipulation of the pools before the swap
+/// is bounded by the max spread. Returns an [`ContractError`] on failure.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **config** is the object of type [`Config`].
+///
+/// * **offer** is the object of type [`AssetInfo`].
+///
+/// * **ask** is the object of type [`AssetInfo`].
+///
+/// * **amount** is the object of type [`Uint128`].
+fn build_swap_msg(
+    deps: Deps,
+    config: &Config,
+    offer: &AssetInfo,
+    ask: &AssetInfo,
+    amount: Uint128,
+) -> Result<CosmosMsg, ContractError> {
+    let mut offer_asset = Asset {
+        info: offer.clone(),
+        amount,
+    };
+
+    let funds = if offer.is_native_token() {
+        // deduct tax first
+        let coin = offer_asset.deduct_tax(&deps.querier)?;
+        offer_asset.amount = coin.amount;
+        vec![coin]
+    } else {
+        vec![]
+    };
+
+    let route: FindBestRouteResponse = deps.querier.query_wasm_smart(
+        &config.router,
+        &RouterQueryMsg::FindBestRoute {
+            offer_asset: offer_asset.clone(),
+            ask_asset_info: ask.clone(),
+            max_hops: None,
+        },
+    )?;
+
+    let offer_value = asset_value(deps, config, &offer_asset)?;
+    let ask_unit_value = asset_value(
+        deps,
+        config,
+        &Asset {
+            info: ask.clone(),
+            amount: Uint128::from(PRICE_UNIT),
+        },
+    )?;
+    if ask_unit_value.is_zero() {
+        return Err(ContractError::ZeroPrice(ask.to_string()));
+    }
+
+    let expected_amount = offer_value.multiply_ratio(PRICE_UNIT, ask_unit_value);
+    let minimum_receive = expected_amount.checked_sub(expected_amount * config.max_spread)?;
+
+    if offer.is_native_token() {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.router.to_string(),
+            msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                operations: route.operations,
+                minimum_receive: Some(minimum_receive),
+                to: None,
+                referral: None,
+            })?,
+            funds,
+        }))
+    } else {
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: offer.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: config.router.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&RouterHookMsg::ExecuteSwapOperations {
+                    operations: route.operations,
+                    minimum_receive: Some(minimum_receive),
+                    to: None,
+                    referral: None,
+                })?,
+            })?,
+            funds,
+        }))
+    }
+}
+
+/// ## Description
+/// Provides the balances of the pair assets as liquidity at the ratio of the pool and continues
+/// with [`CallbackMsg::Stake`] of the received LP tokens. The remainder of the other asset stays in the vault until the
+/// next compounding. Returns an [`ContractError`] on failure, otherwise returns the [`Response`]
+/// with the specified attributes if the operation was successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// ##Executor
+/// Only the autocompounder itself can execute it
+fn provide_liquidity(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let mut amounts = vec![];
+    for info in &config.pair_assets {
+        amounts.push(info.query_pool(&deps.querier, env.contract.address.clone())?);
+    }
+
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(&config.pair, &PairQueryMsg::Pool {})?;
+
+    // The pair doesn't refund the excess of one of the assets, so the ratio of the pool is kept
+    let reserves = [pool.assets[0].amount, pool.assets[1].amount];
+    if !reserves[0].is_zero() && !reserves[1].is_zero() {
+        let matched = amounts[0].multiply_ratio(reserves[1], reserves[0]);
+        if matched <= amounts[1] {
+            amounts[1] = matched;
+        } else {
+            amounts[0] = amounts[1].multiply_ratio(reserves[0], reserves[1]);
+        }
+    }
+
+    if amounts.iter().any(|amount| amount.is_zero()) {
+        return Ok(Response::new().add_attribute("action", "provide_liquidity"));
+    }
+
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut funds: Vec<Coin> = vec![];
+    let mut assets = vec![];
+
+    for (info, amount) in config.pair_assets.iter().zip(amounts) {
+        let mut asset = Asset {
+            info: info.clone(),
+            amount,
+        };
+
+        if info.is_native_token() {
+            let coin = asset.deduct_tax(&deps.querier)?;
+            asset.amount = coin.amount;
+            funds.push(coin);
+        } else {
+            messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: info.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: config.pair.to_string(),
+                    amount,
+                    expires: None,
+                })?,
+                funds: vec![],
+            }));
+        }
+
+        assets.push(asset);
+    }
+
+    funds.sort_by(|a, b| a.denom.cmp(&b.denom));
+
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.pair.to_string(),
+        msg: to_binary(&PairExecuteMsg::ProvideLiquidity {
+            assets: [assets[0].clone(), assets[1].clone()],
+            slippage_tolerance: Some(config.max_spread),
+            auto_stake: None,
+            receiver: None,
+        })?,
+        funds,
+    }));
+    let lp_balance_before = query_token_balance(
+        &deps.querier,
+        config.lp_token.clone(),
+        env.contract.address.clone(),
+    )?;
+    messages.push(callback_msg(
+        &env,
+        CallbackMsg::Stake { lp_balance_before },
+    )?);
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        attr("action", "provide_liquidity"),
+        attr("assets", format!("{}, {}", assets[0], assets[1])),
+    ]))
+}
+
+/// ## Description
+/// Stakes the LP tokens received by the vault from the provide in the generator. The LP tokens
+/// sent to the vault directly are not staked, so they can't change the share price.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified
+/// attributes if the operation was successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_balance_before** is the object of type [`Uint128`]. The LP token balance of the vault
+/// before the provide.
+///
+/// ##Executor
+/// Only the autocompounder itself can execute it
+fn stake(deps: DepsMut, env: Env, lp_balance_before: Uint128) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let amount = query_token_balance(&deps.querier, config.lp_token.clone(), env.contract.address)?
+        .checked_sub(lp_balance_before)?;
+    if amount.is_zero() {
+        return Ok(Response::new().add_attribute("action", "stake"));
+    }
+
+    Ok(Response::new()
+        .add_message(stake_msg(&config, amount)?)
+        .add_attributes(vec![attr("action", "stake"), attr("amount", amount)]))
+}
+
+/// ## Description
+/// Updates general settings. Returns an [`ContractError`] on failure or the following [`Config`]
+/// data will be updated if successful.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **reward_assets** is an [`Option`] field of type [`Vec<AssetInfo>`].
+///
+/// * **price_sources** is an [`Option`] field of type [`Vec<(AssetInfo, PriceSource)>`].
+///
+/// * **bounty** is an [`Option`] field of type [`Decimal`].
+///
+/// * **max_spread** is an [`Option`] field of type [`Decimal`].
+///
+/// ##Executor
+/// Only owner can execute it
+fn update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_assets: Option<Vec<AssetInfo>>,
+    price_sources: Option<Vec<(AssetInfo, PriceSource)>>,
+    bounty: Option<Decimal>,
+    max_spread: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let mut attributes = vec![attr("action", "set_config")];
+
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(reward_assets) = reward_assets {
+        for asset in &reward_assets {
+            asset.check(deps.api)?;
+        }
+
+        config.reward_assets = reward_assets;
+    }
+
+    if let Some(price_sources) = price_sources {
+        config.price_sources = validate_price_sources(deps.as_ref(), price_sources)?;
+    }
+
+    if let Some(bounty) = bounty {
+        validate_bounty(bounty)?;
+
+        config.bounty = bounty;
+        attributes.push(attr("bounty", bounty.to_string()));
+    }
+
+    if let Some(max_spread) = max_spread {
+        validate_max_spread(max_spread)?;
+
+        config.max_spread = max_spread;
+        attributes.push(attr("max_spread", max_spread.to_string()));
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(attributes))
+}
+
+/// ## Description
+/// Returns an [`ContractError`] if the bounty exceeds [`MAX_BOUNTY`].
+fn validate_bounty(bounty: Decimal) -> Result<(), ContractError> {
+    if bounty > Decimal::percent(MAX_BOUNTY) {
+        return Err(ContractError::IncorrectBounty {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns an [`ContractError`] if the max spread exceeds [`MAX_ALLOWED_SPREAD`].
+fn validate_max_spread(max_spread: Decimal) -> Result<(), ContractError> {
+    if max_spread > Decimal::percent(MAX_ALLOWED_SPREAD) {
+        return Err(ContractError::IncorrectMaxSpread {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the price sources with the validated assets and oracle addresses.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **sources** is a vector that contains the assets along with their price sources.
+fn validate_price_sources(
+    deps: Deps,
+    sources: Vec<(AssetInfo, PriceSource)>,
+) -> StdResult<Vec<(AssetInfo, PriceSource)>> {
+    sources
+        .into_iter()
+        .map(|(asset_info, source)| {
+            asset_info.check(deps.api)?;
+            let source = match source {
+                PriceSource::Oracle { contract } => PriceSource::Oracle {
+                    contract: addr_validate_to_lower(deps.api, contract.as_str())?,
+                },
+                fixed => fixed,
+            };
+            Ok((asset_info, source))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the value of the asset in the quote asset
+/// according to its price source.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **config** is the object of type [`Config`].
+///
+/// * **asset** is the object of type [`Asset`].
+fn asset_value(deps: Deps, config: &Config, asset: &Asset) -> Result<Uint128, ContractError> {
+    let (_, source) = config
+        .price_sources
+        .iter()
+        .find(|(info, _)| info.equal(&asset.info))
+        .ok_or_else(|| ContractError::PriceSourceNotFound(asset.info.to_string()))?;
+
+    Ok(match source {
+        PriceSource::Fixed { price } => asset.amount * *price,
+        PriceSource::Oracle { contract } => deps.querier.query_wasm_smart(
+            contract,
+            &OracleQueryMsg::Consult {
+                token: asset.info.clone(),
+                amount: asset.amount,
+            },
+        )?,
+    })
+}
+
+/// ## Description
+/// Returns the message that stakes the specified amount of LP tokens in the generator.
+/// ## Params
+/// * **config** is the object of type [`Config`].
+///
+/// * **amount** is the object of type [`Uint128`].
+fn stake_msg(config: &Config, amount: Uint128) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.lp_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Send {
+            contract: config.generator.to_string(),
+            amount,
+            msg: to_binary(&GeneratorHookMsg::Deposit {})?,
+        })?,
+        funds: vec![],
+    }))
+}
+
+/// ## Description
+/// Returns the message that calls the specified compounding step of the contract itself.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`CallbackMsg`].
+fn callback_msg(env: &Env, msg: CallbackMsg) -> StdResult<CosmosMsg> {
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        msg: to_binary(&ExecuteMsg::Callback(msg))?,
+        funds: vec![],
+    }))
+}
+
+/// ## Description
+/// Returns the amount of LP tokens staked by the vault in the generator.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **config** is the object of type [`Config`].
+fn query_total_deposit(deps: Deps, env: &Env, config: &Config) -> StdResult<Uint128> {
+    deps.querier.query_wasm_smart(
+        &config.generator,
+        &GeneratorQueryMsg::Deposit {
+            lp_token: config.lp_token.clone(),
+            user: env.contract.address.clone(),
+        },
+    )
+}
+
+/// # Description
+/// Describes all query messages.
+/// # Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Config {}** Returns information about the autocompounder configs
+/// in a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::State {}** Returns the total deposit and shares of the vault
+/// in a [`StateResponse`] object.
+///
+/// * **QueryMsg::Deposit { user }** Returns the amount of LP tokens the user's shares are worth.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    match msg {
+        QueryMsg::Config {} => to_binary(&ConfigResponse {
+            owner: config.owner,
+            generator: config.generator,
+            router: config.router,
+            pair: config.pair,
+            lp_token: config.lp_token,
+            share_token: config.share_token,
+            reward_assets: config.reward_assets,
+            price_sources: config.price_sources,
+            bounty: config.bounty,
+            max_spread: config.max_spread,
+        }),
+        QueryMsg::State {} => to_binary(&StateResponse {
+            total_deposit: query_total_deposit(deps, &env, &config)?,
+            total_shares: query_supply(&deps.querier, config.share_token)?,
+        }),
+        QueryMsg::Deposit { user } => {
+            let user = addr_validate_to_lower(deps.api, &user)?;
+            let shares = query_token_balance(&deps.querier, config.share_token.clone(), user)?;
+            let total_shares = query_supply(&deps.querier, config.share_token.clone())?;
+
+            let amount = if total_shares.is_zero() {
+                Uint128::zero()
+            } else {
+                shares.multiply_ratio(query_total_deposit(deps, &env, &config)?, total_shares)
+            };
+
+            to_binary(&amount)
+        }
+    }
+}
+
+/// ## Description
+/// Used for migration of contract. Returns the default object of type [`Response`].
+/// ## Params
+/// * **_deps** is the object of type [`DepsMut`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **_msg** is the object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
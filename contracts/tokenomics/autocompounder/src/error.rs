@@ -0,0 +1,37 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+/// ## Description
+/// This enum describes autocompounder contract errors!
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Incorrect max spread")]
+    IncorrectMaxSpread {},
+
+    #[error("Incorrect bounty")]
+    IncorrectBounty {},
+
+    #[error("The vault has no deposits to compound!")]
+    NothingToCompound {},
+
+    #[error("The deposit is too small to mint any shares!")]
+    DepositTooSmall {},
+
+    #[error("Price source for {0} is not set!")]
+    PriceSourceNotFound(String),
+
+    #[error("Price of {0} is zero!")]
+    ZeroPrice(String),
+}
+
+impl From<OverflowError> for ContractError {
+    fn from(o: OverflowError) -> Self {
+        StdError::from(o).into()
+    }
+}
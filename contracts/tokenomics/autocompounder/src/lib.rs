@@ -0,0 +1,5 @@
+pub mod contract;
+pub mod state;
+
+mod error;
+mod response;
@@ -0,0 +1,43 @@
+use astroport::asset::AssetInfo;
+use astroport::common::OwnershipProposal;
+use astroport::generator::PriceSource;
+use cosmwasm_std::{Addr, Decimal};
+use cw_storage_plus::Item;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure describes the main control config of the autocompounder.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// contract address that used for controls settings
+    pub owner: Addr,
+    /// the generator contract address
+    pub generator: Addr,
+    /// the router contract address
+    pub router: Addr,
+    /// the pair contract address
+    pub pair: Addr,
+    /// the LP token contract address
+    pub lp_token: Addr,
+    /// the share token contract address
+    pub share_token: Addr,
+    /// the assets of the pair
+    pub pair_assets: [AssetInfo; 2],
+    /// the assets received from the generator that are compounded
+    pub reward_assets: Vec<AssetInfo>,
+    /// the price sources of the reward and the pair assets which bound the swaps
+    pub price_sources: Vec<(AssetInfo, PriceSource)>,
+    /// the share of the compounded rewards paid to the caller of `Compound`
+    pub bounty: Decimal,
+    /// the maximum spread
+    pub max_spread: Decimal,
+}
+
+/// ## Description
+/// Stores config at the given key
+pub const CONFIG: Item<Config> = Item::new("config");
+
+/// ## Description
+/// Contains proposal for change ownership.
+pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
@@ -0,0 +1,905 @@
+use astroport::asset::{token_asset, token_asset_info, Asset, AssetInfo, PairInfo};
+use astroport::autocompounder::{
+    CallbackMsg, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+};
+use astroport::factory::{PairConfig, PairType};
+use astroport::generator::{
+    ExecuteMsg as GeneratorExecuteMsg, InstantiateMsg as GeneratorInstantiateMsg,
+    PendingTokenResponse, PriceSource, QueryMsg as GeneratorQueryMsg,
+};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use astroport::vesting::{
+    Cw20HookMsg as VestingHookMsg, InstantiateMsg as VestingInstantiateMsg, VestingAccount,
+    VestingSchedule, VestingSchedulePoint,
+};
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{to_binary, Addr, Decimal, Uint128, Uint64};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terra_multi_test::{next_block, App, BankKeeper, ContractWrapper, Executor, TerraMockQuerier};
+
+const OWNER: &str = "owner";
+const USER1: &str = "user1";
+const USER2: &str = "user2";
+const KEEPER: &str = "keeper";
+
+struct Instances {
+    astro_token: Addr,
+    token_a: Addr,
+    token_b: Addr,
+    lp_token: Addr,
+    generator: Addr,
+    compounder: Addr,
+    share_token: Addr,
+}
+
+#[test]
+fn compound_rewards() {
+    let mut app = mock_app();
+    let instances = instantiate_contracts(&mut app);
+
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let keeper = Addr::unchecked(KEEPER);
+
+    let err = app
+        .execute_contract(
+            keeper.clone(),
+            instances.compounder.clone(),
+            &ExecuteMsg::Compound {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "The vault has no deposits to compound!");
+
+    // Only the LP token of the pair can be deposited
+    let msg = Cw20ExecuteMsg::Send {
+        contract: instances.compounder.to_string(),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(1_000000),
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            instances.astro_token.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    transfer_tokens(&mut app, &instances.lp_token, &user1, 1000_000000);
+    transfer_tokens(&mut app, &instances.lp_token, &user2, 1000_000000);
+
+    // The first deposit must exceed the shares locked in the vault
+    let msg = Cw20ExecuteMsg::Send {
+        contract: instances.compounder.to_string(),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(1000),
+    };
+    let err = app
+        .execute_contract(user1.clone(), instances.lp_token.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The deposit is too small to mint any shares!"
+    );
+
+    deposit(&mut app, &instances, USER1, 1000_000000);
+    check_token_balance(&mut app, &instances.share_token, &user1, 999_999000);
+    check_token_balance(
+        &mut app,
+        &instances.share_token,
+        &instances.compounder,
+        1000,
+    );
+    check_token_balance(&mut app, &instances.lp_token, &user1, 0);
+
+    let state = query_state(&mut app, &instances.compounder);
+    assert_eq!(
+        state,
+        StateResponse {
+            total_deposit: Uint128::new(1000_000000),
+            total_shares: Uint128::new(1000_000000),
+        }
+    );
+
+    for _ in 0..10 {
+        app.update_block(|bi| next_block(bi));
+    }
+
+    let pending: PendingTokenResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &instances.generator,
+            &GeneratorQueryMsg::PendingToken {
+                lp_token: instances.lp_token.clone(),
+                user: instances.compounder.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(pending.pending, Uint128::new(100_000000));
+
+    // The compounding steps can't be called from outside
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            instances.compounder.clone(),
+            &ExecuteMsg::Callback(CallbackMsg::SwapRewards {
+                keeper: user1.clone(),
+            }),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    // Every reward and pair asset needs a price source
+    update_price_sources(
+        &mut app,
+        &instances.compounder,
+        fixed_prices(&[&instances.astro_token, &instances.token_a]),
+    );
+    let err = app
+        .execute_contract(
+            keeper.clone(),
+            instances.compounder.clone(),
+            &ExecuteMsg::Compound {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        format!("Price source for {} is not set!", instances.token_b)
+    );
+
+    // The pools can't be traded below the prices of the price sources by more than the max spread
+    let mut price_sources = fixed_prices(&[
+        &instances.astro_token,
+        &instances.token_a,
+        &instances.token_b,
+    ]);
+    price_sources[0].1 = PriceSource::Fixed {
+        price: Decimal::percent(110),
+    };
+    update_price_sources(&mut app, &instances.compounder, price_sources);
+    app.execute_contract(
+        keeper.clone(),
+        instances.compounder.clone(),
+        &ExecuteMsg::Compound {},
+        &[],
+    )
+    .unwrap_err();
+
+    update_price_sources(
+        &mut app,
+        &instances.compounder,
+        fixed_prices(&[
+            &instances.astro_token,
+            &instances.token_a,
+            &instances.token_b,
+        ]),
+    );
+
+    // Every swap has some price impact, so a zero max spread fails the compounding
+    update_max_spread(&mut app, &instances.compounder, Decimal::zero());
+    app.execute_contract(
+        keeper.clone(),
+        instances.compounder.clone(),
+        &ExecuteMsg::Compound {},
+        &[],
+    )
+    .unwrap_err();
+
+    // The LP tokens sent to the vault directly are not staked
+    transfer_tokens(
+        &mut app,
+        &instances.lp_token,
+        &instances.compounder,
+        1_000000,
+    );
+
+    update_max_spread(&mut app, &instances.compounder, Decimal::percent(5));
+    app.execute_contract(
+        keeper.clone(),
+        instances.compounder.clone(),
+        &ExecuteMsg::Compound {},
+        &[],
+    )
+    .unwrap();
+
+    // The keeper gets 1% of the rewards, the rest is compounded
+    check_token_balance(&mut app, &instances.astro_token, &keeper, 1_000000);
+    check_token_balance(&mut app, &instances.astro_token, &instances.compounder, 0);
+    check_token_balance(
+        &mut app,
+        &instances.lp_token,
+        &instances.compounder,
+        1_000000,
+    );
+
+    // 99 ASTRO are swapped into ~49.5 of each token and provided as ~49.5 LP tokens
+    let state = query_state(&mut app, &instances.compounder);
+    assert_eq!(state.total_shares, Uint128::new(1000_000000));
+    assert!(state.total_deposit > Uint128::new(1049_000000));
+    assert!(state.total_deposit < Uint128::new(1049_500000));
+
+    let user1_deposit: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &instances.compounder,
+            &QueryMsg::Deposit {
+                user: USER1.to_string(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        user1_deposit,
+        state
+            .total_deposit
+            .multiply_ratio(999_999000u128, 1000_000000u128)
+    );
+
+    // The deposit which is worth no shares is rejected
+    let msg = Cw20ExecuteMsg::Send {
+        contract: instances.compounder.to_string(),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(1),
+    };
+    let err = app
+        .execute_contract(user2.clone(), instances.lp_token.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The deposit is too small to mint any shares!"
+    );
+
+    // The later depositor gets fewer shares for the same amount of LP tokens
+    deposit(&mut app, &instances, USER2, 1000_000000);
+    let user2_shares = 1000_000000u128 * 1000_000000u128 / state.total_deposit.u128();
+    check_token_balance(&mut app, &instances.share_token, &user2, user2_shares);
+
+    let total_deposit = state.total_deposit.u128() + 1000_000000;
+    let total_shares = 1000_000000 + user2_shares;
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: instances.compounder.to_string(),
+        msg: to_binary(&Cw20HookMsg::Withdraw {}).unwrap(),
+        amount: Uint128::new(999_999000),
+    };
+    app.execute_contract(user1.clone(), instances.share_token.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &instances.share_token, &user1, 0);
+    check_token_balance(
+        &mut app,
+        &instances.lp_token,
+        &user1,
+        999_999000 * total_deposit / total_shares,
+    );
+
+    let state = query_state(&mut app, &instances.compounder);
+    assert_eq!(state.total_shares, Uint128::new(1000 + user2_shares));
+    assert_eq!(
+        state.total_deposit,
+        Uint128::new(total_deposit - 999_999000 * total_deposit / total_shares)
+    );
+}
+
+#[test]
+fn update_config() {
+    let mut app = mock_app();
+    let instances = instantiate_contracts(&mut app);
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&instances.compounder, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.lp_token, instances.lp_token);
+    assert_eq!(config.share_token, instances.share_token);
+    assert_eq!(config.bounty, Decimal::percent(1));
+    assert_eq!(config.max_spread, Decimal::percent(5));
+    assert_eq!(
+        config.reward_assets,
+        vec![token_asset_info(instances.astro_token.clone())]
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: None,
+        price_sources: None,
+        bounty: Some(Decimal::percent(2)),
+        max_spread: None,
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(USER1),
+            instances.compounder.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: None,
+        price_sources: None,
+        bounty: Some(Decimal::percent(11)),
+        max_spread: None,
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            instances.compounder.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Incorrect bounty");
+
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: None,
+        price_sources: None,
+        bounty: None,
+        max_spread: Some(Decimal::percent(51)),
+    };
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            instances.compounder.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Incorrect max spread");
+
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: Some(vec![
+            token_asset_info(instances.astro_token.clone()),
+            token_asset_info(instances.token_a.clone()),
+        ]),
+        price_sources: Some(fixed_prices(&[&instances.astro_token])),
+        bounty: Some(Decimal::percent(2)),
+        max_spread: Some(Decimal::percent(3)),
+    };
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        instances.compounder.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&instances.compounder, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(config.bounty, Decimal::percent(2));
+    assert_eq!(config.max_spread, Decimal::percent(3));
+    assert_eq!(config.reward_assets.len(), 2);
+    assert_eq!(
+        config.price_sources,
+        fixed_prices(&[&instances.astro_token])
+    );
+}
+
+fn mock_app() -> App {
+    let api = MockApi::default();
+    let env = mock_env();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let terra_mock_querier = TerraMockQuerier::new(MockQuerier::new(&[]));
+
+    App::new(api, env.block, bank, storage, terra_mock_querier)
+}
+
+fn instantiate_contracts(app: &mut App) -> Instances {
+    let owner = Addr::unchecked(OWNER);
+
+    let token_contract = Box::new(ContractWrapper::new(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+    let token_code_id = app.store_code(token_contract);
+
+    let astro_token = instantiate_token(app, token_code_id, "ASTRO");
+    let token_a = instantiate_token(app, token_code_id, "TOKA");
+    let token_b = instantiate_token(app, token_code_id, "TOKB");
+
+    let pair_contract = Box::new(
+        ContractWrapper::new(
+            astroport_pair::contract::execute,
+            astroport_pair::contract::instantiate,
+            astroport_pair::contract::query,
+        )
+        .with_reply(astroport_pair::contract::reply),
+    );
+    let pair_code_id = app.store_code(pair_contract);
+
+    let factory_contract = Box::new(
+        ContractWrapper::new(
+            astroport_factory::contract::execute,
+            astroport_factory::contract::instantiate,
+            astroport_factory::contract::query,
+        )
+        .with_reply(astroport_factory::contract::reply),
+    );
+    let factory_code_id = app.store_code(factory_contract);
+
+    let msg = astroport::factory::InstantiateMsg {
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 0,
+            maker_fee_bps: 0,
+            is_disabled: None,
+        }],
+        token_code_id,
+        fee_address: None,
+        owner: owner.to_string(),
+        generator_address: None,
+    };
+    let factory = app
+        .instantiate_contract(factory_code_id, owner.clone(), &msg, &[], "Factory", None)
+        .unwrap();
+
+    create_pair(app, &factory, &astro_token, &token_a);
+    create_pair(app, &factory, &astro_token, &token_b);
+    let pair_info = create_pair(app, &factory, &token_a, &token_b);
+
+    let router_contract = Box::new(ContractWrapper::new(
+        mock_router::execute,
+        mock_router::instantiate,
+        mock_router::query,
+    ));
+    let router_code_id = app.store_code(router_contract);
+
+    let router = app
+        .instantiate_contract(
+            router_code_id,
+            owner.clone(),
+            &astroport::router::InstantiateMsg {
+                astroport_factory: factory.to_string(),
+                owner: owner.to_string(),
+                max_hops: None,
+                max_route_candidates: None,
+                max_referral_commission_bps: None,
+            },
+            &[],
+            "Router",
+            None,
+        )
+        .unwrap();
+
+    let generator = instantiate_generator(app, &astro_token);
+
+    let msg = GeneratorExecuteMsg::Add {
+        alloc_point: Uint64::from(100u64),
+        reward_proxy: None,
+        lp_token: pair_info.liquidity_token.clone(),
+    };
+    app.execute_contract(owner.clone(), generator.clone(), &msg, &[])
+        .unwrap();
+
+    let compounder_contract = Box::new(
+        ContractWrapper::new(
+            astroport_autocompounder::contract::execute,
+            astroport_autocompounder::contract::instantiate,
+            astroport_autocompounder::contract::query,
+        )
+        .with_reply(astroport_autocompounder::contract::reply),
+    );
+    let compounder_code_id = app.store_code(compounder_contract);
+
+    let msg = InstantiateMsg {
+        owner: owner.to_string(),
+        token_code_id,
+        generator: generator.to_string(),
+        router: router.to_string(),
+        pair: pair_info.contract_addr.to_string(),
+        reward_assets: vec![token_asset_info(astro_token.clone())],
+        price_sources: fixed_prices(&[&astro_token, &token_a, &token_b]),
+        bounty: None,
+        max_spread: None,
+    };
+    let compounder = app
+        .instantiate_contract(
+            compounder_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            "Autocompounder",
+            None,
+        )
+        .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&compounder, &QueryMsg::Config {})
+        .unwrap();
+
+    Instances {
+        astro_token,
+        token_a,
+        token_b,
+        lp_token: pair_info.liquidity_token,
+        generator,
+        compounder,
+        share_token: config.share_token,
+    }
+}
+
+fn instantiate_token(app: &mut App, token_code_id: u64, name: &str) -> Addr {
+    let msg = TokenInstantiateMsg {
+        name: name.to_string(),
+        symbol: name.to_string(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: None,
+        }),
+    };
+
+    app.instantiate_contract(
+        token_code_id,
+        Addr::unchecked(OWNER),
+        &msg,
+        &[],
+        name.to_string(),
+        None,
+    )
+    .unwrap()
+}
+
+fn instantiate_generator(app: &mut App, astro_token: &Addr) -> Addr {
+    let owner = Addr::unchecked(OWNER);
+
+    let vesting_contract = Box::new(ContractWrapper::new(
+        astroport_vesting::contract::execute,
+        astroport_vesting::contract::instantiate,
+        astroport_vesting::contract::query,
+    ));
+    let vesting_code_id = app.store_code(vesting_contract);
+
+    let vesting = app
+        .instantiate_contract(
+            vesting_code_id,
+            owner.clone(),
+            &VestingInstantiateMsg {
+                token_addr: astro_token.to_string(),
+            },
+            &[],
+            "Vesting",
+            None,
+        )
+        .unwrap();
+
+    let generator_contract = Box::new(
+        ContractWrapper::new(
+            astroport_generator::contract::execute,
+            astroport_generator::contract::instantiate,
+            astroport_generator::contract::query,
+        )
+        .with_reply(astroport_generator::contract::reply),
+    );
+    let generator_code_id = app.store_code(generator_contract);
+
+    let msg = GeneratorInstantiateMsg {
+        owner: owner.to_string(),
+        allowed_reward_proxies: vec![],
+        start_block: Uint64::from(app.block_info().height),
+        astro_token: astro_token.to_string(),
+        tokens_per_block: Uint128::new(10_000000),
+        vesting_contract: vesting.to_string(),
+        tokens_per_second: None,
+        start_time: None,
+    };
+    let generator = app
+        .instantiate_contract(generator_code_id, owner.clone(), &msg, &[], "Guage", None)
+        .unwrap();
+
+    let amount = Uint128::new(63072000_000000);
+    mint_tokens(app, astro_token, &owner, amount.u128());
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: vesting.to_string(),
+        msg: to_binary(&VestingHookMsg::RegisterVestingAccounts {
+            vesting_accounts: vec![VestingAccount {
+                address: generator.to_string(),
+                schedules: vec![VestingSchedule {
+                    start_point: VestingSchedulePoint {
+                        time: app.block_info().time,
+                        amount,
+                    },
+                    end_point: None,
+                }],
+            }],
+        })
+        .unwrap(),
+        amount,
+    };
+    app.execute_contract(owner, astro_token.clone(), &msg, &[])
+        .unwrap();
+
+    generator
+}
+
+fn create_pair(app: &mut App, factory: &Addr, token_x: &Addr, token_y: &Addr) -> PairInfo {
+    let owner = Addr::unchecked(OWNER);
+    let amount = 1_000_000_000000u128;
+
+    let asset_infos = [
+        token_asset_info(token_x.clone()),
+        token_asset_info(token_y.clone()),
+    ];
+
+    app.execute_contract(
+        owner.clone(),
+        factory.clone(),
+        &astroport::factory::ExecuteMsg::CreatePair {
+            pair_type: PairType::Xyk {},
+            asset_infos: asset_infos.clone(),
+            init_params: None,
+            initial_alloc_point: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    let pair_info: PairInfo = app
+        .wrap()
+        .query_wasm_smart(factory, &astroport::factory::QueryMsg::Pair { asset_infos })
+        .unwrap();
+
+    for token in [token_x, token_y].iter() {
+        mint_tokens(app, token, &owner, amount);
+
+        let msg = Cw20ExecuteMsg::IncreaseAllowance {
+            spender: pair_info.contract_addr.to_string(),
+            amount: Uint128::new(amount),
+            expires: None,
+        };
+        app.execute_contract(owner.clone(), (*token).clone(), &msg, &[])
+            .unwrap();
+    }
+
+    let assets: [Asset; 2] = [
+        token_asset(token_x.clone(), Uint128::new(amount)),
+        token_asset(token_y.clone(), Uint128::new(amount)),
+    ];
+    app.execute_contract(
+        owner,
+        pair_info.contract_addr.clone(),
+        &astroport::pair::ExecuteMsg::ProvideLiquidity {
+            assets,
+            slippage_tolerance: None,
+            auto_stake: None,
+            receiver: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    pair_info
+}
+
+fn mint_tokens(app: &mut App, token: &Addr, recipient: &Addr, amount: u128) {
+    let msg = Cw20ExecuteMsg::Mint {
+        recipient: recipient.to_string(),
+        amount: Uint128::from(amount),
+    };
+
+    app.execute_contract(Addr::unchecked(OWNER), token.to_owned(), &msg, &[])
+        .unwrap();
+}
+
+fn transfer_tokens(app: &mut App, token: &Addr, recipient: &Addr, amount: u128) {
+    let msg = Cw20ExecuteMsg::Transfer {
+        recipient: recipient.to_string(),
+        amount: Uint128::from(amount),
+    };
+
+    app.execute_contract(Addr::unchecked(OWNER), token.to_owned(), &msg, &[])
+        .unwrap();
+}
+
+fn deposit(app: &mut App, instances: &Instances, depositor: &str, amount: u128) {
+    let msg = Cw20ExecuteMsg::Send {
+        contract: instances.compounder.to_string(),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        amount: Uint128::from(amount),
+    };
+
+    app.execute_contract(
+        Addr::unchecked(depositor),
+        instances.lp_token.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap();
+}
+
+fn update_max_spread(app: &mut App, compounder: &Addr, max_spread: Decimal) {
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: None,
+        price_sources: None,
+        bounty: None,
+        max_spread: Some(max_spread),
+    };
+
+    app.execute_contract(Addr::unchecked(OWNER), compounder.clone(), &msg, &[])
+        .unwrap();
+}
+
+fn update_price_sources(
+    app: &mut App,
+    compounder: &Addr,
+    price_sources: Vec<(AssetInfo, PriceSource)>,
+) {
+    let msg = ExecuteMsg::UpdateConfig {
+        reward_assets: None,
+        price_sources: Some(price_sources),
+        bounty: None,
+        max_spread: None,
+    };
+
+    app.execute_contract(Addr::unchecked(OWNER), compounder.clone(), &msg, &[])
+        .unwrap();
+}
+
+fn fixed_prices(tokens: &[&Addr]) -> Vec<(AssetInfo, PriceSource)> {
+    tokens
+        .iter()
+        .map(|token| {
+            (
+                token_asset_info((*token).clone()),
+                PriceSource::Fixed {
+                    price: Decimal::one(),
+                },
+            )
+        })
+        .collect()
+}
+
+fn query_state(app: &mut App, compounder: &Addr) -> StateResponse {
+    app.wrap()
+        .query_wasm_smart(compounder, &QueryMsg::State {})
+        .unwrap()
+}
+
+fn check_token_balance(app: &mut App, token: &Addr, address: &Addr, expected: u128) {
+    let msg = Cw20QueryMsg::Balance {
+        address: address.to_string(),
+    };
+    let res: BalanceResponse = app.wrap().query_wasm_smart(token, &msg).unwrap();
+    assert_eq!(res.balance, Uint128::from(expected));
+}
+
+/// A router which swaps CW20 tokens through the direct pair of the factory. The minimum receive
+/// is enforced by the pair itself as the belief price with zero spread.
+mod mock_router {
+    use astroport::asset::{Asset, AssetInfo};
+    use astroport::pair::{
+        Cw20HookMsg as PairHookMsg, QueryMsg as PairQueryMsg, SimulationResponse,
+    };
+    use astroport::querier::query_pair_info;
+    use astroport::router::{
+        Cw20HookMsg, ExecuteMsg, FindBestRouteResponse, InstantiateMsg, QueryMsg,
+        SimulateSwapOperationsResponse, SwapOperation,
+    };
+    use cosmwasm_std::{
+        from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+        StdError, StdResult, Uint128, WasmMsg,
+    };
+    use cw20::Cw20ExecuteMsg;
+    use cw_storage_plus::Item;
+
+    const FACTORY: Item<Addr> = Item::new("factory");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        FACTORY.save(deps.storage, &Addr::unchecked(msg.astroport_factory))?;
+        Ok(Response::default())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        let cw20_msg = match msg {
+            ExecuteMsg::Receive(cw20_msg) => cw20_msg,
+            _ => return Err(StdError::generic_err("unsupported")),
+        };
+
+        match from_binary(&cw20_msg.msg)? {
+            Cw20HookMsg::ExecuteSwapOperations {
+                operations,
+                minimum_receive,
+                ..
+            } => {
+                let pair = pair_address(deps.as_ref(), &operations[0])?;
+
+                let (belief_price, max_spread) = match minimum_receive {
+                    Some(minimum_receive) => (
+                        Some(Decimal::from_ratio(cw20_msg.amount, minimum_receive)),
+                        Some(Decimal::zero()),
+                    ),
+                    None => (None, None),
+                };
+
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: info.sender.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: pair.to_string(),
+                        amount: cw20_msg.amount,
+                        msg: to_binary(&PairHookMsg::Swap {
+                            belief_price,
+                            max_spread,
+                            to: Some(cw20_msg.sender),
+                        })?,
+                    })?,
+                    funds: vec![],
+                }))
+            }
+            _ => Err(StdError::generic_err("unsupported")),
+        }
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::FindBestRoute {
+                offer_asset,
+                ask_asset_info,
+                ..
+            } => {
+                let operations = vec![SwapOperation::AstroSwap {
+                    offer_asset_info: offer_asset.info.clone(),
+                    ask_asset_info,
+                }];
+                let amount = simulate(deps, &operations[0], offer_asset.amount)?;
+
+                to_binary(&FindBestRouteResponse { operations, amount })
+            }
+            QueryMsg::SimulateSwapOperations {
+                offer_amount,
+                operations,
+                ..
+            } => to_binary(&SimulateSwapOperationsResponse {
+                amount: simulate(deps, &operations[0], offer_amount)?,
+                referral_amount: Uint128::zero(),
+            }),
+            _ => Err(StdError::generic_err("unsupported")),
+        }
+    }
+
+    fn pair_address(deps: Deps, operation: &SwapOperation) -> StdResult<Addr> {
+        let asset_infos: [AssetInfo; 2] = [
+            operation.get_offer_asset_info(),
+            operation.get_target_asset_info(),
+        ];
+
+        Ok(
+            query_pair_info(&deps.querier, FACTORY.load(deps.storage)?, &asset_infos)?
+                .contract_addr,
+        )
+    }
+
+    fn simulate(deps: Deps, operation: &SwapOperation, amount: Uint128) -> StdResult<Uint128> {
+        let res: SimulationResponse = deps.querier.query_wasm_smart(
+            pair_address(deps, operation)?,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: operation.get_offer_asset_info(),
+                    amount,
+                },
+            },
+        )?;
+
+        Ok(res.return_amount)
+    }
+}
@@ -1,27 +1,44 @@
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo,
-    Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, Uint64, WasmMsg,
+    entry_point, from_binary, to_binary, Addr, Binary, ContractResult, Decimal, Deps, DepsMut,
+    Empty, Env, Event, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, Uint64, WasmMsg,
 };
-use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, MinterResponse};
+use cw_storage_plus::U64Key;
 
 use crate::error::ContractError;
 use crate::state::{
-    get_pools, update_user_balance, Config, ExecuteOnReply, PoolInfo, UserInfo, CONFIG,
-    OWNERSHIP_PROPOSAL, POOL_INFO, TMP_USER_ACTION, USER_INFO,
+    checkpoint_deposit, get_active_pools, get_pools, proxy_reward_debt, read_claim_operators,
+    read_epoch_votes, read_pools, read_user_positions, retired_proxy_rewards,
+    seed_deposit_snapshots, stream_reward_debt, update_user_balance, Config, ExecuteOnReply,
+    PoolInfo, ProxyCall, RetiredProxy, UserInfo, UserVotes, ACTIVE_POOLS, BLOCKS_PER_YEAR,
+    CLAIM_OPERATORS, CONFIG, EPOCH_VOTES, LAST_TUNED_EPOCH, MAX_INCENTIVES, MAX_INCENTIVE_DURATION,
+    MAX_INCENTIVE_FEE, MAX_LOCK_TIERS, MAX_REWARD_STREAMS, MAX_USER_LOCKS, OWNERSHIP_PROPOSAL,
+    POOL_INFO, SECONDS_PER_YEAR, SNAPSHOTS_SEED_CURSOR, SNAPSHOTS_START, TMP_PROXY_CALLS,
+    TMP_USER_ACTION, TOTAL_DEPOSITS, UNPAID_PROXY_REWARDS, USER_DEPOSITS, USER_INFO, USER_VOTES,
+    VOTING_POWER,
 };
-use astroport::asset::addr_validate_to_lower;
+use astroport::asset::{addr_validate_to_lower, Asset, AssetInfo};
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
-use astroport::querier::query_token_balance;
+use astroport::querier::{query_supply, query_token_balance};
 use astroport::DecimalCheckedOps;
 use astroport::{
     generator::{
-        ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PendingTokenResponse,
-        PoolLengthResponse, QueryMsg, RewardInfoResponse,
+        ClaimOperatorResponse, ConfigResponse, Cw20HookMsg, EmissionPeriod,
+        EmissionScheduleResponse, EpochVotesResponse, ExecuteMsg, IncentiveSchedule,
+        InstantiateMsg, LockTier, LockedPosition, MigrateMsg, PendingTokenResponse,
+        PoolAprResponse, PoolInfoResponse, PoolLengthResponse, PriceSource,
+        ProjectedAllocationsResponse, QueryMsg, RewardInfoResponse, RewardStream,
+        UserBoostResponse, UserPositionResponse, UserVotesResponse,
     },
     generator_proxy::{
         Cw20HookMsg as ProxyCw20HookMsg, ExecuteMsg as ProxyExecuteMsg, QueryMsg as ProxyQueryMsg,
     },
-    vesting::ExecuteMsg as VestingExecuteMsg,
+    oracle::QueryMsg as OracleQueryMsg,
+    pair::{PoolResponse, QueryMsg as PairQueryMsg},
+    vesting::{
+        ExecuteMsg as VestingExecuteMsg, QueryMsg as VestingQueryMsg, VestingAccountResponse,
+    },
 };
 use cw2::set_contract_version;
 
@@ -30,6 +47,15 @@ const CONTRACT_NAME: &str = "astroport-generator";
 /// Contract version that is used for migration.
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// The share of the deposit, in percents, that is counted for ASTRO rewards without any xASTRO.
+const BOOST_BASE_PERCENT: u128 = 40;
+
+/// The maximum sum of the gauge votes of a user in basis points.
+const MAX_VOTE_BPS: u32 = 10_000;
+
+/// The total allocation points distributed between the pools according to the gauge votes.
+const GAUGE_ALLOC_POINTS: u128 = 10_000;
+
 /// ## Description
 /// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
 /// Returns the default object of type [`Response`] if the operation was successful,
@@ -37,14 +63,14 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// ## Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
-/// * **_env** is the object of type [`Env`].
+/// * **env** is the object of type [`Env`].
 ///
 /// * **_info** is the object of type [`MessageInfo`].
 /// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating a contract
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
@@ -63,10 +89,21 @@ pub fn instantiate(
         start_block: msg.start_block,
         allowed_reward_proxies,
         vesting_contract: addr_validate_to_lower(deps.api, &msg.vesting_contract)?,
+        factory: None,
+        tokens_per_second: msg.tokens_per_second,
+        start_time: msg.start_time.unwrap_or_else(|| env.block.time.seconds()),
+        emission_schedule: vec![],
+        xastro_token: None,
+        gauge_epoch_length: None,
+        maker: None,
+        incentive_fee: Decimal::zero(),
+        price_sources: vec![],
+        reward_stream_assets: vec![],
     };
 
     CONFIG.save(deps.storage, &config)?;
     TMP_USER_ACTION.save(deps.storage, &None)?;
+    SNAPSHOTS_START.save(deps.storage, &env.block.height)?;
 
     Ok(Response::default())
 }
@@ -83,7 +120,15 @@ pub fn instantiate(
 /// * **msg** is the object of type [`ExecuteMsg`].
 ///
 /// ## Queries
-/// * **ExecuteMsg::UpdateConfig { vesting_contract }** Sets a new vesting contract.
+/// * **ExecuteMsg::UpdateConfig {
+///             vesting_contract,
+///             factory,
+///             xastro_token,
+///             gauge_epoch_length,
+///             maker,
+///             incentive_fee,
+///         }** Sets a new vesting contract, the factory contract, the xASTRO token,
+/// the length of gauge voting epochs, the maker contract and the incentive fee.
 ///
 /// * **ExecuteMsg::Add {
 ///             lp_token,
@@ -97,17 +142,30 @@ pub fn instantiate(
 ///             alloc_point,
 ///         }** Updates the given pool's ASTRO allocation point.
 ///
+/// * **ExecuteMsg::DeactivatePool { lp_token }** Stops ASTRO emissions of the given pool and
+/// excludes it from mass updates.
+///
 /// * **ExecuteMsg::MassUpdatePools {}** Updates reward variables for all pools.
 ///
 /// * **ExecuteMsg::UpdatePool { lp_token }** Updates reward variables of the given pool to be up-to-date.
 ///
 /// * **ExecuteMsg::Withdraw { lp_token, amount }** Withdraw LP tokens from Generator.
 ///
+/// * **ExecuteMsg::ClaimRewards { lp_tokens }** Sends pending rewards of the given pools.
+///
+/// * **ExecuteMsg::Checkpoint { lp_token, user }** Recalculates the boosted amount of the user's deposit.
+///
 /// * **ExecuteMsg::EmergencyWithdraw { lp_token }** Withdraw without caring about rewards.
 /// EMERGENCY ONLY.
 ///
 /// * **ExecuteMsg::SetAllowedRewardProxies { proxies }** Sets allowed reward proxies contracts.
 ///
+/// * **ExecuteMsg::SetPriceSources { sources }** Sets the price sources of the assets used to
+/// calculate the APR of the pools.
+///
+/// * **ExecuteMsg::SetRewardStreamAssets { assets }** Sets the assets allowed in external reward
+/// streams along with the minimum funding amounts.
+///
 /// * **ExecuteMsg::SendOrphanProxyReward {
 ///             recipient,
 ///             lp_token,
@@ -116,9 +174,63 @@ pub fn instantiate(
 /// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
 /// it depending on the received template.
 ///
+/// * **ExecuteMsg::FundRewardStream {
+///             lp_token,
+///             reward,
+///             end_time,
+///         }** Funds an external reward stream of the pool with native tokens.
+///
+/// * **ExecuteMsg::Incentivize {
+///             lp_token,
+///             reward,
+///             duration,
+///         }** Streams the native tokens to the stakers of the pool over the duration.
+///
 /// * **ExecuteMsg::SetTokensPerBlock { amount }** Sets a new count of tokens per block.
 /// Before that, we will need to update all pools so as not to lose rewards.
 ///
+/// * **ExecuteMsg::SetTokensPerSecond { amount }** Sets a new count of tokens per second
+/// for time-based emissions.
+///
+/// * **ExecuteMsg::SetEmissionSchedule { periods }** Sets the emission schedule for time-based emissions.
+///
+/// * **ExecuteMsg::SetLockTiers { lp_token, tiers }** Sets the lock-up tiers of the pool.
+///
+/// * **ExecuteMsg::ReattachRewardProxy { lp_token }** Enables the disabled reward proxy of the pool.
+///
+/// * **ExecuteMsg::ClaimUnpaidProxyRewards { reward_proxy }** Sends the proxy rewards of the sender
+/// which were not paid because of a failure of the proxy.
+///
+/// * **ExecuteMsg::MoveToProxy { lp_token, new_proxy }** Moves the LP tokens of the pool
+/// to the new reward proxy.
+///
+/// * **ExecuteMsg::DetachProxy { lp_token }** Withdraws the LP tokens of the pool from the reward
+/// proxy to the generator.
+///
+/// * **ExecuteMsg::TransferPosition {
+///             lp_token,
+///             amount,
+///             recipient,
+///         }** Moves a part of the sender's deposit to the recipient.
+///
+/// * **ExecuteMsg::SetClaimOperator { operator, recipient }** Allows the operator to claim
+/// the rewards of the sender to the recipient.
+///
+/// * **ExecuteMsg::RemoveClaimOperator { operator }** Revokes the claim allowance of the operator.
+///
+/// * **ExecuteMsg::ClaimRewardsFor { lp_tokens, user }** Claims the rewards of the user
+/// to the recipient set by the user.
+///
+/// * **ExecuteMsg::Vote { votes }** Votes for the pools with the xASTRO locked by the sender.
+///
+/// * **ExecuteMsg::UnlockVotingPower { amount }** Sends the locked xASTRO back to the sender.
+///
+/// * **ExecuteMsg::TuneAllocations {}** Sets the allocation points of the pools according to
+/// the votes of the previous epoch.
+///
+/// * **ExecuteMsg::SeedSnapshots { limit }** Writes the current deposits of the next users
+/// to the deposit snapshots.
+///
 /// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a new request to change ownership.
 ///
 /// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change ownership.
@@ -132,16 +244,30 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { vesting_contract } => {
-            execute_update_config(deps, info, vesting_contract)
-        }
+        ExecuteMsg::UpdateConfig {
+            vesting_contract,
+            factory,
+            xastro_token,
+            gauge_epoch_length,
+            maker,
+            incentive_fee,
+        } => execute_update_config(
+            deps,
+            info,
+            vesting_contract,
+            factory,
+            xastro_token,
+            gauge_epoch_length,
+            maker,
+            incentive_fee,
+        ),
         ExecuteMsg::Add {
             lp_token,
             alloc_point,
             reward_proxy,
         } => {
             let cfg = CONFIG.load(deps.storage)?;
-            if info.sender != cfg.owner {
+            if !is_owner_or_factory(&cfg, &info.sender) {
                 return Err(ContractError::Unauthorized {});
             }
 
@@ -161,7 +287,7 @@ pub fn execute(
             alloc_point,
         } => {
             let cfg = CONFIG.load(deps.storage)?;
-            if info.sender != cfg.owner {
+            if !is_owner_or_factory(&cfg, &info.sender) {
                 return Err(ContractError::Unauthorized {});
             }
 
@@ -175,40 +301,234 @@ pub fn execute(
                 },
             )
         }
+        ExecuteMsg::DeactivatePool { lp_token } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if !is_owner_or_factory(&cfg, &info.sender) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            update_rewards_and_execute(deps, env, None, ExecuteOnReply::DeactivatePool { lp_token })
+        }
         ExecuteMsg::MassUpdatePools {} => {
             update_rewards_and_execute(deps, env, None, ExecuteOnReply::MassUpdatePools {})
         }
         ExecuteMsg::UpdatePool { lp_token } => update_rewards_and_execute(
             deps,
             env,
-            Some(lp_token.clone()),
+            Some(vec![lp_token.clone()]),
             ExecuteOnReply::UpdatePool { lp_token },
         ),
         ExecuteMsg::Withdraw { lp_token, amount } => update_rewards_and_execute(
             deps,
             env,
-            Some(lp_token.clone()),
+            Some(vec![lp_token.clone()]),
             ExecuteOnReply::Withdraw {
                 lp_token,
                 account: info.sender,
                 amount,
             },
         ),
+        ExecuteMsg::ClaimRewards { lp_tokens } => {
+            let mut lp_tokens = lp_tokens
+                .iter()
+                .map(|lp_token| addr_validate_to_lower(deps.api, lp_token))
+                .collect::<StdResult<Vec<_>>>()?;
+            lp_tokens.sort();
+            lp_tokens.dedup();
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(lp_tokens.clone()),
+                ExecuteOnReply::ClaimRewards {
+                    lp_tokens,
+                    account: info.sender.clone(),
+                    recipient: info.sender,
+                },
+            )
+        }
+        ExecuteMsg::ClaimRewardsFor { lp_tokens, user } => {
+            let user = addr_validate_to_lower(deps.api, &user)?;
+            let recipient = CLAIM_OPERATORS
+                .may_load(deps.storage, (&user, &info.sender))?
+                .ok_or(ContractError::Unauthorized {})?;
+
+            let mut lp_tokens = lp_tokens
+                .iter()
+                .map(|lp_token| addr_validate_to_lower(deps.api, lp_token))
+                .collect::<StdResult<Vec<_>>>()?;
+            lp_tokens.sort();
+            lp_tokens.dedup();
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(lp_tokens.clone()),
+                ExecuteOnReply::ClaimRewards {
+                    lp_tokens,
+                    account: user,
+                    recipient,
+                },
+            )
+        }
+        ExecuteMsg::TransferPosition {
+            lp_token,
+            amount,
+            recipient,
+        } => {
+            let recipient = addr_validate_to_lower(deps.api, &recipient)?;
+            if recipient == info.sender {
+                return Err(ContractError::TransferToSelf {});
+            }
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::TransferPosition {
+                    lp_token,
+                    account: info.sender,
+                    recipient,
+                    amount,
+                },
+            )
+        }
+        ExecuteMsg::SetClaimOperator {
+            operator,
+            recipient,
+        } => set_claim_operator(deps, info, operator, recipient),
+        ExecuteMsg::RemoveClaimOperator { operator } => {
+            let operator = addr_validate_to_lower(deps.api, &operator)?;
+            CLAIM_OPERATORS.remove(deps.storage, (&info.sender, &operator));
+
+            Ok(Response::new()
+                .add_attribute("action", "remove_claim_operator")
+                .add_attribute("operator", operator))
+        }
+        ExecuteMsg::Checkpoint { lp_token, user } => {
+            let user = addr_validate_to_lower(deps.api, &user)?;
+
+            // A checkpoint is a zero withdrawal on behalf of the user, which sends the pending
+            // rewards and recalculates the boosted amount
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::Withdraw {
+                    lp_token,
+                    account: user,
+                    amount: Uint128::zero(),
+                },
+            )
+        }
         ExecuteMsg::EmergencyWithdraw { lp_token } => emergency_withdraw(deps, env, info, lp_token),
         ExecuteMsg::SetAllowedRewardProxies { proxies } => {
             set_allowed_reward_proxies(deps, info, proxies)
         }
+        ExecuteMsg::SetPriceSources { sources } => set_price_sources(deps, info, sources),
+        ExecuteMsg::SetRewardStreamAssets { assets } => {
+            set_reward_stream_assets(deps, info, assets)
+        }
+        ExecuteMsg::SetLockTiers { lp_token, tiers } => set_lock_tiers(deps, info, lp_token, tiers),
+        ExecuteMsg::ReattachRewardProxy { lp_token } => reattach_reward_proxy(deps, info, lp_token),
+        ExecuteMsg::ClaimUnpaidProxyRewards { reward_proxy } => {
+            claim_unpaid_proxy_rewards(deps, info, reward_proxy)
+        }
+        ExecuteMsg::MoveToProxy {
+            lp_token,
+            new_proxy,
+        } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if info.sender != cfg.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let new_proxy = addr_validate_to_lower(deps.api, &new_proxy)?;
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::MoveToProxy {
+                    lp_token,
+                    new_proxy: Some(new_proxy),
+                },
+            )
+        }
+        ExecuteMsg::DetachProxy { lp_token } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if info.sender != cfg.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                Some(vec![lp_token.clone()]),
+                ExecuteOnReply::MoveToProxy {
+                    lp_token,
+                    new_proxy: None,
+                },
+            )
+        }
+        ExecuteMsg::Vote { votes } => vote(deps, env, info, votes),
+        ExecuteMsg::UnlockVotingPower { amount } => unlock_voting_power(deps, env, info, amount),
+        ExecuteMsg::TuneAllocations {} => {
+            let cfg = CONFIG.load(deps.storage)?;
+            let epoch_length = cfg
+                .gauge_epoch_length
+                .ok_or(ContractError::GaugeVotingDisabled {})?;
+
+            // Only the votes of the finished epoch are applied
+            let epoch = (env.block.time.seconds() / epoch_length).saturating_sub(1);
+            if let Some(last_tuned_epoch) = LAST_TUNED_EPOCH.may_load(deps.storage)? {
+                if last_tuned_epoch >= epoch {
+                    return Err(ContractError::AllocationsAlreadyTuned(epoch));
+                }
+            }
+
+            update_rewards_and_execute(deps, env, None, ExecuteOnReply::TuneAllocations { epoch })
+        }
+        ExecuteMsg::SeedSnapshots { limit } => seed_snapshots(deps, env, info, limit),
         ExecuteMsg::SendOrphanProxyReward {
             recipient,
             lp_token,
         } => send_orphan_proxy_rewards(deps, info, recipient, lp_token),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::FundRewardStream {
+            lp_token,
+            reward,
+            end_time,
+        } => {
+            if !reward.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+            reward.assert_sent_native_token_balance(&info)?;
+
+            fund_reward_stream(deps, env, lp_token, reward, end_time)
+        }
+        ExecuteMsg::Incentivize {
+            lp_token,
+            reward,
+            duration,
+        } => {
+            if !reward.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+            reward.assert_sent_native_token_balance(&info)?;
+
+            incentivize(deps, env, info.sender, lp_token, reward, duration)
+        }
         ExecuteMsg::SetTokensPerBlock { amount } => {
             let cfg = CONFIG.load(deps.storage)?;
             if info.sender != cfg.owner {
                 return Err(ContractError::Unauthorized {});
             }
 
+            if cfg.tokens_per_second.is_some() {
+                return Err(ContractError::TimeBasedEmissions {});
+            }
+
             update_rewards_and_execute(
                 deps,
                 env,
@@ -216,6 +536,42 @@ pub fn execute(
                 ExecuteOnReply::SetTokensPerBlock { amount },
             )
         }
+        ExecuteMsg::SetTokensPerSecond { amount } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if info.sender != cfg.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if cfg.tokens_per_second.is_none() {
+                return Err(ContractError::BlockBasedEmissions {});
+            }
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                None,
+                ExecuteOnReply::SetTokensPerSecond { amount },
+            )
+        }
+        ExecuteMsg::SetEmissionSchedule { periods } => {
+            let cfg = CONFIG.load(deps.storage)?;
+            if info.sender != cfg.owner {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            if cfg.tokens_per_second.is_none() {
+                return Err(ContractError::BlockBasedEmissions {});
+            }
+
+            validate_emission_schedule(deps.as_ref(), &env, &cfg, &periods)?;
+
+            update_rewards_and_execute(
+                deps,
+                env,
+                None,
+                ExecuteOnReply::SetEmissionSchedule { periods },
+            )
+        }
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let config: Config = CONFIG.load(deps.storage)?;
 
@@ -251,7 +607,18 @@ pub fn execute(
 }
 
 /// ## Description
-/// Sets a new vesting contract. Returns an [`ContractError`] on failure or the following [`CONFIG`]
+/// Returns `true` if the `sender` is the owner or the factory contract specified in the [`Config`].
+/// ## Params
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **sender** is the object of type [`Addr`].
+fn is_owner_or_factory(cfg: &Config, sender: &Addr) -> bool {
+    *sender == cfg.owner || cfg.factory.as_ref() == Some(sender)
+}
+
+/// ## Description
+/// Sets a new vesting contract, factory, xASTRO token, the length of gauge voting epochs, maker
+/// and incentive fee. Returns an [`ContractError`] on failure or the following [`CONFIG`]
 /// data will be updated if successful.
 ///
 /// ## Params
@@ -260,12 +627,28 @@ pub fn execute(
 /// * **info** is the object of type [`MessageInfo`].
 ///
 /// * **vesting_contract** is an [`Option`] field object of type [`String`].
+///
+/// * **factory** is an [`Option`] field object of type [`String`].
+///
+/// * **xastro_token** is an [`Option`] field object of type [`String`].
+///
+/// * **gauge_epoch_length** is an [`Option`] field object of type [`u64`].
+///
+/// * **maker** is an [`Option`] field object of type [`String`].
+///
+/// * **incentive_fee** is an [`Option`] field object of type [`Decimal`].
 /// ##Executor
 /// Only owner can execute it
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut,
     info: MessageInfo,
     vesting_contract: Option<String>,
+    factory: Option<String>,
+    xastro_token: Option<String>,
+    gauge_epoch_length: Option<u64>,
+    maker: Option<String>,
+    incentive_fee: Option<Decimal>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
@@ -278,6 +661,32 @@ pub fn execute_update_config(
         config.vesting_contract = addr_validate_to_lower(deps.api, vesting_contract.as_str())?;
     }
 
+    if let Some(factory) = factory {
+        config.factory = Some(addr_validate_to_lower(deps.api, factory.as_str())?);
+    }
+
+    if let Some(xastro_token) = xastro_token {
+        config.xastro_token = Some(addr_validate_to_lower(deps.api, xastro_token.as_str())?);
+    }
+
+    if let Some(gauge_epoch_length) = gauge_epoch_length {
+        if gauge_epoch_length == 0 {
+            return Err(StdError::generic_err("Gauge epoch length must be positive!").into());
+        }
+        config.gauge_epoch_length = Some(gauge_epoch_length);
+    }
+
+    if let Some(maker) = maker {
+        config.maker = Some(addr_validate_to_lower(deps.api, maker.as_str())?);
+    }
+
+    if let Some(incentive_fee) = incentive_fee {
+        if incentive_fee > Decimal::percent(MAX_INCENTIVE_FEE) {
+            return Err(ContractError::IncorrectIncentiveFee(MAX_INCENTIVE_FEE));
+        }
+        config.incentive_fee = incentive_fee;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
@@ -299,7 +708,7 @@ pub fn execute_update_config(
 /// * **reward_proxy** is an [`Option`] field object of type [`String`].
 ///
 /// ##Executor
-/// Can only be called by the owner
+/// Can only be called by the owner or the factory
 pub fn add(
     mut deps: DepsMut,
     env: Env,
@@ -333,14 +742,24 @@ pub fn add(
         alloc_point,
         last_reward_block: (cfg.start_block).max(Uint64::from(env.block.height)),
         accumulated_rewards_per_share: Decimal::zero(),
+        last_reward_time: cfg.start_time.max(env.block.time.seconds()),
+        total_virtual_amount: Some(Uint128::zero()),
         reward_proxy,
+        reward_proxy_disabled: false,
+        proxy_deposit: None,
         accumulated_proxy_rewards_per_share: Decimal::zero(),
         proxy_reward_balance_before_update: Uint128::zero(),
         orphan_proxy_rewards: Uint128::zero(),
+        reward_streams: vec![],
+        lock_tiers: vec![],
+        retired_proxies: vec![],
+        incentives: vec![],
+        deactivated: false,
     };
 
     CONFIG.save(deps.storage, &cfg)?;
     POOL_INFO.save(deps.storage, &lp_token, &pool_info)?;
+    ACTIVE_POOLS.save(deps.storage, &lp_token, &Empty {})?;
 
     Ok(Response::new()
         .add_attribute("action", "add_pool")
@@ -361,7 +780,7 @@ pub fn add(
 /// * **alloc_point** is the object of type [`Uint64`].
 ///
 /// ##Executor
-/// Can only be called by the owner
+/// Can only be called by the owner or the factory
 pub fn set(
     mut deps: DepsMut,
     env: Env,
@@ -375,6 +794,9 @@ pub fn set(
     mass_update_pools(deps.branch(), env)?;
 
     let mut pool_info = POOL_INFO.load(deps.storage, &lp_token)?;
+    if pool_info.deactivated {
+        return Err(ContractError::PoolIsDeactivated {});
+    }
 
     cfg.total_alloc_point = cfg
         .total_alloc_point
@@ -391,8 +813,52 @@ pub fn set(
 }
 
 /// ## Description
-/// Updates rewards for single pool if specified in input parameters, otherwise updates rewards for
-/// all pools that are in the [`POOL_INFO`]. Returns an [`ContractError`] on failure,
+/// Returns an [`ContractError`] on failure, otherwise sets the allocation point of the given pool
+/// to zero, marks it as deactivated and removes it from the [`ACTIVE_POOLS`], so the pool is no longer
+/// updated by the mass updates. Users can still withdraw their deposits and claim the rewards.
+///
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// ##Executor
+/// Can only be called by the owner or the factory
+pub fn deactivate_pool(
+    mut deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+) -> Result<Response, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+
+    let pool_info = POOL_INFO.load(deps.storage, &lp_token)?;
+    if pool_info.deactivated {
+        return Err(ContractError::PoolIsDeactivated {});
+    }
+
+    mass_update_pools(deps.branch(), env)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    let mut pool_info = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    cfg.total_alloc_point = cfg.total_alloc_point.checked_sub(pool_info.alloc_point)?;
+    pool_info.alloc_point = Uint64::zero();
+    pool_info.deactivated = true;
+
+    CONFIG.save(deps.storage, &cfg)?;
+    POOL_INFO.save(deps.storage, &lp_token, &pool_info)?;
+    ACTIVE_POOLS.remove(deps.storage, &lp_token);
+
+    Ok(Response::new()
+        .add_attribute("action", "deactivate_pool")
+        .add_attribute("lp_token", lp_token))
+}
+
+/// ## Description
+/// Updates rewards for the pools if specified in input parameters, otherwise updates rewards for
+/// all pools that are in the [`ACTIVE_POOLS`]. Returns an [`ContractError`] on failure,
 /// otherwise returns the [`Response`] object with the specified attributes.
 ///
 /// ## Params
@@ -400,13 +866,13 @@ pub fn set(
 ///
 /// * **env** is the object of type [`Env`].
 ///
-/// * **update_single_pool** is an [`Option`] field object of type [`Addr`].
+/// * **update_pools** is an [`Option`] field object of type [`Vec<Addr>`].
 ///
 /// * **on_reply** is the object of type [`ExecuteOnReply`]. Sets the action to be performed.
 fn update_rewards_and_execute(
     mut deps: DepsMut,
     env: Env,
-    update_single_pool: Option<Addr>,
+    update_pools: Option<Vec<Addr>>,
     on_reply: ExecuteOnReply,
 ) -> Result<Response, ContractError> {
     TMP_USER_ACTION.update(deps.storage, |v| {
@@ -416,42 +882,55 @@ fn update_rewards_and_execute(
             Ok(Some(on_reply))
         }
     })?;
+    TMP_PROXY_CALLS.save(deps.storage, &vec![])?;
 
     let pools: Vec<(Addr, PoolInfo)>;
-    match update_single_pool {
-        Some(lp_token) => {
-            let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
-            let pool = POOL_INFO.load(deps.storage, &lp_token)?;
-            pools = vec![(lp_token, pool)];
+    match update_pools {
+        Some(lp_tokens) => {
+            pools = lp_tokens
+                .into_iter()
+                .map(|lp_token| {
+                    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+                    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+                    Ok((lp_token, pool))
+                })
+                .collect::<StdResult<Vec<_>>>()?;
         }
         None => {
-            pools = get_pools(deps.storage);
+            pools = get_active_pools(deps.storage)?;
         }
     }
 
     let mut messages: Vec<SubMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
     for (lp_token, mut pool) in pools {
+        if pool.reward_proxy_disabled {
+            continue;
+        }
+
         if let Some(reward_proxy) = pool.reward_proxy.clone() {
-            messages.append(&mut get_proxy_rewards(
-                deps.branch(),
-                &lp_token,
-                &mut pool,
-                &reward_proxy,
-            )?);
+            match get_proxy_rewards(deps.branch(), &lp_token, &mut pool, &reward_proxy)? {
+                Ok(msg) => messages.extend(msg),
+                Err(event) => events.push(event),
+            }
         }
     }
 
+    // The action is executed in the reply of the last message, whether the proxy call succeeded or not
     if let Some(last) = messages.last_mut() {
-        last.reply_on = ReplyOn::Success;
-        Ok(Response::new().add_submessages(messages))
+        last.reply_on = ReplyOn::Always;
+        Ok(Response::new().add_submessages(messages).add_events(events))
     } else {
-        process_after_update(deps, env)
+        Ok(process_after_update(deps, env)?.add_events(events))
     }
 }
 
 /// ## Description
 /// Gets proxy rewards. Saves reward amount before update. Returns an [`ContractError`]
-/// on failure, otherwise returns the vector that contains the objects of type [`SubMsg`].
+/// on failure, otherwise returns the [`SubMsg`] claiming the rewards if there are any.
+/// A failure of the proxy query or call disables the proxy of the pool instead of reverting
+/// the transaction, the query failure is returned as the [`Event`] describing it. The deposit
+/// on the proxy is queried as well, so the pool is not updated with the failing proxy.
 ///
 /// ## Params
 /// * **deps** is the object of type [`DepsMut`].
@@ -466,69 +945,293 @@ fn get_proxy_rewards(
     lp_token: &Addr,
     pool: &mut PoolInfo,
     reward_proxy: &Addr,
-) -> Result<Vec<SubMsg>, ContractError> {
-    let reward_amount: Uint128 = deps
+) -> Result<Result<Option<SubMsg>, Event>, ContractError> {
+    let deposit: Uint128 = match deps
+        .querier
+        .query_wasm_smart(reward_proxy, &ProxyQueryMsg::Deposit {})
+    {
+        Ok(deposit) => deposit,
+        Err(err) => {
+            return Ok(Err(disable_reward_proxy(
+                deps.storage,
+                lp_token,
+                reward_proxy,
+                err.to_string(),
+            )?))
+        }
+    };
+
+    let reward_amount: Uint128 = match deps
         .querier
-        .query_wasm_smart(reward_proxy, &ProxyQueryMsg::Reward {})?;
+        .query_wasm_smart(reward_proxy, &ProxyQueryMsg::Reward {})
+    {
+        Ok(reward_amount) => reward_amount,
+        Err(err) => {
+            return Ok(Err(disable_reward_proxy(
+                deps.storage,
+                lp_token,
+                reward_proxy,
+                err.to_string(),
+            )?))
+        }
+    };
 
+    pool.proxy_deposit = Some(deposit);
     pool.proxy_reward_balance_before_update = reward_amount;
     POOL_INFO.save(deps.storage, lp_token, pool)?;
 
     let msg = ProxyQueryMsg::PendingToken {};
-    let res: Uint128 = deps.querier.query_wasm_smart(reward_proxy, &msg)?;
+    let res: Uint128 = match deps.querier.query_wasm_smart(reward_proxy, &msg) {
+        Ok(res) => res,
+        Err(err) => {
+            return Ok(Err(disable_reward_proxy(
+                deps.storage,
+                lp_token,
+                reward_proxy,
+                err.to_string(),
+            )?))
+        }
+    };
 
-    Ok(if !res.is_zero() {
-        vec![SubMsg::new(WasmMsg::Execute {
-            contract_addr: reward_proxy.to_string(),
-            funds: vec![],
-            msg: to_binary(&ProxyExecuteMsg::UpdateRewards {})?,
-        })]
+    Ok(Ok(if !res.is_zero() {
+        Some(proxy_call_submsg(
+            deps.storage,
+            ProxyCall::UpdateRewards {
+                lp_token: lp_token.clone(),
+                reward_proxy: reward_proxy.clone(),
+            },
+            WasmMsg::Execute {
+                contract_addr: reward_proxy.to_string(),
+                funds: vec![],
+                msg: to_binary(&ProxyExecuteMsg::UpdateRewards {})?,
+            },
+        )?)
     } else {
-        vec![]
-    })
+        None
+    }))
 }
 
-/// # Description
-/// The entry point to the contract for processing the reply from the submessage.
-/// # Params
-/// * **deps** is the object of type [`DepsMut`].
+/// ## Description
+/// Saves the proxy call to the [`TMP_PROXY_CALLS`] and returns the sub-message which replies on error
+/// with the index of the call as the id.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
 ///
-/// * **env** is the object of type [`Env`].
+/// * **call** is the object of type [`ProxyCall`].
 ///
-/// * **_msg** is the object of type [`Reply`].
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, env: Env, _msg: Reply) -> Result<Response, ContractError> {
-    process_after_update(deps, env)
+/// * **msg** is the object of type [`WasmMsg`]. The message to the reward proxy.
+fn proxy_call_submsg(
+    storage: &mut dyn Storage,
+    call: ProxyCall,
+    msg: WasmMsg,
+) -> StdResult<SubMsg> {
+    let mut calls = TMP_PROXY_CALLS.may_load(storage)?.unwrap_or_default();
+    let id = calls.len() as u64;
+    calls.push(call);
+    TMP_PROXY_CALLS.save(storage, &calls)?;
+
+    Ok(SubMsg::reply_on_error(msg, id))
 }
 
 /// # Description
-/// Loads an action from [`TMP_USER_ACTION`] if set, and executes it. Returns an [`ContractError`]
-/// on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// The entry point to the contract for processing the reply from the submessage.
+/// A failed proxy call disables the proxy of the pool, the rewards which the proxy failed to send
+/// are kept for a later claim and the deposit which the proxy failed to withdraw is restored.
+/// The user action is executed after the last proxy rewards update.
 /// # Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
 /// * **env** is the object of type [`Env`].
-fn process_after_update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
-    match TMP_USER_ACTION.load(deps.storage)? {
-        Some(action) => {
-            TMP_USER_ACTION.save(deps.storage, &None)?;
-            match action {
-                ExecuteOnReply::MassUpdatePools {} => mass_update_pools(deps, env),
-                ExecuteOnReply::Add {
-                    lp_token,
-                    alloc_point,
+///
+/// * **msg** is the object of type [`Reply`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    let calls = TMP_PROXY_CALLS.may_load(deps.storage)?.unwrap_or_default();
+    let call = calls
+        .get(msg.id as usize)
+        .cloned()
+        .ok_or_else(|| StdError::generic_err(format!("Unknown reply id: {}", msg.id)))?;
+
+    match call {
+        ProxyCall::UpdateRewards {
+            lp_token,
+            reward_proxy,
+        } => {
+            let mut events = vec![];
+            if let ContractResult::Err(error) = msg.result {
+                events.push(disable_reward_proxy(
+                    deps.storage,
+                    &lp_token,
+                    &reward_proxy,
+                    error,
+                )?);
+            }
+
+            let response = if msg.id as usize + 1 == calls.len() {
+                process_after_update(deps, env)?
+            } else {
+                Response::new()
+            };
+
+            Ok(response.add_events(events))
+        }
+        ProxyCall::SendRewards {
+            lp_token,
+            reward_proxy,
+            account,
+            amount,
+        } => {
+            let error = match msg.result {
+                ContractResult::Err(error) => error,
+                ContractResult::Ok(_) => return Ok(Response::new()),
+            };
+
+            UNPAID_PROXY_REWARDS.update::<_, StdError>(
+                deps.storage,
+                (&reward_proxy, &account),
+                |v| Ok(v.unwrap_or_default().checked_add(amount)?),
+            )?;
+
+            Ok(Response::new().add_event(match lp_token {
+                Some(lp_token) => {
+                    disable_reward_proxy(deps.storage, &lp_token, &reward_proxy, error)?
+                }
+                None => Event::new("proxy_rewards_unpaid")
+                    .add_attribute("reward_proxy", reward_proxy)
+                    .add_attribute("error", error),
+            }))
+        }
+        ProxyCall::Withdraw {
+            lp_token,
+            reward_proxy,
+            account,
+            amount,
+        } => {
+            let error = match msg.result {
+                ContractResult::Err(error) => error,
+                ContractResult::Ok(_) => return Ok(Response::new()),
+            };
+
+            let event = disable_reward_proxy(deps.storage, &lp_token, &reward_proxy, error)?;
+            restore_deposit(deps, env, lp_token, account, amount)?;
+
+            Ok(Response::new().add_event(event))
+        }
+    }
+}
+
+/// ## Description
+/// Disables the reward proxy of the pool unless it was changed since the failed call.
+/// Returns the event describing the failure.
+/// ## Params
+/// * **storage** is the object of type [`Storage`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **reward_proxy** is the object of type [`Addr`].
+///
+/// * **error** is the object of type [`String`]. The error of the proxy call.
+fn disable_reward_proxy(
+    storage: &mut dyn Storage,
+    lp_token: &Addr,
+    reward_proxy: &Addr,
+    error: String,
+) -> StdResult<Event> {
+    let mut pool = POOL_INFO.load(storage, lp_token)?;
+    if pool.reward_proxy.as_ref() == Some(reward_proxy) && !pool.reward_proxy_disabled {
+        pool.reward_proxy_disabled = true;
+        POOL_INFO.save(storage, lp_token, &pool)?;
+    }
+
+    Ok(Event::new("reward_proxy_disabled")
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("reward_proxy", reward_proxy)
+        .add_attribute("error", error))
+}
+
+/// ## Description
+/// Returns the amount which the reward proxy failed to withdraw to the deposit of the user.
+/// The pending rewards are already paid by the withdrawal, so only the balances are updated.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **account** is the object of type [`Addr`].
+///
+/// * **amount** is the object of type [`Uint128`].
+fn restore_deposit(
+    deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+    account: Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let user = USER_INFO
+        .load(deps.storage, (&lp_token, &account))
+        .unwrap_or_default();
+
+    let updated_amount = user.amount.checked_add(amount)?;
+    let lp_supply =
+        query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?.checked_add(amount)?;
+    pool.proxy_deposit = Some(lp_supply);
+
+    let virtual_amount = calculate_virtual_amount(
+        deps.as_ref(),
+        &cfg,
+        &account,
+        &user,
+        updated_amount,
+        lp_supply,
+    )?;
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &account,
+        user.amount,
+        updated_amount,
+    )?;
+    let user = update_user_balance(user, &mut pool, updated_amount, virtual_amount)?;
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+    USER_INFO.save(deps.storage, (&lp_token, &account), &user)
+}
+
+/// # Description
+/// Loads an action from [`TMP_USER_ACTION`] if set, and executes it. Returns an [`ContractError`]
+/// on failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+fn process_after_update(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    match TMP_USER_ACTION.load(deps.storage)? {
+        Some(action) => {
+            TMP_USER_ACTION.save(deps.storage, &None)?;
+            match action {
+                ExecuteOnReply::MassUpdatePools {} => mass_update_pools(deps, env),
+                ExecuteOnReply::Add {
+                    lp_token,
+                    alloc_point,
                     reward_proxy,
                 } => add(deps, env, lp_token, alloc_point, reward_proxy),
                 ExecuteOnReply::Set {
                     lp_token,
                     alloc_point,
                 } => set(deps, env, lp_token, alloc_point),
+                ExecuteOnReply::DeactivatePool { lp_token } => deactivate_pool(deps, env, lp_token),
                 ExecuteOnReply::UpdatePool { lp_token } => update_pool(deps, env, lp_token),
                 ExecuteOnReply::Deposit {
                     lp_token,
                     account,
                     amount,
-                } => deposit(deps, env, lp_token, account, amount),
+                    lock_duration,
+                } => deposit(deps, env, lp_token, account, amount, lock_duration),
                 ExecuteOnReply::Withdraw {
                     lp_token,
                     account,
@@ -537,6 +1240,28 @@ fn process_after_update(deps: DepsMut, env: Env) -> Result<Response, ContractErr
                 ExecuteOnReply::SetTokensPerBlock { amount } => {
                     set_tokens_per_block(deps, env, amount)
                 }
+                ExecuteOnReply::SetTokensPerSecond { amount } => {
+                    set_tokens_per_second(deps, env, amount)
+                }
+                ExecuteOnReply::SetEmissionSchedule { periods } => {
+                    set_emission_schedule(deps, env, periods)
+                }
+                ExecuteOnReply::ClaimRewards {
+                    lp_tokens,
+                    account,
+                    recipient,
+                } => claim_rewards(deps, env, lp_tokens, account, recipient),
+                ExecuteOnReply::TransferPosition {
+                    lp_token,
+                    account,
+                    recipient,
+                    amount,
+                } => transfer_position(deps, env, lp_token, account, recipient, amount),
+                ExecuteOnReply::TuneAllocations { epoch } => tune_allocations(deps, env, epoch),
+                ExecuteOnReply::MoveToProxy {
+                    lp_token,
+                    new_proxy,
+                } => move_to_proxy(deps, env, lp_token, new_proxy),
             }
         }
         None => Ok(Response::default()),
@@ -569,7 +1294,109 @@ fn set_tokens_per_block(
 }
 
 /// # Description
-/// Updates reward variables for all pools. Returns an [`ContractError`] on failure, otherwise
+/// Sets a new count of tokens per second for time-based emissions and removes the emission schedule.
+/// Before that, we will need to update all pools so as not to lose rewards. Returns an [`ContractError`] on failure, otherwise returns
+/// the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **amount** is the object of type [`Uint128`]. Sets a new count of tokens per second.
+fn set_tokens_per_second(
+    mut deps: DepsMut,
+    env: Env,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    mass_update_pools(deps.branch(), env)?;
+
+    CONFIG.update::<_, ContractError>(deps.storage, |mut v| {
+        v.tokens_per_second = Some(amount);
+        v.emission_schedule = vec![];
+        Ok(v)
+    })?;
+
+    Ok(Response::new().add_attribute("action", "set_tokens_per_second"))
+}
+
+/// # Description
+/// Sets the emission schedule for time-based emissions. Before that, we will need to update all pools
+/// so as not to lose rewards. Returns an [`ContractError`] on failure, otherwise returns the [`Response`]
+/// with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **periods** is a vector that contains objects of type [`EmissionPeriod`].
+fn set_emission_schedule(
+    mut deps: DepsMut,
+    env: Env,
+    periods: Vec<EmissionPeriod>,
+) -> Result<Response, ContractError> {
+    mass_update_pools(deps.branch(), env)?;
+
+    CONFIG.update::<_, ContractError>(deps.storage, |mut v| {
+        v.emission_schedule = periods;
+        Ok(v)
+    })?;
+
+    Ok(Response::new().add_attribute("action", "set_emission_schedule"))
+}
+
+/// # Description
+/// Checks that the emission periods are sorted and don't overlap, and that the ASTRO left to emit
+/// according to the schedule doesn't exceed the generator's remaining allocation in the vesting contract.
+/// # Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **periods** is a slice that contains objects of type [`EmissionPeriod`].
+fn validate_emission_schedule(
+    deps: Deps,
+    env: &Env,
+    cfg: &Config,
+    periods: &[EmissionPeriod],
+) -> Result<(), ContractError> {
+    let mut previous_end_time = 0;
+    for period in periods {
+        if period.start_time >= period.end_time || period.start_time < previous_end_time {
+            return Err(ContractError::InvalidEmissionSchedule {});
+        }
+        previous_end_time = period.end_time;
+    }
+
+    let emissions = scheduled_emissions(periods, env.block.time.seconds(), u64::MAX)?;
+
+    let vesting: VestingAccountResponse = deps.querier.query_wasm_smart(
+        &cfg.vesting_contract,
+        &VestingQueryMsg::VestingAccount {
+            address: env.contract.address.clone(),
+        },
+    )?;
+
+    let mut allocation = Uint128::zero();
+    for schedule in &vesting.info.schedules {
+        let point = schedule.end_point.as_ref().unwrap_or(&schedule.start_point);
+        allocation = allocation.checked_add(point.amount)?;
+    }
+    let allocation = allocation.checked_sub(vesting.info.released_amount)?;
+
+    if emissions > allocation {
+        return Err(ContractError::EmissionsExceedAllocation {
+            emissions,
+            allocation,
+        });
+    }
+
+    Ok(())
+}
+
+/// # Description
+/// Updates reward variables for all pools which are not deactivated. Returns an [`ContractError`] on failure, otherwise
 /// returns the [`Response`] with the specified attributes if the operation was successful.
 /// # Params
 /// * **deps** is the object of type [`DepsMut`].
@@ -579,7 +1406,7 @@ pub fn mass_update_pools(mut deps: DepsMut, env: Env) -> Result<Response, Contra
     let response = Response::default();
 
     let cfg = CONFIG.load(deps.storage)?;
-    let pools = get_pools(deps.storage);
+    let pools = get_active_pools(deps.storage)?;
 
     if pools.is_empty() {
         return Ok(response);
@@ -629,6 +1456,8 @@ pub fn update_pool(mut deps: DepsMut, env: Env, lp_token: Addr) -> Result<Respon
 /// * **cfg** is the object of type [`Config`]
 ///
 /// * **deposited** is an [`Option`] field object of type [`Uint128`].
+///
+/// A failed query of the reward proxy disables it, the deposit tracked by the generator is used instead.
 // Update reward variables of the given pool to be up-to-date.
 pub fn accumulate_rewards_per_share(
     deps: DepsMut,
@@ -638,169 +1467,750 @@ pub fn accumulate_rewards_per_share(
     cfg: &Config,
     deposited: Option<Uint128>,
 ) -> StdResult<()> {
-    let lp_supply: Uint128;
-
-    match &pool.reward_proxy {
-        Some(proxy) => {
-            lp_supply = deps
+    let lp_supply = match &pool.reward_proxy {
+        Some(proxy) if !pool.reward_proxy_disabled => {
+            match deps
                 .querier
-                .query_wasm_smart(proxy, &ProxyQueryMsg::Deposit {})?;
+                .query_wasm_smart(proxy, &ProxyQueryMsg::Deposit {})
+            {
+                Ok(deposit) => deposit,
+                Err(_) => {
+                    pool.reward_proxy_disabled = true;
+                    pool.proxy_deposit.unwrap_or_default()
+                }
+            }
+        }
+        _ => query_lp_supply(deps.as_ref(), env, lp_token, pool, deposited)?,
+    };
 
-            if !lp_supply.is_zero() {
-                let reward_amount: Uint128 = deps
-                    .querier
-                    .query_wasm_smart(proxy, &ProxyQueryMsg::Reward {})?;
+    if let Some(proxy) = &pool.reward_proxy {
+        pool.proxy_deposit = Some(lp_supply);
 
-                let token_rewards =
-                    reward_amount.checked_sub(pool.proxy_reward_balance_before_update)?;
+        if !lp_supply.is_zero() && !pool.reward_proxy_disabled {
+            match deps
+                .querier
+                .query_wasm_smart::<Uint128>(proxy, &ProxyQueryMsg::Reward {})
+            {
+                Ok(reward_amount) => {
+                    let token_rewards =
+                        reward_amount.checked_sub(pool.proxy_reward_balance_before_update)?;
 
-                let share = Decimal::from_ratio(token_rewards, lp_supply);
-                pool.accumulated_proxy_rewards_per_share = pool
-                    .accumulated_proxy_rewards_per_share
-                    .checked_add(share)?;
-                pool.proxy_reward_balance_before_update = reward_amount;
+                    let share = Decimal::from_ratio(token_rewards, lp_supply);
+                    pool.accumulated_proxy_rewards_per_share = pool
+                        .accumulated_proxy_rewards_per_share
+                        .checked_add(share)?;
+                    pool.proxy_reward_balance_before_update = reward_amount;
+                }
+                Err(_) => pool.reward_proxy_disabled = true,
             }
         }
-        None => {
-            let res: BalanceResponse = deps.querier.query_wasm_smart(
-                lp_token,
-                &cw20::Cw20QueryMsg::Balance {
-                    address: env.contract.address.to_string(),
-                },
-            )?;
+    }
 
-            if let Some(amount) = deposited {
-                // On deposit balance is already increased in contract, so we need to subtract it
-                lp_supply = res.balance.checked_sub(amount)?;
-            } else {
-                lp_supply = res.balance;
-            }
-        }
-    };
+    // Pools added before boosting have the total boosted amount equal to the staked amount
+    let total_virtual_amount = *pool.total_virtual_amount.get_or_insert(lp_supply);
 
-    if env.block.height > pool.last_reward_block.u64() {
-        if !lp_supply.is_zero() {
+    if is_reward_due(env, pool, cfg) {
+        if !total_virtual_amount.is_zero() {
             let token_rewards = calculate_rewards(env, pool, cfg)?;
 
-            let share = Decimal::from_ratio(token_rewards, lp_supply);
+            let share = Decimal::from_ratio(token_rewards, total_virtual_amount);
             pool.accumulated_rewards_per_share =
                 pool.accumulated_rewards_per_share.checked_add(share)?;
         }
 
         pool.last_reward_block = Uint64::from(env.block.height);
+        pool.last_reward_time = env.block.time.seconds();
     }
 
-    Ok(())
+    update_reward_streams(env, pool, lp_supply)
 }
 
-/// ## Description
-/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
-/// If the template is not found in the received message, then an [`ContractError`] is returned,
-/// otherwise returns the [`Response`] with the specified attributes if the operation was successful
-/// ## Params
-/// * **deps** is the object of type [`DepsMut`].
+/// # Description
+/// Returns the amount of LP tokens staked in the pool: the deposit on the reward proxy if it is set,
+/// otherwise the generator's own LP token balance. The disabled proxy is not queried, the deposit
+/// tracked by the generator is used instead.
+/// # Params
+/// * **deps** is the object of type [`Deps`].
 ///
 /// * **env** is the object of type [`Env`].
 ///
-/// * **info** is the object of type [`MessageInfo`].
+/// * **lp_token** is the object of type [`Addr`].
 ///
-/// * **cw20_msg** is the object of type [`Cw20ReceiveMsg`].
-fn receive_cw20(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    cw20_msg: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
-    let amount = cw20_msg.amount;
-    let lp_token = info.sender;
-
-    if POOL_INFO.load(deps.storage, &lp_token).is_err() {
-        return Err(ContractError::Unauthorized {});
-    }
+/// * **pool** is the object of type [`PoolInfo`].
+///
+/// * **deposited** is an [`Option`] field object of type [`Uint128`].
+fn query_lp_supply(
+    deps: Deps,
+    env: &Env,
+    lp_token: &Addr,
+    pool: &PoolInfo,
+    deposited: Option<Uint128>,
+) -> StdResult<Uint128> {
+    match &pool.reward_proxy {
+        Some(_) if pool.reward_proxy_disabled => Ok(pool.proxy_deposit.unwrap_or_default()),
+        Some(proxy) => deps
+            .querier
+            .query_wasm_smart(proxy, &ProxyQueryMsg::Deposit {}),
+        None => {
+            let balance = query_token_balance(
+                &deps.querier,
+                lp_token.clone(),
+                env.contract.address.clone(),
+            )?;
 
-    match from_binary(&cw20_msg.msg)? {
-        Cw20HookMsg::Deposit {} => update_rewards_and_execute(
-            deps,
-            env,
-            Some(lp_token.clone()),
-            ExecuteOnReply::Deposit {
-                lp_token,
-                account: Addr::unchecked(cw20_msg.sender),
-                amount,
-            },
-        ),
-        Cw20HookMsg::DepositFor(beneficiary) => update_rewards_and_execute(
-            deps,
-            env,
-            Some(lp_token.clone()),
-            ExecuteOnReply::Deposit {
-                lp_token,
-                account: beneficiary,
-                amount,
-            },
-        ),
+            match deposited {
+                // On deposit balance is already increased in contract, so we need to subtract it
+                Some(amount) => Ok(balance.checked_sub(amount)?),
+                None => Ok(balance),
+            }
+        }
     }
 }
 
 /// # Description
-/// Distributes the pending rewards between recipient and the proxy contract.
-/// Returns an [`ContractError`] on failure, otherwise returns the vector that
-/// contains the objects of type [`SubMsg`].
+/// Accumulates rewards per share of the external reward streams and the incentives of the pool.
+/// While nothing is staked in the pool, the end time of the streams and the incentives is postponed
+/// so the funded rewards are not lost. Finished incentives are removed.
 /// # Params
-/// * **cfg** is the object of type [`Config`].
+/// * **env** is the object of type [`Env`].
 ///
 /// * **pool** is the object of type [`PoolInfo`].
 ///
-/// * **user** is the object of type [`UserInfo`].
-///
-/// * **to** is the object of type [`Addr`].
-pub fn send_pending_rewards(
-    cfg: &Config,
-    pool: &PoolInfo,
-    user: &UserInfo,
-    to: &Addr,
-) -> Result<Vec<WasmMsg>, ContractError> {
-    if user.amount.is_zero() {
-        return Ok(vec![]);
-    }
-
-    let mut messages = vec![];
+/// * **lp_supply** is the object of type [`Uint128`]. The amount of LP tokens staked in the pool.
+pub fn update_reward_streams(env: &Env, pool: &mut PoolInfo, lp_supply: Uint128) -> StdResult<()> {
+    let now = env.block.time.seconds();
+
+    for stream in pool.reward_streams.iter_mut() {
+        let until = now.min(stream.end_time);
+        if until <= stream.last_update_time {
+            continue;
+        }
 
-    let pending_rewards = pool
-        .accumulated_rewards_per_share
-        .checked_mul(user.amount)?
-        .checked_sub(user.reward_debt)?;
+        let elapsed = until - stream.last_update_time;
+        if lp_supply.is_zero() {
+            stream.end_time += elapsed;
+        } else {
+            let rewards = stream
+                .reward_per_second
+                .checked_mul(Uint128::from(elapsed))?;
+            let share = Decimal::from_ratio(rewards, lp_supply);
+            stream.accumulated_rewards_per_share =
+                stream.accumulated_rewards_per_share.checked_add(share)?;
+        }
 
-    if !pending_rewards.is_zero() {
-        messages.push(WasmMsg::Execute {
-            contract_addr: cfg.vesting_contract.to_string(),
-            msg: to_binary(&VestingExecuteMsg::Claim {
-                recipient: Some(to.to_string()),
-                amount: Some(pending_rewards),
-            })?,
-            funds: vec![],
-        });
+        stream.last_update_time = until;
     }
 
-    if let Some(proxy) = &pool.reward_proxy {
-        let pending_proxy_rewards = pool
-            .accumulated_proxy_rewards_per_share
-            .checked_mul(user.amount)?
-            .checked_sub(user.reward_debt_proxy)?;
+    let PoolInfo {
+        reward_streams,
+        incentives,
+        ..
+    } = pool;
 
-        if !pending_proxy_rewards.is_zero() {
-            messages.push(WasmMsg::Execute {
-                contract_addr: proxy.to_string(),
+    for incentive in incentives.iter_mut() {
+        let until = now.min(incentive.end_time);
+        if until <= incentive.last_update_time {
+            continue;
+        }
+
+        let elapsed = until - incentive.last_update_time;
+        if lp_supply.is_zero() {
+            incentive.end_time += elapsed;
+        } else if let Some(stream) = reward_streams
+            .iter_mut()
+            .find(|stream| stream.info.equal(&incentive.info))
+        {
+            let rewards = incentive
+                .reward_per_second
+                .checked_mul(Uint128::from(elapsed))?;
+            let share = Decimal::from_ratio(rewards, lp_supply);
+            stream.accumulated_rewards_per_share =
+                stream.accumulated_rewards_per_share.checked_add(share)?;
+        }
+
+        incentive.last_update_time = until;
+    }
+
+    incentives.retain(|incentive| incentive.end_time > now);
+
+    Ok(())
+}
+
+/// # Description
+/// Funds an external reward stream of the pool. If the pool already has a stream for the reward asset,
+/// the remaining rewards and the new amount are distributed together until the new end time.
+/// The asset must be allowed in reward streams and the amount can't be less than its minimum, so
+/// the limited stream slots of the pool can't be taken by dust. Finished streams keep their
+/// accumulated rewards but don't take a slot. While nothing is staked in the pool, the end time
+/// of the stream is postponed, see [`update_reward_streams`].
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified
+/// attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`String`].
+///
+/// * **reward** is the object of type [`Asset`]. The reward asset and amount to distribute.
+///
+/// * **end_time** is the object of type [`u64`]. The time when the distribution ends.
+/// ##Executor
+/// Anyone can execute it
+pub fn fund_reward_stream(
+    deps: DepsMut,
+    env: Env,
+    lp_token: String,
+    reward: Asset,
+    end_time: u64,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let lp_token = addr_validate_to_lower(deps.api, &lp_token)?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    if reward.amount.is_zero() {
+        return Err(ContractError::RewardStreamAmountIsZero {});
+    }
+
+    // LP tokens held by the generator are used to calculate the staked amount of the pools
+    if let AssetInfo::Token { contract_addr } = &reward.info {
+        if POOL_INFO.load(deps.storage, contract_addr).is_ok() {
+            return Err(ContractError::InvalidRewardStreamAsset {});
+        }
+    }
+
+    let minimum_amount = cfg
+        .reward_stream_assets
+        .iter()
+        .find(|(info, _)| info.equal(&reward.info))
+        .map(|(_, minimum_amount)| *minimum_amount)
+        .ok_or(ContractError::RewardStreamAssetNotAllowed {})?;
+    if reward.amount < minimum_amount {
+        return Err(ContractError::RewardStreamAmountTooLow(minimum_amount));
+    }
+
+    let now = env.block.time.seconds();
+    if end_time <= now {
+        return Err(ContractError::InvalidRewardStreamEndTime {});
+    }
+
+    let lp_supply = query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?;
+    update_reward_streams(&env, &mut pool, lp_supply)?;
+
+    let active_streams = pool
+        .reward_streams
+        .iter()
+        .filter(|stream| stream.end_time > now && !stream.info.equal(&reward.info))
+        .count();
+    if active_streams >= MAX_REWARD_STREAMS {
+        return Err(ContractError::RewardStreamLimitExceeded(MAX_REWARD_STREAMS));
+    }
+
+    match pool
+        .reward_streams
+        .iter_mut()
+        .find(|stream| stream.info.equal(&reward.info))
+    {
+        Some(stream) => {
+            if end_time < stream.end_time {
+                return Err(ContractError::InvalidRewardStreamEndTime {});
+            }
+
+            let remaining = if stream.end_time > now {
+                stream
+                    .reward_per_second
+                    .checked_mul(Uint128::from(stream.end_time - now))?
+            } else {
+                Uint128::zero()
+            };
+
+            stream.reward_per_second =
+                Decimal::from_ratio(remaining.checked_add(reward.amount)?, end_time - now);
+            stream.last_update_time = now;
+            stream.end_time = end_time;
+        }
+        None => {
+            pool.reward_streams.push(RewardStream {
+                info: reward.info.clone(),
+                reward_per_second: Decimal::from_ratio(reward.amount, end_time - now),
+                last_update_time: now,
+                end_time,
+                accumulated_rewards_per_share: Decimal::zero(),
+            });
+        }
+    }
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "fund_reward_stream")
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("reward", reward.info.to_string())
+        .add_attribute("amount", reward.amount)
+        .add_attribute("end_time", end_time.to_string()))
+}
+
+/// # Description
+/// Streams the reward to the stakers of the pool over the duration. The incentive fee is sent to
+/// the maker if it is set. The asset must be allowed in reward streams and the amount can't be less
+/// than its minimum, so the limited incentive slots of the pool can't be taken by dust. The rewards
+/// are accumulated in the reward stream of the same asset, which is added with a zero rate if the
+/// pool doesn't have it yet. Such a stream is finished, so it doesn't take a stream slot. Returns an [`ContractError`] on failure,
+/// otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **incentivizer** is the object of type [`Addr`]. The account which funds the incentive.
+///
+/// * **lp_token** is the object of type [`String`].
+///
+/// * **reward** is the object of type [`Asset`]. The reward asset and amount to distribute.
+///
+/// * **duration** is the object of type [`u64`]. The duration of the distribution in seconds.
+/// ##Executor
+/// Anyone can execute it
+pub fn incentivize(
+    deps: DepsMut,
+    env: Env,
+    incentivizer: Addr,
+    lp_token: String,
+    reward: Asset,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let lp_token = addr_validate_to_lower(deps.api, &lp_token)?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    if duration == 0 || duration > MAX_INCENTIVE_DURATION {
+        return Err(ContractError::InvalidIncentiveDuration(
+            MAX_INCENTIVE_DURATION,
+        ));
+    }
+
+    // LP tokens held by the generator are used to calculate the staked amount of the pools
+    if let AssetInfo::Token { contract_addr } = &reward.info {
+        if POOL_INFO.load(deps.storage, contract_addr).is_ok() {
+            return Err(ContractError::InvalidRewardStreamAsset {});
+        }
+    }
+
+    let minimum_amount = cfg
+        .reward_stream_assets
+        .iter()
+        .find(|(info, _)| info.equal(&reward.info))
+        .map(|(_, minimum_amount)| *minimum_amount)
+        .ok_or(ContractError::RewardStreamAssetNotAllowed {})?;
+    if reward.amount < minimum_amount {
+        return Err(ContractError::RewardStreamAmountTooLow(minimum_amount));
+    }
+
+    let lp_supply = query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?;
+    update_reward_streams(&env, &mut pool, lp_supply)?;
+
+    if pool.incentives.len() >= MAX_INCENTIVES {
+        return Err(ContractError::IncentiveLimitExceeded(MAX_INCENTIVES));
+    }
+
+    let fee = match cfg.maker {
+        Some(_) => reward.amount * cfg.incentive_fee,
+        None => Uint128::zero(),
+    };
+    let amount = reward.amount.checked_sub(fee)?;
+    if amount.is_zero() {
+        return Err(ContractError::RewardStreamAmountIsZero {});
+    }
+
+    let now = env.block.time.seconds();
+
+    if !pool
+        .reward_streams
+        .iter()
+        .any(|stream| stream.info.equal(&reward.info))
+    {
+        pool.reward_streams.push(RewardStream {
+            info: reward.info.clone(),
+            reward_per_second: Decimal::zero(),
+            last_update_time: now,
+            end_time: now,
+            accumulated_rewards_per_share: Decimal::zero(),
+        });
+    }
+
+    pool.incentives.push(IncentiveSchedule {
+        incentivizer,
+        info: reward.info.clone(),
+        amount,
+        reward_per_second: Decimal::from_ratio(amount, duration),
+        start_time: now,
+        last_update_time: now,
+        end_time: now + duration,
+    });
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    let mut response = Response::new();
+    if let Some(maker) = cfg.maker {
+        if !fee.is_zero() {
+            response = response.add_message(
+                Asset {
+                    info: reward.info.clone(),
+                    amount: fee,
+                }
+                .into_msg(&deps.querier, maker)?,
+            );
+        }
+    }
+
+    Ok(response
+        .add_attribute("action", "incentivize")
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("reward", reward.info.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("fee", fee)
+        .add_attribute("end_time", (now + duration).to_string()))
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise returns the [`Response`] with the specified attributes if the operation was successful
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is the object of type [`Cw20ReceiveMsg`].
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let amount = cw20_msg.amount;
+
+    let (account, lock_duration) = match from_binary(&cw20_msg.msg)? {
+        Cw20HookMsg::Deposit {} => (Addr::unchecked(cw20_msg.sender), None),
+        Cw20HookMsg::DepositFor(beneficiary) => (beneficiary, None),
+        Cw20HookMsg::DepositLocked { duration } => {
+            (Addr::unchecked(cw20_msg.sender), Some(duration))
+        }
+        Cw20HookMsg::FundRewardStream { lp_token, end_time } => {
+            let reward = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount,
+            };
+
+            return fund_reward_stream(deps, env, lp_token, reward, end_time);
+        }
+        Cw20HookMsg::Incentivize { lp_token, duration } => {
+            let reward = Asset {
+                info: AssetInfo::Token {
+                    contract_addr: info.sender,
+                },
+                amount,
+            };
+            let incentivizer = addr_validate_to_lower(deps.api, &cw20_msg.sender)?;
+
+            return incentivize(deps, env, incentivizer, lp_token, reward, duration);
+        }
+        Cw20HookMsg::LockVotingPower {} => {
+            let user = addr_validate_to_lower(deps.api, &cw20_msg.sender)?;
+
+            return lock_voting_power(deps, info, user, amount);
+        }
+    };
+
+    let lp_token = info.sender;
+
+    match POOL_INFO.may_load(deps.storage, &lp_token)? {
+        Some(pool) if pool.deactivated => return Err(ContractError::PoolIsDeactivated {}),
+        Some(_) => {}
+        None => return Err(ContractError::Unauthorized {}),
+    }
+
+    update_rewards_and_execute(
+        deps,
+        env,
+        Some(vec![lp_token.clone()]),
+        ExecuteOnReply::Deposit {
+            lp_token,
+            account,
+            amount,
+            lock_duration,
+        },
+    )
+}
+
+/// ## Description
+/// This structure describes the pending rewards of a user in one or several pools.
+#[derive(Default)]
+struct PendingRewards {
+    /// ASTRO rewards
+    astro: Uint128,
+    /// rewards of each reward proxy along with the LP token of its pool
+    proxies: Vec<(Addr, Addr, Uint128)>,
+    /// rewards of each disabled reward proxy, which are kept for a later claim
+    unpaid_proxies: Vec<(Addr, Uint128)>,
+    /// rewards of the external reward streams
+    streams: Vec<Asset>,
+}
+
+impl PendingRewards {
+    /// ## Description
+    /// Calculates the pending rewards of the user in the pool.
+    /// ## Params
+    /// * **lp_token** is the object of type [`Addr`].
+    ///
+    /// * **pool** is the object of type [`PoolInfo`].
+    ///
+    /// * **user** is the object of type [`UserInfo`].
+    fn calculate(lp_token: &Addr, pool: &PoolInfo, user: &UserInfo) -> StdResult<Self> {
+        let mut pending = PendingRewards::default();
+
+        if user.amount.is_zero() {
+            return Ok(pending);
+        }
+
+        pending.astro = pool
+            .accumulated_rewards_per_share
+            .checked_mul(user.virtual_amount())?
+            .checked_sub(user.reward_debt)?;
+
+        // The rewards left on the retired proxies are paid out with the unpaid proxy rewards
+        pending.unpaid_proxies = retired_proxy_rewards(pool, user)?;
+
+        if let Some(proxy) = &pool.reward_proxy {
+            let pending_proxy_rewards = pool
+                .accumulated_proxy_rewards_per_share
+                .checked_mul(user.amount)?
+                .checked_sub(proxy_reward_debt(pool, user))?;
+
+            if !pending_proxy_rewards.is_zero() {
+                // The rewards of a disabled proxy are kept until it is fixed
+                if pool.reward_proxy_disabled {
+                    merge_proxy_rewards(
+                        &mut pending.unpaid_proxies,
+                        vec![(proxy.clone(), pending_proxy_rewards)],
+                    )?;
+                } else {
+                    pending
+                        .proxies
+                        .push((lp_token.clone(), proxy.clone(), pending_proxy_rewards));
+                }
+            }
+        }
+
+        for stream in &pool.reward_streams {
+            let pending_stream_rewards = stream
+                .accumulated_rewards_per_share
+                .checked_mul(user.amount)?
+                .checked_sub(stream_reward_debt(user, &stream.info))?;
+
+            if !pending_stream_rewards.is_zero() {
+                pending.streams.push(Asset {
+                    info: stream.info.clone(),
+                    amount: pending_stream_rewards,
+                });
+            }
+        }
+
+        Ok(pending)
+    }
+
+    /// ## Description
+    /// Adds the other pending rewards, summing up rewards of the same proxy or asset.
+    /// ## Params
+    /// * **other** is the object of type [`PendingRewards`].
+    fn merge(&mut self, other: PendingRewards) -> StdResult<()> {
+        self.astro = self.astro.checked_add(other.astro)?;
+
+        for (lp_token, proxy, amount) in other.proxies {
+            match self
+                .proxies
+                .iter_mut()
+                .find(|(l, p, _)| *l == lp_token && *p == proxy)
+            {
+                Some((_, _, total)) => *total = total.checked_add(amount)?,
+                None => self.proxies.push((lp_token, proxy, amount)),
+            }
+        }
+        merge_proxy_rewards(&mut self.unpaid_proxies, other.unpaid_proxies)?;
+
+        for asset in other.streams {
+            match self
+                .streams
+                .iter_mut()
+                .find(|total| total.info.equal(&asset.info))
+            {
+                Some(total) => total.amount = total.amount.checked_add(asset.amount)?,
+                None => self.streams.push(asset),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// ## Description
+    /// Returns the messages sending the rewards to the recipient: a vesting claim for ASTRO, a payout
+    /// per reward proxy and a transfer per external reward asset. The rewards of the disabled
+    /// proxies are saved to the [`UNPAID_PROXY_REWARDS`].
+    /// ## Params
+    /// * **deps** is the object of type [`DepsMut`].
+    ///
+    /// * **cfg** is the object of type [`Config`].
+    ///
+    /// * **to** is the object of type [`Addr`].
+    fn into_messages(self, deps: DepsMut, cfg: &Config, to: &Addr) -> StdResult<Vec<SubMsg>> {
+        let mut messages = vec![];
+
+        if !self.astro.is_zero() {
+            messages.push(SubMsg::new(WasmMsg::Execute {
+                contract_addr: cfg.vesting_contract.to_string(),
+                msg: to_binary(&VestingExecuteMsg::Claim {
+                    recipient: Some(to.to_string()),
+                    amount: Some(self.astro),
+                })?,
                 funds: vec![],
-                msg: to_binary(&ProxyExecuteMsg::SendRewards {
+            }));
+        }
+
+        for (lp_token, proxy, amount) in self.proxies {
+            messages.push(proxy_call_submsg(
+                deps.storage,
+                ProxyCall::SendRewards {
+                    lp_token: Some(lp_token),
+                    reward_proxy: proxy.clone(),
                     account: to.clone(),
-                    amount: pending_proxy_rewards,
-                })?,
-            });
+                    amount,
+                },
+                WasmMsg::Execute {
+                    contract_addr: proxy.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ProxyExecuteMsg::SendRewards {
+                        account: to.clone(),
+                        amount,
+                    })?,
+                },
+            )?);
+        }
+
+        for (proxy, amount) in self.unpaid_proxies {
+            UNPAID_PROXY_REWARDS.update::<_, StdError>(deps.storage, (&proxy, to), |v| {
+                Ok(v.unwrap_or_default().checked_add(amount)?)
+            })?;
+        }
+
+        for asset in self.streams {
+            messages.push(SubMsg::new(asset.into_msg(&deps.querier, to.clone())?));
+        }
+
+        Ok(messages)
+    }
+}
+
+/// ## Description
+/// Adds the rewards of the reward proxies to the totals, summing up rewards of the same proxy.
+/// ## Params
+/// * **totals** is a vector that contains the reward proxies along with their rewards.
+///
+/// * **rewards** is a vector that contains the reward proxies along with their rewards.
+fn merge_proxy_rewards(
+    totals: &mut Vec<(Addr, Uint128)>,
+    rewards: Vec<(Addr, Uint128)>,
+) -> StdResult<()> {
+    for (proxy, amount) in rewards {
+        match totals.iter_mut().find(|(addr, _)| *addr == proxy) {
+            Some((_, total)) => *total = total.checked_add(amount)?,
+            None => totals.push((proxy, amount)),
         }
     }
 
-    Ok(messages)
+    Ok(())
+}
+
+/// # Description
+/// Distributes the pending rewards between recipient, the proxy contract and the external reward streams.
+/// Returns an [`ContractError`] on failure, otherwise returns the vector that
+/// contains the objects of type [`SubMsg`].
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **pool** is the object of type [`PoolInfo`].
+///
+/// * **user** is the object of type [`UserInfo`].
+///
+/// * **to** is the object of type [`Addr`].
+pub fn send_pending_rewards(
+    deps: DepsMut,
+    cfg: &Config,
+    lp_token: &Addr,
+    pool: &PoolInfo,
+    user: &UserInfo,
+    to: &Addr,
+) -> Result<Vec<SubMsg>, ContractError> {
+    Ok(PendingRewards::calculate(lp_token, pool, user)?.into_messages(deps, cfg, to)?)
+}
+
+/// # Description
+/// Sends the pending rewards of the account in the given pools to the recipient. ASTRO is claimed
+/// from the vesting contract in one message, proxy and external stream rewards are sent once per
+/// proxy and asset. Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with
+/// the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_tokens** is a vector that contains objects of type [`Addr`].
+///
+/// * **account** is the object of type [`Addr`].
+///
+/// * **recipient** is the object of type [`Addr`].
+pub fn claim_rewards(
+    mut deps: DepsMut,
+    env: Env,
+    lp_tokens: Vec<Addr>,
+    account: Addr,
+    recipient: Addr,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let mut pending = PendingRewards::default();
+
+    for lp_token in &lp_tokens {
+        let mut user = USER_INFO.load(deps.storage, (lp_token, &account))?;
+        let mut pool = POOL_INFO.load(deps.storage, lp_token)?;
+
+        accumulate_rewards_per_share(deps.branch(), &env, lp_token, &mut pool, &cfg, None)?;
+
+        pending.merge(PendingRewards::calculate(lp_token, &pool, &user)?)?;
+
+        let amount = user.amount;
+        let lp_supply = query_lp_supply(deps.as_ref(), &env, lp_token, &pool, None)?;
+        remove_expired_locks(&env, &mut user);
+        let virtual_amount =
+            calculate_virtual_amount(deps.as_ref(), &cfg, &account, &user, amount, lp_supply)?;
+        let user = update_user_balance(user, &mut pool, amount, virtual_amount)?;
+
+        POOL_INFO.save(deps.storage, lp_token, &pool)?;
+        USER_INFO.save(deps.storage, (lp_token, &account), &user)?;
+    }
+
+    Ok(Response::new()
+        .add_submessages(pending.into_messages(deps, &cfg, &recipient)?)
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("recipient", recipient)
+        .add_attribute("pools", lp_tokens.len().to_string()))
 }
 
 /// # Description
@@ -816,6 +2226,9 @@ pub fn send_pending_rewards(
 /// * **beneficiary** is the object of type [`Addr`]. Sets the recipient for Claim operation.
 ///
 /// * **amount** is the object of type [`Uint128`].
+///
+/// * **lock_duration** is an [`Option`] field of type [`u64`]. Locks the deposit for the duration
+/// of the pool's lock-up tier if set.
 // Deposit LP tokens to MasterChef for ASTRO allocation.
 pub fn deposit(
     mut deps: DepsMut,
@@ -823,17 +2236,33 @@ pub fn deposit(
     lp_token: Addr,
     beneficiary: Addr,
     amount: Uint128,
+    lock_duration: Option<u64>,
 ) -> Result<Response, ContractError> {
     let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
     let beneficiary = addr_validate_to_lower(deps.api, beneficiary.as_str())?;
 
-    let user = USER_INFO
+    let mut user = USER_INFO
         .load(deps.storage, (&lp_token, &beneficiary))
         .unwrap_or_default();
 
     let cfg = CONFIG.load(deps.storage)?;
     let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
 
+    // the LP tokens are not sent to the proxy which failed
+    if !amount.is_zero() && pool.reward_proxy.is_some() && pool.reward_proxy_disabled {
+        return Err(ContractError::RewardProxyIsDisabled {});
+    }
+
+    let lock_tier = lock_duration
+        .map(|duration| {
+            pool.lock_tiers
+                .iter()
+                .find(|tier| tier.duration == duration)
+                .cloned()
+                .ok_or(ContractError::LockTierNotFound(duration))
+        })
+        .transpose()?;
+
     accumulate_rewards_per_share(
         deps.branch(),
         &env,
@@ -844,7 +2273,8 @@ pub fn deposit(
     )?;
 
     // send pending rewards
-    let send_rewards_msg = send_pending_rewards(&cfg, &pool, &user, &beneficiary)?;
+    let send_rewards_msg =
+        send_pending_rewards(deps.branch(), &cfg, &lp_token, &pool, &user, &beneficiary)?;
 
     // if reward proxy is set - send lp tokens to proxy
     let transfer_msg = if !amount.is_zero() && pool.reward_proxy.is_some() {
@@ -861,15 +2291,41 @@ pub fn deposit(
         vec![]
     };
 
+    remove_expired_locks(&env, &mut user);
+    if let Some(tier) = lock_tier {
+        add_lock(&env, &mut user, amount, tier)?;
+    }
+
     // Update user balance
     let updated_amount = user.amount.checked_add(amount)?;
-    let user = update_user_balance(user, &pool, updated_amount)?;
+    let lp_supply = query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, Some(amount))?
+        .checked_add(amount)?;
+    if pool.reward_proxy.is_some() {
+        pool.proxy_deposit = Some(lp_supply);
+    }
+    let virtual_amount = calculate_virtual_amount(
+        deps.as_ref(),
+        &cfg,
+        &beneficiary,
+        &user,
+        updated_amount,
+        lp_supply,
+    )?;
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &beneficiary,
+        user.amount,
+        updated_amount,
+    )?;
+    let user = update_user_balance(user, &mut pool, updated_amount, virtual_amount)?;
 
     POOL_INFO.save(deps.storage, &lp_token, &pool)?;
     USER_INFO.save(deps.storage, (&lp_token, &beneficiary), &user)?;
 
     Ok(Response::new()
-        .add_messages(send_rewards_msg)
+        .add_submessages(send_rewards_msg)
         .add_messages(transfer_msg)
         .add_attribute("action", "deposit")
         .add_attribute("amount", amount))
@@ -897,48 +2353,85 @@ pub fn withdraw(
 ) -> Result<Response, ContractError> {
     let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
 
-    let user = USER_INFO
+    let mut user = USER_INFO
         .load(deps.storage, (&lp_token, &account))
         .unwrap_or_default();
     if user.amount < amount {
         return Err(ContractError::BalanceTooSmall {});
     }
 
-    let cfg = CONFIG.load(deps.storage)?;
+    let locked = user.locked_amount(env.block.time.seconds())?;
+    if user.amount.checked_sub(locked)? < amount {
+        return Err(ContractError::AmountIsLocked { locked });
+    }
+
+    let cfg = CONFIG.load(deps.storage)?;
     let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
 
     accumulate_rewards_per_share(deps.branch(), &env, &lp_token, &mut pool, &cfg, None)?;
 
     // send pending rewards
-    let send_rewards_msg = send_pending_rewards(&cfg, &pool, &user, &account)?;
+    let send_rewards_msg =
+        send_pending_rewards(deps.branch(), &cfg, &lp_token, &pool, &user, &account)?;
 
-    // call to transfer function for lp token
+    // call to transfer function for lp token, a failure of the proxy restores the deposit
     let transfer_msg = if !amount.is_zero() {
         vec![match &pool.reward_proxy {
-            Some(proxy) => WasmMsg::Execute {
-                contract_addr: proxy.to_string(),
-                funds: vec![],
-                msg: to_binary(&ProxyExecuteMsg::Withdraw {
+            Some(proxy) => proxy_call_submsg(
+                deps.storage,
+                ProxyCall::Withdraw {
+                    lp_token: lp_token.clone(),
+                    reward_proxy: proxy.clone(),
                     account: account.clone(),
                     amount,
-                })?,
-            },
-            None => WasmMsg::Execute {
+                },
+                WasmMsg::Execute {
+                    contract_addr: proxy.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ProxyExecuteMsg::Withdraw {
+                        account: account.clone(),
+                        amount,
+                    })?,
+                },
+            )?,
+            None => SubMsg::new(WasmMsg::Execute {
                 contract_addr: lp_token.to_string(),
                 msg: to_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: account.to_string(),
                     amount,
                 })?,
                 funds: vec![],
-            },
+            }),
         }]
     } else {
         vec![]
     };
 
     // Update user balance
+    remove_expired_locks(&env, &mut user);
     let updated_amount = user.amount.checked_sub(amount)?;
-    let user = update_user_balance(user, &pool, updated_amount)?;
+    let lp_supply =
+        query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?.checked_sub(amount)?;
+    if pool.reward_proxy.is_some() {
+        pool.proxy_deposit = Some(lp_supply);
+    }
+    let virtual_amount = calculate_virtual_amount(
+        deps.as_ref(),
+        &cfg,
+        &account,
+        &user,
+        updated_amount,
+        lp_supply,
+    )?;
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &account,
+        user.amount,
+        updated_amount,
+    )?;
+    let user = update_user_balance(user, &mut pool, updated_amount, virtual_amount)?;
 
     POOL_INFO.save(deps.storage, &lp_token, &pool)?;
 
@@ -949,12 +2442,131 @@ pub fn withdraw(
     }
 
     Ok(Response::new()
-        .add_messages(send_rewards_msg)
-        .add_messages(transfer_msg)
+        .add_submessages(send_rewards_msg)
+        .add_submessages(transfer_msg)
         .add_attribute("action", "withdraw")
         .add_attribute("amount", amount))
 }
 
+/// # Description
+/// Moves a part of the account's deposit to the recipient. The pending rewards of both accounts are
+/// sent to them, locked deposits can't be transferred. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **account** is the object of type [`Addr`]. The owner of the deposit.
+///
+/// * **recipient** is the object of type [`Addr`]. The recipient of the deposit.
+///
+/// * **amount** is the object of type [`Uint128`].
+pub fn transfer_position(
+    mut deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+    account: Addr,
+    recipient: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+
+    let mut user = USER_INFO
+        .load(deps.storage, (&lp_token, &account))
+        .unwrap_or_default();
+    if user.amount < amount {
+        return Err(ContractError::BalanceTooSmall {});
+    }
+
+    let locked = user.locked_amount(env.block.time.seconds())?;
+    if user.amount.checked_sub(locked)? < amount {
+        return Err(ContractError::AmountIsLocked { locked });
+    }
+
+    let mut recipient_info = USER_INFO
+        .load(deps.storage, (&lp_token, &recipient))
+        .unwrap_or_default();
+
+    let cfg = CONFIG.load(deps.storage)?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    accumulate_rewards_per_share(deps.branch(), &env, &lp_token, &mut pool, &cfg, None)?;
+
+    // send pending rewards of both accounts
+    let mut send_rewards_msg =
+        send_pending_rewards(deps.branch(), &cfg, &lp_token, &pool, &user, &account)?;
+    send_rewards_msg.append(&mut send_pending_rewards(
+        deps.branch(),
+        &cfg,
+        &lp_token,
+        &pool,
+        &recipient_info,
+        &recipient,
+    )?);
+
+    // The LP tokens stay in the pool, so the staked amount doesn't change
+    let lp_supply = query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?;
+
+    remove_expired_locks(&env, &mut user);
+    let updated_amount = user.amount.checked_sub(amount)?;
+    let virtual_amount = calculate_virtual_amount(
+        deps.as_ref(),
+        &cfg,
+        &account,
+        &user,
+        updated_amount,
+        lp_supply,
+    )?;
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &account,
+        user.amount,
+        updated_amount,
+    )?;
+    let user = update_user_balance(user, &mut pool, updated_amount, virtual_amount)?;
+
+    remove_expired_locks(&env, &mut recipient_info);
+    let updated_amount = recipient_info.amount.checked_add(amount)?;
+    let virtual_amount = calculate_virtual_amount(
+        deps.as_ref(),
+        &cfg,
+        &recipient,
+        &recipient_info,
+        updated_amount,
+        lp_supply,
+    )?;
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &recipient,
+        recipient_info.amount,
+        updated_amount,
+    )?;
+    let recipient_info =
+        update_user_balance(recipient_info, &mut pool, updated_amount, virtual_amount)?;
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+    USER_INFO.save(deps.storage, (&lp_token, &recipient), &recipient_info)?;
+
+    if !user.amount.is_zero() {
+        USER_INFO.save(deps.storage, (&lp_token, &account), &user)?;
+    } else {
+        USER_INFO.remove(deps.storage, (&lp_token, &account));
+    }
+
+    Ok(Response::new()
+        .add_submessages(send_rewards_msg)
+        .add_attribute("action", "transfer_position")
+        .add_attribute("recipient", recipient)
+        .add_attribute("amount", amount))
+}
+
 /// # Description
 /// Withdraw without caring about rewards. EMERGENCY ONLY. Returns an [`ContractError`] on
 /// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
@@ -968,7 +2580,7 @@ pub fn withdraw(
 /// * **lp_token** is the object of type [`Addr`].
 pub fn emergency_withdraw(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     lp_token: Addr,
 ) -> Result<Response, ContractError> {
@@ -980,9 +2592,25 @@ pub fn emergency_withdraw(
     pool.orphan_proxy_rewards = pool.orphan_proxy_rewards.checked_add(
         pool.accumulated_proxy_rewards_per_share
             .checked_mul(user.amount)?
-            .saturating_sub(user.reward_debt_proxy),
+            .saturating_sub(proxy_reward_debt(&pool, &user)),
     )?;
 
+    // The rewards left on the retired proxies were earned before the proxy was changed
+    for (proxy, amount) in retired_proxy_rewards(&pool, &user)? {
+        UNPAID_PROXY_REWARDS.update::<_, StdError>(deps.storage, (&proxy, &info.sender), |v| {
+            Ok(v.unwrap_or_default().checked_add(amount)?)
+        })?;
+    }
+
+    // Locked amounts are released as well, the lock-up bonus is forfeited along with the rewards
+    if let Some(total_virtual_amount) = pool.total_virtual_amount {
+        pool.total_virtual_amount = Some(total_virtual_amount.checked_sub(user.virtual_amount())?);
+    }
+
+    if let Some(proxy_deposit) = pool.proxy_deposit {
+        pool.proxy_deposit = Some(proxy_deposit.checked_sub(user.amount)?);
+    }
+
     //call to transfer function for lp token
     let transfer_msg: WasmMsg;
     if let Some(proxy) = &pool.reward_proxy {
@@ -1006,6 +2634,14 @@ pub fn emergency_withdraw(
     }
 
     // Change user balance
+    checkpoint_deposit(
+        deps.storage,
+        env.block.height,
+        &lp_token,
+        &info.sender,
+        user.amount,
+        Uint128::zero(),
+    )?;
     USER_INFO.remove(deps.storage, (&lp_token, &info.sender));
     POOL_INFO.save(deps.storage, &lp_token, &pool)?;
 
@@ -1049,137 +2685,1631 @@ fn set_allowed_reward_proxies(
 }
 
 /// # Description
-/// Sends the orphan proxy rewards which are left by emergency withdrawals. Returns an [`ContractError`] on
+/// Sets the price sources of the assets used to calculate the APR of the pools. Returns an
+/// [`ContractError`] on failure, otherwise returns the [`Response`] with the specified attributes
+/// if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **sources** is a vector that contains the assets along with their price sources.
+fn set_price_sources(
+    deps: DepsMut,
+    info: MessageInfo,
+    sources: Vec<(AssetInfo, PriceSource)>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut price_sources = vec![];
+    for (asset_info, source) in sources {
+        asset_info.check(deps.api)?;
+        let source = match source {
+            PriceSource::Oracle { contract } => PriceSource::Oracle {
+                contract: addr_validate_to_lower(deps.api, contract.as_str())?,
+            },
+            fixed => fixed,
+        };
+        price_sources.push((asset_info, source));
+    }
+
+    config.price_sources = price_sources;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "set_price_sources"))
+}
+
+/// # Description
+/// Sets the assets allowed in external reward streams along with the minimum funding amounts.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified
+/// attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **assets** is a vector that contains the assets along with the minimum funding amounts.
+fn set_reward_stream_assets(
+    deps: DepsMut,
+    info: MessageInfo,
+    assets: Vec<(AssetInfo, Uint128)>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for (asset_info, _) in &assets {
+        asset_info.check(deps.api)?;
+    }
+
+    config.reward_stream_assets = assets;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attribute("action", "set_reward_stream_assets"))
+}
+
+/// # Description
+/// Sets the lock-up tiers of the pool. Returns an [`ContractError`] on
 /// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
 /// # Params
 /// * **deps** is the object of type [`DepsMut`].
 ///
 /// * **info** is the object of type [`MessageInfo`].
 ///
-/// * **recipient** is the object of type [`String`].
+/// * **lp_token** is the object of type [`Addr`].
 ///
-/// * **lp_token** is the object of type [`String`].
-fn send_orphan_proxy_rewards(
+/// * **tiers** is a vector that contains objects of type [`LockTier`].
+fn set_lock_tiers(
     deps: DepsMut,
     info: MessageInfo,
-    recipient: String,
-    lp_token: String,
+    lp_token: Addr,
+    tiers: Vec<LockTier>,
 ) -> Result<Response, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    if tiers.len() > MAX_LOCK_TIERS {
+        return Err(ContractError::LockTierLimitExceeded(MAX_LOCK_TIERS));
+    }
+
+    for (i, tier) in tiers.iter().enumerate() {
+        if tier.duration == 0
+            || tier.multiplier < Decimal::one()
+            || tiers[..i].iter().any(|t| t.duration == tier.duration)
+        {
+            return Err(ContractError::InvalidLockTiers {});
+        }
+    }
+
+    pool.lock_tiers = tiers;
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_lock_tiers")
+        .add_attribute("lp_token", lp_token))
+}
+
+/// ## Description
+/// Removes the expired locks of the user, so their amounts lose the lock-up bonus. The bonus is
+/// accrued until the locks are removed rather than until their unlock time, so keepers are expected
+/// to checkpoint users with expired locks.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **user** is the object of type [`UserInfo`].
+fn remove_expired_locks(env: &Env, user: &mut UserInfo) {
+    let now = env.block.time.seconds();
+    user.locks.retain(|lock| lock.unlock_time > now);
+}
+
+/// ## Description
+/// Locks the deposited amount of the user for the duration of the lock-up tier. Merges the lock
+/// with an existing one ending at the same time. Returns an [`ContractError`] if the user has
+/// too many active locks.
+/// ## Params
+/// * **env** is the object of type [`Env`].
+///
+/// * **user** is the object of type [`UserInfo`].
+///
+/// * **amount** is the object of type [`Uint128`].
+///
+/// * **tier** is the object of type [`LockTier`].
+fn add_lock(
+    env: &Env,
+    user: &mut UserInfo,
+    amount: Uint128,
+    tier: LockTier,
+) -> Result<(), ContractError> {
+    let unlock_time = env.block.time.seconds() + tier.duration;
+
+    match user
+        .locks
+        .iter_mut()
+        .find(|lock| lock.unlock_time == unlock_time && lock.multiplier == tier.multiplier)
+    {
+        Some(lock) => lock.amount = lock.amount.checked_add(amount)?,
+        None => {
+            if user.locks.len() >= MAX_USER_LOCKS {
+                return Err(ContractError::LockLimitExceeded(MAX_USER_LOCKS));
+            }
+
+            user.locks.push(LockedPosition {
+                amount,
+                multiplier: tier.multiplier,
+                unlock_time,
+            });
+        }
+    }
+
+    Ok(())
+}
 
+/// # Description
+/// Enables the disabled reward proxy of the pool. The reward balance of the proxy is used as
+/// the starting point for the next proxy rewards update. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+fn reattach_reward_proxy(
+    deps: DepsMut,
+    info: MessageInfo,
+    lp_token: Addr,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
     if info.sender != cfg.owner {
         return Err(ContractError::Unauthorized {});
-    };
+    }
 
-    let lp_token = addr_validate_to_lower(deps.api, &lp_token)?;
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    let reward_proxy = pool
+        .reward_proxy
+        .clone()
+        .ok_or(ContractError::PoolDoesNotHaveAdditionalRewards {})?;
+    if !pool.reward_proxy_disabled {
+        return Err(ContractError::RewardProxyIsNotDisabled {});
+    }
+
+    pool.proxy_reward_balance_before_update = deps
+        .querier
+        .query_wasm_smart(&reward_proxy, &ProxyQueryMsg::Reward {})?;
+    pool.reward_proxy_disabled = false;
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "reattach_reward_proxy")
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("reward_proxy", reward_proxy))
+}
+
+/// # Description
+/// Sends the proxy rewards of the sender which were not paid because of a failure of the proxy.
+/// The rewards are kept if the proxy fails again. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **reward_proxy** is the object of type [`String`].
+fn claim_unpaid_proxy_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    reward_proxy: String,
+) -> Result<Response, ContractError> {
+    let reward_proxy = addr_validate_to_lower(deps.api, &reward_proxy)?;
+
+    let amount = UNPAID_PROXY_REWARDS
+        .may_load(deps.storage, (&reward_proxy, &info.sender))?
+        .unwrap_or_default();
+    if amount.is_zero() {
+        return Err(ContractError::NoUnpaidProxyRewards {});
+    }
+
+    UNPAID_PROXY_REWARDS.remove(deps.storage, (&reward_proxy, &info.sender));
+
+    TMP_PROXY_CALLS.save(deps.storage, &vec![])?;
+    let send_rewards_msg = proxy_call_submsg(
+        deps.storage,
+        ProxyCall::SendRewards {
+            lp_token: None,
+            reward_proxy: reward_proxy.clone(),
+            account: info.sender.clone(),
+            amount,
+        },
+        WasmMsg::Execute {
+            contract_addr: reward_proxy.to_string(),
+            funds: vec![],
+            msg: to_binary(&ProxyExecuteMsg::SendRewards {
+                account: info.sender,
+                amount,
+            })?,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_submessage(send_rewards_msg)
+        .add_attribute("action", "claim_unpaid_proxy_rewards")
+        .add_attribute("amount", amount))
+}
+
+/// # Description
+/// Allows the operator to claim the rewards of the sender to the recipient. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **operator** is the object of type [`String`].
+///
+/// * **recipient** is the object of type [`String`]. The recipient of the claimed rewards.
+fn set_claim_operator(
+    deps: DepsMut,
+    info: MessageInfo,
+    operator: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let operator = addr_validate_to_lower(deps.api, &operator)?;
+    let recipient = addr_validate_to_lower(deps.api, &recipient)?;
+
+    CLAIM_OPERATORS.save(deps.storage, (&info.sender, &operator), &recipient)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_claim_operator")
+        .add_attribute("operator", operator)
+        .add_attribute("recipient", recipient))
+}
+
+/// # Description
+/// Moves the LP tokens of the pool from the current reward proxy to the new one, or to the generator
+/// if the new proxy is not set. The proxy rewards are accumulated before the move, the rewards which
+/// are left on the retired proxy are settled for each user on their next action in the pool.
+/// Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified
+/// attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **new_proxy** is an [`Option`] field object of type [`Addr`].
+///
+/// ##Executor
+/// Can only be called by the owner
+fn move_to_proxy(
+    mut deps: DepsMut,
+    env: Env,
+    lp_token: Addr,
+    new_proxy: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    if pool.reward_proxy.is_none() && new_proxy.is_none() {
+        return Err(ContractError::PoolDoesNotHaveAdditionalRewards {});
+    }
+    if pool.reward_proxy == new_proxy {
+        return Err(ContractError::SameRewardProxy {});
+    }
+    if let Some(proxy) = &new_proxy {
+        if !cfg.allowed_reward_proxies.contains(proxy) {
+            return Err(ContractError::RewardProxyNotAllowed {});
+        }
+    }
+
+    accumulate_rewards_per_share(deps.branch(), &env, &lp_token, &mut pool, &cfg, None)?;
+
+    let amount = query_lp_supply(deps.as_ref(), &env, &lp_token, &pool, None)?;
+    let mut messages = vec![];
+
+    if let Some(proxy) = pool.reward_proxy.take() {
+        // The rewards received while nothing is staked are orphaned along with the proxy
+        if amount.is_zero() && !pool.reward_proxy_disabled {
+            let reward_amount: Uint128 = deps
+                .querier
+                .query_wasm_smart(&proxy, &ProxyQueryMsg::Reward {})?;
+            pool.orphan_proxy_rewards = pool.orphan_proxy_rewards.checked_add(
+                reward_amount.saturating_sub(pool.proxy_reward_balance_before_update),
+            )?;
+        }
+
+        pool.retired_proxies.push(RetiredProxy {
+            reward_proxy: proxy.clone(),
+            accumulated_proxy_rewards_per_share: pool.accumulated_proxy_rewards_per_share,
+            orphan_proxy_rewards: pool.orphan_proxy_rewards,
+        });
+
+        if !amount.is_zero() {
+            messages.push(WasmMsg::Execute {
+                contract_addr: proxy.to_string(),
+                funds: vec![],
+                msg: to_binary(&ProxyExecuteMsg::Withdraw {
+                    account: env.contract.address.clone(),
+                    amount,
+                })?,
+            });
+        }
+    }
+
+    pool.accumulated_proxy_rewards_per_share = Decimal::zero();
+    pool.orphan_proxy_rewards = Uint128::zero();
+    pool.reward_proxy_disabled = false;
+    pool.proxy_reward_balance_before_update = Uint128::zero();
+    pool.proxy_deposit = new_proxy.as_ref().map(|_| amount);
+
+    if let Some(proxy) = &new_proxy {
+        pool.proxy_reward_balance_before_update = deps
+            .querier
+            .query_wasm_smart(proxy, &ProxyQueryMsg::Reward {})?;
+
+        if !amount.is_zero() {
+            messages.push(WasmMsg::Execute {
+                contract_addr: lp_token.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: proxy.to_string(),
+                    msg: to_binary(&ProxyCw20HookMsg::Deposit {})?,
+                    amount,
+                })?,
+                funds: vec![],
+            });
+        }
+    }
+    pool.reward_proxy = new_proxy;
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "move_to_proxy")
+        .add_attribute("lp_token", lp_token)
+        .add_attribute(
+            "reward_proxy",
+            pool.reward_proxy
+                .map(|proxy| proxy.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+        )
+        .add_attribute("amount", amount))
+}
+
+/// # Description
+/// Locks the received xASTRO as the gauge voting weight of the user. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **user** is the object of type [`Addr`].
+///
+/// * **amount** is the object of type [`Uint128`].
+fn lock_voting_power(
+    deps: DepsMut,
+    info: MessageInfo,
+    user: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if cfg.xastro_token != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let voting_power = VOTING_POWER.update::<_, StdError>(deps.storage, &user, |v| {
+        Ok(v.unwrap_or_default().checked_add(amount)?)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "lock_voting_power")
+        .add_attribute("user", user)
+        .add_attribute("amount", amount)
+        .add_attribute("voting_power", voting_power))
+}
+
+/// # Description
+/// Writes the current deposits of up to `limit` users to the deposit snapshots. Once all deposits
+/// are seeded, the snapshots are available since the next block. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **limit** is an [`Option`] field of type [`u32`].
+///
+/// ##Executor
+/// Only owner can execute it
+fn seed_snapshots(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if SNAPSHOTS_START.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::SnapshotsAlreadySeeded {});
+    }
+
+    let finished = seed_deposit_snapshots(deps.storage, env.block.height, limit)?;
+    if finished {
+        SNAPSHOTS_START.save(deps.storage, &(env.block.height + 1))?;
+        SNAPSHOTS_SEED_CURSOR.remove(deps.storage);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "seed_snapshots")
+        .add_attribute("finished", finished.to_string()))
+}
+
+/// # Description
+/// Sends the locked xASTRO back to the sender. The xASTRO can't be unlocked in the epoch
+/// of the votes of the sender, so it can't be voted with twice. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **amount** is the object of type [`Uint128`].
+fn unlock_voting_power(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let xastro_token = cfg
+        .xastro_token
+        .ok_or(ContractError::GaugeVotingDisabled {})?;
+
+    if let (Some(epoch_length), Some(votes)) = (
+        cfg.gauge_epoch_length,
+        USER_VOTES.may_load(deps.storage, &info.sender)?,
+    ) {
+        if votes.epoch == env.block.time.seconds() / epoch_length {
+            return Err(ContractError::VotingPowerIsLocked {});
+        }
+    }
+
+    let voting_power = VOTING_POWER
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default()
+        .checked_sub(amount)
+        .map_err(|_| ContractError::NoVotingPower {})?;
+    if voting_power.is_zero() {
+        VOTING_POWER.remove(deps.storage, &info.sender);
+    } else {
+        VOTING_POWER.save(deps.storage, &info.sender, &voting_power)?;
+    }
+
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: xastro_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+        })
+        .add_attribute("action", "unlock_voting_power")
+        .add_attribute("amount", amount)
+        .add_attribute("voting_power", voting_power))
+}
+
+/// # Description
+/// Votes for the pools with the xASTRO locked by the sender in the current epoch, the previous
+/// votes of the sender in the epoch are revoked. The locked xASTRO can't be unlocked until
+/// the next epoch. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **votes** is a vector that contains the LP token contracts along with the share of
+/// the voting weight in basis points.
+fn vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    votes: Vec<(String, u16)>,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let epoch_length = match (cfg.gauge_epoch_length, cfg.xastro_token) {
+        (Some(epoch_length), Some(_)) => epoch_length,
+        _ => return Err(ContractError::GaugeVotingDisabled {}),
+    };
+    let epoch = env.block.time.seconds() / epoch_length;
+
+    let votes = votes
+        .into_iter()
+        .map(|(lp_token, bps)| Ok((addr_validate_to_lower(deps.api, &lp_token)?, bps)))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut total_bps = 0u32;
+    for (i, (lp_token, bps)) in votes.iter().enumerate() {
+        total_bps += *bps as u32;
+        if *bps == 0
+            || ACTIVE_POOLS.may_load(deps.storage, lp_token)?.is_none()
+            || votes[..i].iter().any(|(v, _)| v == lp_token)
+        {
+            return Err(ContractError::InvalidVotes {});
+        }
+    }
+    if total_bps > MAX_VOTE_BPS {
+        return Err(ContractError::InvalidVotes {});
+    }
+
+    let weight = VOTING_POWER
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if weight.is_zero() {
+        return Err(ContractError::NoVotingPower {});
+    }
+
+    if let Some(previous) = USER_VOTES.may_load(deps.storage, &info.sender)? {
+        if previous.epoch == epoch {
+            for (lp_token, bps) in &previous.votes {
+                EPOCH_VOTES.update::<_, StdError>(
+                    deps.storage,
+                    (U64Key::new(epoch), lp_token),
+                    |v| {
+                        Ok(v.unwrap_or_default()
+                            .checked_sub(previous.weight.multiply_ratio(*bps, MAX_VOTE_BPS))?)
+                    },
+                )?;
+            }
+        }
+    }
+
+    for (lp_token, bps) in &votes {
+        EPOCH_VOTES.update::<_, StdError>(deps.storage, (U64Key::new(epoch), lp_token), |v| {
+            Ok(v.unwrap_or_default()
+                .checked_add(weight.multiply_ratio(*bps, MAX_VOTE_BPS))?)
+        })?;
+    }
+
+    USER_VOTES.save(
+        deps.storage,
+        &info.sender,
+        &UserVotes {
+            epoch,
+            weight,
+            votes,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "vote")
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("weight", weight))
+}
+
+/// ## Description
+/// Returns the allocation points of the pools proportional to their votes in the epoch.
+/// The result is empty if there are no votes.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **epoch** is the object of type [`u64`].
+fn epoch_allocations(deps: Deps, epoch: u64) -> StdResult<Vec<(Addr, Uint64)>> {
+    let votes = read_epoch_votes(deps.storage, epoch)?;
+
+    let total = votes
+        .iter()
+        .try_fold(Uint128::zero(), |acc, (_, v)| acc.checked_add(*v))?;
+    if total.is_zero() {
+        return Ok(vec![]);
+    }
+
+    Ok(votes
+        .into_iter()
+        .map(|(lp_token, v)| {
+            let alloc_point = v.multiply_ratio(GAUGE_ALLOC_POINTS, total).u128() as u64;
+            (lp_token, Uint64::from(alloc_point))
+        })
+        .collect())
+}
+
+/// # Description
+/// Sets the allocation points of the pools according to the votes of the epoch. Pools without
+/// votes get no ASTRO, the allocation points are not changed if nobody voted in the epoch.
+/// Deactivated pools are skipped. Returns an [`ContractError`] on failure, otherwise returns the [`Response`] with the specified
+/// attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **epoch** is the object of type [`u64`].
+fn tune_allocations(mut deps: DepsMut, env: Env, epoch: u64) -> Result<Response, ContractError> {
+    LAST_TUNED_EPOCH.save(deps.storage, &epoch)?;
+
+    let allocations = epoch_allocations(deps.as_ref(), epoch)?;
+    if allocations.is_empty() {
+        return Ok(Response::new()
+            .add_attribute("action", "tune_allocations")
+            .add_attribute("epoch", epoch.to_string()));
+    }
+
+    mass_update_pools(deps.branch(), env)?;
+
+    let mut cfg = CONFIG.load(deps.storage)?;
+    cfg.total_alloc_point = Uint64::zero();
+
+    for (lp_token, mut pool) in get_active_pools(deps.storage)? {
+        pool.alloc_point = allocations
+            .iter()
+            .find(|(v, _)| *v == lp_token)
+            .map(|(_, alloc_point)| *alloc_point)
+            .unwrap_or_default();
+        cfg.total_alloc_point = cfg.total_alloc_point.checked_add(pool.alloc_point)?;
+
+        POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+    }
+
+    CONFIG.save(deps.storage, &cfg)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "tune_allocations")
+        .add_attribute("epoch", epoch.to_string())
+        .add_attribute("pools", allocations.len().to_string()))
+}
+
+/// # Description
+/// Sends the orphan proxy rewards which are left by emergency withdrawals on the current and the retired
+/// reward proxies of the pool. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] with the specified attributes if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **recipient** is the object of type [`String`].
+///
+/// * **lp_token** is the object of type [`String`].
+fn send_orphan_proxy_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+    lp_token: String,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if info.sender != cfg.owner {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let lp_token = addr_validate_to_lower(deps.api, &lp_token)?;
+
+    let recipient = addr_validate_to_lower(deps.api, &recipient)?;
+
+    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    if pool.reward_proxy.is_none() && pool.retired_proxies.is_empty() {
+        return Err(ContractError::PoolDoesNotHaveAdditionalRewards {});
+    }
+
+    let mut rewards = vec![];
+    if let Some(proxy) = &pool.reward_proxy {
+        rewards.push((proxy.clone(), pool.orphan_proxy_rewards));
+        pool.orphan_proxy_rewards = Uint128::zero();
+    }
+    for retired in pool.retired_proxies.iter_mut() {
+        rewards.push((retired.reward_proxy.clone(), retired.orphan_proxy_rewards));
+        retired.orphan_proxy_rewards = Uint128::zero();
+    }
+    rewards.retain(|(_, amount)| !amount.is_zero());
+
+    if rewards.is_empty() {
+        return Err(ContractError::OrphanRewardsTooSmall {});
+    }
+
+    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+
+    let mut amount = Uint128::zero();
+    let mut messages = vec![];
+    for (proxy, proxy_amount) in rewards {
+        amount = amount.checked_add(proxy_amount)?;
+        messages.push(WasmMsg::Execute {
+            contract_addr: proxy.to_string(),
+            funds: vec![],
+            msg: to_binary(&ProxyExecuteMsg::SendRewards {
+                account: recipient.clone(),
+                amount: proxy_amount,
+            })?,
+        });
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attribute("action", "send_orphan_rewards")
+        .add_attribute("recipient", recipient.to_string())
+        .add_attribute("lp_token", lp_token)
+        .add_attribute("amount", amount))
+}
+
+/// ## Description
+/// Available the query messages of the contract.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::PoolLength {}** Returns information about the pool length in the
+/// [`PoolLengthResponse`] object.
+///
+/// * **QueryMsg::Deposit { lp_token, user }** Returns information about the user deposit amount.
+///
+/// * **QueryMsg::PendingToken { lp_token, user }** Returns information in
+/// the [`PendingTokenResponse`] object.
+///
+/// * **QueryMsg::Config {}** Returns information about the generator configs in
+/// the [`ConfigResponse`] object.
+///
+/// * **QueryMsg::RewardInfo { lp_token }** Returns information about the reward of pool
+/// in a [`RewardInfoResponse`] object.
+///
+/// * **QueryMsg::OrphanProxyRewards { lp_token }** Returns information about the orphan proxy rewards.
+///
+/// * **QueryMsg::RewardStreams { lp_token }** Returns the external reward streams of the pool.
+///
+/// * **QueryMsg::Incentives { lp_token }** Returns the active incentive schedules of the pool
+/// in the [`IncentiveSchedule`] objects.
+///
+/// * **QueryMsg::EmissionSchedule {}** Returns the emission schedule in the [`EmissionScheduleResponse`] object.
+///
+/// * **QueryMsg::UserBoost { lp_token, user }** Returns the boosted amount of the user's deposit
+/// in the [`UserBoostResponse`] object.
+///
+/// * **QueryMsg::UserPositions {
+///             user,
+///             start_after,
+///             limit,
+///         }** Returns the deposits and pending rewards of the user in the [`UserPositionResponse`] objects.
+///
+/// * **QueryMsg::Pools { start_after, limit }** Returns information about the pools
+/// in the [`PoolInfoResponse`] objects.
+///
+/// * **QueryMsg::UserLocks { lp_token, user }** Returns the locked deposits of the user
+/// in the [`LockedPosition`] objects.
+///
+/// * **QueryMsg::UnpaidProxyRewards { reward_proxy, user }** Returns the proxy rewards of the user
+/// which were not paid because of a failure of the proxy.
+///
+/// * **QueryMsg::ClaimOperators { user }** Returns the operators which are allowed to claim
+/// the rewards of the user in the [`ClaimOperatorResponse`] objects.
+///
+/// * **QueryMsg::PoolApr { lp_token }** Returns the APR of the rewards and the value of the LP tokens
+/// staked in the pool in the [`PoolAprResponse`] object.
+///
+/// * **QueryMsg::DepositAt { lp_token, user, height }** Returns the deposit of the user in the pool
+/// at the beginning of the block.
+///
+/// * **QueryMsg::TotalDepositAt { lp_token, height }** Returns the total deposit of the pool
+/// at the beginning of the block.
+///
+/// * **QueryMsg::UserVotes { user }** Returns the latest gauge votes of the user
+/// in the [`UserVotesResponse`] object.
+///
+/// * **QueryMsg::VotingPower { user }** Returns the amount of xASTRO locked by the user
+/// for gauge voting.
+///
+/// * **QueryMsg::EpochVotes { epoch }** Returns the vote tally of the epoch
+/// in the [`EpochVotesResponse`] object.
+///
+/// * **QueryMsg::ProjectedAllocations {}** Returns the allocation points according to the votes
+/// of the current epoch in the [`ProjectedAllocationsResponse`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::PoolLength {} => Ok(to_binary(&pool_length(deps)?)?),
+        QueryMsg::Deposit { lp_token, user } => {
+            Ok(to_binary(&query_deposit(deps, lp_token, user)?)?)
+        }
+        QueryMsg::PendingToken { lp_token, user } => {
+            Ok(to_binary(&pending_token(deps, env, lp_token, user)?)?)
+        }
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::RewardInfo { lp_token } => Ok(to_binary(&query_reward_info(deps, lp_token)?)?),
+        QueryMsg::OrphanProxyRewards { lp_token } => {
+            Ok(to_binary(&query_orphan_proxy_rewards(deps, lp_token)?)?)
+        }
+        QueryMsg::RewardStreams { lp_token } => {
+            Ok(to_binary(&query_reward_streams(deps, lp_token)?)?)
+        }
+        QueryMsg::Incentives { lp_token } => {
+            Ok(to_binary(&query_incentives(deps, env, lp_token)?)?)
+        }
+        QueryMsg::EmissionSchedule {} => Ok(to_binary(&query_emission_schedule(deps, env)?)?),
+        QueryMsg::UserBoost { lp_token, user } => {
+            Ok(to_binary(&query_user_boost(deps, env, lp_token, user)?)?)
+        }
+        QueryMsg::UserPositions {
+            user,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_user_positions(
+            deps,
+            env,
+            user,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::Pools { start_after, limit } => {
+            Ok(to_binary(&query_pools(deps, start_after, limit)?)?)
+        }
+        QueryMsg::UserLocks { lp_token, user } => {
+            Ok(to_binary(&query_user_locks(deps, lp_token, user)?)?)
+        }
+        QueryMsg::UnpaidProxyRewards { reward_proxy, user } => Ok(to_binary(
+            &query_unpaid_proxy_rewards(deps, reward_proxy, user)?,
+        )?),
+        QueryMsg::ClaimOperators { user } => Ok(to_binary(&query_claim_operators(deps, user)?)?),
+        QueryMsg::PoolApr { lp_token } => Ok(to_binary(&query_pool_apr(deps, env, lp_token)?)?),
+        QueryMsg::DepositAt {
+            lp_token,
+            user,
+            height,
+        } => Ok(to_binary(&query_deposit_at(deps, lp_token, user, height)?)?),
+        QueryMsg::TotalDepositAt { lp_token, height } => {
+            Ok(to_binary(&query_total_deposit_at(deps, lp_token, height)?)?)
+        }
+        QueryMsg::UserVotes { user } => Ok(to_binary(&query_user_votes(deps, user)?)?),
+        QueryMsg::VotingPower { user } => Ok(to_binary(&query_voting_power(deps, user)?)?),
+        QueryMsg::EpochVotes { epoch } => Ok(to_binary(&query_epoch_votes(deps, env, epoch)?)?),
+        QueryMsg::ProjectedAllocations {} => {
+            Ok(to_binary(&query_projected_allocations(deps, env)?)?)
+        }
+    }
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the pool length
+/// in a [`PoolLengthResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+pub fn pool_length(deps: Deps) -> Result<PoolLengthResponse, ContractError> {
+    let length = POOL_INFO
+        .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
+        .count();
+    Ok(PoolLengthResponse { length })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the user deposit amount.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **user** is the object of type [`Addr`].
+pub fn query_deposit(deps: Deps, lp_token: Addr, user: Addr) -> Result<Uint128, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+
+    let user_info = USER_INFO
+        .load(deps.storage, (&lp_token, &user))
+        .unwrap_or_default();
+    Ok(user_info.amount)
+}
+
+/// ## Description
+/// Calculates pending token. Returns an [`ContractError`] on failure, otherwise returns
+/// information in a [`PendingTokenResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **user** is the object of type [`Addr`].
+// View function to see pending ASTRO on frontend.
+pub fn pending_token(
+    deps: Deps,
+    env: Env,
+    lp_token: Addr,
+    user: Addr,
+) -> Result<PendingTokenResponse, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+
+    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let user_info = USER_INFO
+        .load(deps.storage, (&lp_token, &user))
+        .unwrap_or_default();
+
+    let mut pending_on_proxy = None;
+
+    let lp_supply = query_lp_supply(deps, &env, &lp_token, &pool, None)?;
+
+    if let Some(proxy) = &pool.reward_proxy {
+        if !lp_supply.is_zero() {
+            // The rewards of a disabled proxy are not updated
+            let res: Option<Uint128> = if pool.reward_proxy_disabled {
+                None
+            } else {
+                deps.querier
+                    .query_wasm_smart(proxy, &ProxyQueryMsg::PendingToken {})?
+            };
+
+            let mut acc_per_share_on_proxy = pool.accumulated_proxy_rewards_per_share;
+            if let Some(token_rewards) = res {
+                let share = Decimal::from_ratio(token_rewards, lp_supply);
+                acc_per_share_on_proxy = pool
+                    .accumulated_proxy_rewards_per_share
+                    .checked_add(share)?;
+            }
+
+            pending_on_proxy = Some(
+                acc_per_share_on_proxy
+                    .checked_mul(user_info.amount)?
+                    .checked_sub(proxy_reward_debt(&pool, &user_info))?,
+            );
+        }
+    }
+
+    let total_virtual_amount = pool.total_virtual_amount.unwrap_or(lp_supply);
+
+    let mut acc_per_share = pool.accumulated_rewards_per_share;
+    if is_reward_due(&env, &pool, &cfg) && !total_virtual_amount.is_zero() {
+        let token_rewards = calculate_rewards(&env, &pool, &cfg)?;
+        let share = Decimal::from_ratio(token_rewards, total_virtual_amount);
+        acc_per_share = pool.accumulated_rewards_per_share.checked_add(share)?;
+    }
+
+    let pending = acc_per_share
+        .checked_mul(user_info.virtual_amount())?
+        .checked_sub(user_info.reward_debt)?;
+
+    let mut pool = pool;
+    update_reward_streams(&env, &mut pool, lp_supply)?;
+
+    let pending_on_streams = pool
+        .reward_streams
+        .iter()
+        .map(|stream| {
+            Ok(Asset {
+                info: stream.info.clone(),
+                amount: stream
+                    .accumulated_rewards_per_share
+                    .checked_mul(user_info.amount)?
+                    .checked_sub(stream_reward_debt(&user_info, &stream.info))?,
+            })
+        })
+        .collect::<StdResult<Vec<Asset>>>()?;
+
+    Ok(PendingTokenResponse {
+        pending,
+        pending_on_proxy,
+        pending_on_streams,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the generator
+/// configs in a [`ConfigResponse`] object .
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        allowed_reward_proxies: config.allowed_reward_proxies,
+        astro_token: config.astro_token,
+        owner: config.owner,
+        start_block: config.start_block,
+        tokens_per_block: config.tokens_per_block,
+        total_alloc_point: config.total_alloc_point,
+        vesting_contract: config.vesting_contract,
+        factory: config.factory,
+        tokens_per_second: config.tokens_per_second,
+        start_time: config.start_time,
+        xastro_token: config.xastro_token,
+        gauge_epoch_length: config.gauge_epoch_length,
+        maker: config.maker,
+        incentive_fee: config.incentive_fee,
+        price_sources: config.price_sources,
+        reward_stream_assets: config.reward_stream_assets,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the reward of pool
+/// in a [`RewardInfoResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+fn query_reward_info(deps: Deps, lp_token: Addr) -> Result<RewardInfoResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+
+    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    let proxy_reward_token = match pool.reward_proxy {
+        Some(proxy) => {
+            let res: Addr = deps
+                .querier
+                .query_wasm_smart(&proxy, &ProxyQueryMsg::RewardInfo {})?;
+            Some(res)
+        }
+        None => None,
+    };
+
+    Ok(RewardInfoResponse {
+        base_reward_token: config.astro_token,
+        proxy_reward_token,
+        deactivated: pool.deactivated,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the orphan proxy rewards.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+fn query_orphan_proxy_rewards(deps: Deps, lp_token: Addr) -> Result<Uint128, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+
+    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    if pool.reward_proxy.is_none() {
+        return Err(ContractError::PoolDoesNotHaveAdditionalRewards {});
+    }
+
+    Ok(pool.orphan_proxy_rewards)
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the emission schedule and the current
+/// emission rate in a [`EmissionScheduleResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+fn query_emission_schedule(
+    deps: Deps,
+    env: Env,
+) -> Result<EmissionScheduleResponse, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let now = env.block.time.seconds();
+
+    Ok(EmissionScheduleResponse {
+        scheduled_emissions: scheduled_emissions(&cfg.emission_schedule, now, u64::MAX)?,
+        tokens_per_second: current_tokens_per_second(&cfg, now),
+        periods: cfg.emission_schedule,
+    })
+}
+
+/// ## Description
+/// Returns the current emission rate if emissions are time-based: the rate of the current
+/// emission period if the emission schedule is set, otherwise `tokens_per_second`.
+/// ## Params
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **now** is the object of type [`u64`]. The current time.
+fn current_tokens_per_second(cfg: &Config, now: u64) -> Option<Uint128> {
+    if cfg.emission_schedule.is_empty() {
+        cfg.tokens_per_second
+    } else {
+        Some(
+            cfg.emission_schedule
+                .iter()
+                .find(|period| period.start_time <= now && now < period.end_time)
+                .map(|period| period.tokens_per_second)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the APR of the ASTRO and proxy rewards
+/// and the value of the LP tokens staked in the pool in a [`PoolAprResponse`] object. The LP token
+/// is valued by the assets of its pair, which is the minter of the LP token. The proxy APR is
+/// estimated from the rewards accrued on the proxy since the last update of the pool.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+fn query_pool_apr(deps: Deps, env: Env, lp_token: Addr) -> Result<PoolAprResponse, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+    let now = env.block.time.seconds();
+
+    let pair = deps
+        .querier
+        .query_wasm_smart::<Option<MinterResponse>>(&lp_token, &Cw20QueryMsg::Minter {})?
+        .ok_or_else(|| ContractError::PairNotFound(lp_token.to_string()))?
+        .minter;
+    let pair_pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(&pair, &PairQueryMsg::Pool {})?;
+
+    let mut pool_value = Uint128::zero();
+    for asset in pair_pool.assets.iter() {
+        pool_value = pool_value.checked_add(asset_value(deps, &cfg, asset)?)?;
+    }
+
+    let staked = query_lp_supply(deps, &env, &lp_token, &pool, None)?;
+    let tvl = if pair_pool.total_share.is_zero() {
+        Uint128::zero()
+    } else {
+        pool_value.multiply_ratio(staked, pair_pool.total_share)
+    };
+
+    let emissions_per_year = match current_tokens_per_second(&cfg, now) {
+        Some(tokens_per_second) => tokens_per_second.checked_mul(SECONDS_PER_YEAR.into())?,
+        None => cfg.tokens_per_block.checked_mul(BLOCKS_PER_YEAR.into())?,
+    };
+    let astro_per_year = if cfg.total_alloc_point.is_zero() {
+        Uint128::zero()
+    } else {
+        emissions_per_year.multiply_ratio(pool.alloc_point.u64(), cfg.total_alloc_point.u64())
+    };
+
+    let astro_value = asset_value(
+        deps,
+        &cfg,
+        &Asset {
+            info: AssetInfo::Token {
+                contract_addr: cfg.astro_token.clone(),
+            },
+            amount: astro_per_year,
+        },
+    )?;
+
+    let proxy_apr = match &pool.reward_proxy {
+        Some(reward_proxy) if !pool.reward_proxy_disabled => {
+            let reward_token: Addr = deps
+                .querier
+                .query_wasm_smart(reward_proxy, &ProxyQueryMsg::RewardInfo {})?;
+            let pending: Uint128 = deps
+                .querier
+                .query_wasm_smart(reward_proxy, &ProxyQueryMsg::PendingToken {})?;
+
+            let elapsed = now.saturating_sub(pool.last_reward_time);
+            let rewards_per_year = if elapsed == 0 {
+                Uint128::zero()
+            } else {
+                pending.multiply_ratio(SECONDS_PER_YEAR, elapsed)
+            };
+
+            let proxy_value = asset_value(
+                deps,
+                &cfg,
+                &Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: reward_token,
+                    },
+                    amount: rewards_per_year,
+                },
+            )?;
+            Some(apr(proxy_value, tvl))
+        }
+        _ => None,
+    };
+
+    Ok(PoolAprResponse {
+        tvl,
+        astro_per_year,
+        reward_apr: apr(astro_value, tvl),
+        proxy_apr,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the deposit of the user in the pool
+/// at the beginning of the block with the given height.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **user** is the object of type [`Addr`].
+///
+/// * **height** is the object of type [`u64`].
+fn query_deposit_at(
+    deps: Deps,
+    lp_token: Addr,
+    user: Addr,
+    height: u64,
+) -> Result<Uint128, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+    assert_snapshot_available(deps, height)?;
+
+    Ok(USER_DEPOSITS
+        .may_load_at_height(deps.storage, (&lp_token, &user), height)?
+        .unwrap_or_default())
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the total deposit of the pool
+/// at the beginning of the block with the given height.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **height** is the object of type [`u64`].
+fn query_total_deposit_at(
+    deps: Deps,
+    lp_token: Addr,
+    height: u64,
+) -> Result<Uint128, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    assert_snapshot_available(deps, height)?;
+
+    Ok(TOTAL_DEPOSITS
+        .may_load_at_height(deps.storage, &lp_token, height)?
+        .unwrap_or_default())
+}
+
+/// ## Description
+/// Returns an [`ContractError`] if the deposits were not tracked at the given height.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **height** is the object of type [`u64`].
+fn assert_snapshot_available(deps: Deps, height: u64) -> Result<(), ContractError> {
+    let start = SNAPSHOTS_START
+        .may_load(deps.storage)?
+        .ok_or(ContractError::SnapshotsNotSeeded {})?;
+    if height < start {
+        return Err(ContractError::SnapshotUnavailable(start));
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the value of the asset in the quote asset
+/// according to its price source.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **asset** is the object of type [`Asset`].
+fn asset_value(deps: Deps, cfg: &Config, asset: &Asset) -> Result<Uint128, ContractError> {
+    let (_, source) = cfg
+        .price_sources
+        .iter()
+        .find(|(info, _)| info.equal(&asset.info))
+        .ok_or_else(|| ContractError::PriceSourceNotFound(asset.info.to_string()))?;
+
+    Ok(match source {
+        PriceSource::Fixed { price } => asset.amount * *price,
+        PriceSource::Oracle { contract } => deps.querier.query_wasm_smart(
+            contract,
+            &OracleQueryMsg::Consult {
+                token: asset.info.clone(),
+                amount: asset.amount,
+            },
+        )?,
+    })
+}
+
+/// ## Description
+/// Returns the ratio of the yearly rewards value to the staked value, zero if nothing is staked.
+/// ## Params
+/// * **value_per_year** is the object of type [`Uint128`].
+///
+/// * **tvl** is the object of type [`Uint128`].
+fn apr(value_per_year: Uint128, tvl: Uint128) -> Decimal {
+    if tvl.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(value_per_year, tvl)
+    }
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the deposits and pending rewards
+/// of the user in the pools in a vector of [`UserPositionResponse`] objects.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **user** is the object of type [`Addr`].
+///
+/// * **start_after** is an [`Option`] field object of type [`Addr`].
+///
+/// * **limit** is an [`Option`] field object of type [`u32`].
+fn query_user_positions(
+    deps: Deps,
+    env: Env,
+    user: Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Result<Vec<UserPositionResponse>, ContractError> {
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+    let start_after = start_after
+        .map(|v| addr_validate_to_lower(deps.api, v.as_str()))
+        .transpose()?;
+
+    read_user_positions(deps.storage, &user, start_after, limit)?
+        .into_iter()
+        .map(|(lp_token, user_info)| {
+            let pending = pending_token(deps, env.clone(), lp_token.clone(), user.clone())?;
+
+            Ok(UserPositionResponse {
+                lp_token,
+                amount: user_info.amount,
+                pending: pending.pending,
+                pending_on_proxy: pending.pending_on_proxy,
+                pending_on_streams: pending.pending_on_streams,
+            })
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns information about the pools
+/// in a vector of [`PoolInfoResponse`] objects.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **start_after** is an [`Option`] field object of type [`Addr`].
+///
+/// * **limit** is an [`Option`] field object of type [`u32`].
+fn query_pools(
+    deps: Deps,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Result<Vec<PoolInfoResponse>, ContractError> {
+    let start_after = start_after
+        .map(|v| addr_validate_to_lower(deps.api, v.as_str()))
+        .transpose()?;
+
+    Ok(read_pools(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|(lp_token, pool)| PoolInfoResponse {
+            lp_token,
+            alloc_point: pool.alloc_point,
+            last_reward_block: pool.last_reward_block,
+            last_reward_time: pool.last_reward_time,
+            accumulated_rewards_per_share: pool.accumulated_rewards_per_share,
+            reward_proxy: pool.reward_proxy,
+            reward_proxy_disabled: pool.reward_proxy_disabled,
+            accumulated_proxy_rewards_per_share: pool.accumulated_proxy_rewards_per_share,
+            orphan_proxy_rewards: pool.orphan_proxy_rewards,
+            reward_streams: pool.reward_streams,
+            total_virtual_amount: pool.total_virtual_amount,
+            lock_tiers: pool.lock_tiers,
+            deactivated: pool.deactivated,
+        })
+        .collect())
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the locked deposits of the user
+/// including the expired ones which are removed on the next action of the user.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **lp_token** is the object of type [`Addr`].
+///
+/// * **user** is the object of type [`Addr`].
+fn query_user_locks(
+    deps: Deps,
+    lp_token: Addr,
+    user: Addr,
+) -> Result<Vec<LockedPosition>, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+
+    let user_info = USER_INFO
+        .load(deps.storage, (&lp_token, &user))
+        .unwrap_or_default();
+
+    Ok(user_info.locks)
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the proxy rewards of the user
+/// which were not paid because of a failure of the proxy.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **reward_proxy** is the object of type [`Addr`].
+///
+/// * **user** is the object of type [`Addr`].
+fn query_unpaid_proxy_rewards(
+    deps: Deps,
+    reward_proxy: Addr,
+    user: Addr,
+) -> Result<Uint128, ContractError> {
+    let reward_proxy = addr_validate_to_lower(deps.api, reward_proxy.as_str())?;
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
 
-    let recipient = addr_validate_to_lower(deps.api, &recipient)?;
+    let mut amount = UNPAID_PROXY_REWARDS
+        .may_load(deps.storage, (&reward_proxy, &user))?
+        .unwrap_or_default();
 
-    let mut pool = POOL_INFO.load(deps.storage, &lp_token)?;
-    let proxy = match &pool.reward_proxy {
-        Some(proxy) => proxy.clone(),
-        None => return Err(ContractError::PoolDoesNotHaveAdditionalRewards {}),
-    };
+    // The rewards on the retired proxies are saved on the next action of the user in the pool
+    for (lp_token, pool) in get_pools(deps.storage) {
+        if !pool
+            .retired_proxies
+            .iter()
+            .any(|retired| retired.reward_proxy == reward_proxy)
+        {
+            continue;
+        }
 
-    let amount = pool.orphan_proxy_rewards;
-    if amount.is_zero() {
-        return Err(ContractError::OrphanRewardsTooSmall {});
+        if let Some(user_info) = USER_INFO.may_load(deps.storage, (&lp_token, &user))? {
+            for (proxy, rewards) in retired_proxy_rewards(&pool, &user_info)? {
+                if proxy == reward_proxy {
+                    amount = amount.checked_add(rewards)?;
+                }
+            }
+        }
     }
 
-    pool.orphan_proxy_rewards = Uint128::zero();
-    POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+    Ok(amount)
+}
 
-    Ok(Response::new()
-        .add_message(WasmMsg::Execute {
-            contract_addr: proxy.to_string(),
-            funds: vec![],
-            msg: to_binary(&ProxyExecuteMsg::SendRewards {
-                account: recipient.clone(),
-                amount,
-            })?,
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the operators which are allowed to claim
+/// the rewards of the user in a vector of [`ClaimOperatorResponse`] objects.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **user** is the object of type [`Addr`].
+fn query_claim_operators(
+    deps: Deps,
+    user: Addr,
+) -> Result<Vec<ClaimOperatorResponse>, ContractError> {
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+
+    Ok(read_claim_operators(deps.storage, &user)?
+        .into_iter()
+        .map(|(operator, recipient)| ClaimOperatorResponse {
+            operator,
+            recipient,
         })
-        .add_attribute("action", "send_orphan_rewards")
-        .add_attribute("recipient", recipient.to_string())
-        .add_attribute("lp_token", lp_token)
-        .add_attribute("amount", amount))
+        .collect())
 }
 
 /// ## Description
-/// Available the query messages of the contract.
+/// Returns the current gauge voting epoch, or a [`ContractError`] if gauge voting is disabled.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
 ///
-/// * **_env** is the object of type [`Env`].
-///
-/// * **msg** is the object of type [`QueryMsg`].
-///
-/// ## Queries
-/// * **QueryMsg::PoolLength {}** Returns information about the pool length in the
-/// [`PoolLengthResponse`] object.
-///
-/// * **QueryMsg::Deposit { lp_token, user }** Returns information about the user deposit amount.
-///
-/// * **QueryMsg::PendingToken { lp_token, user }** Returns information in
-/// the [`PendingTokenResponse`] object.
-///
-/// * **QueryMsg::Config {}** Returns information about the generator configs in
-/// the [`ConfigResponse`] object.
-///
-/// * **QueryMsg::RewardInfo { lp_token }** Returns information about the reward of pool
-/// in a [`RewardInfoResponse`] object.
-///
-/// * **QueryMsg::OrphanProxyRewards { lp_token }** Returns information about the orphan proxy rewards.
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
-    match msg {
-        QueryMsg::PoolLength {} => Ok(to_binary(&pool_length(deps)?)?),
-        QueryMsg::Deposit { lp_token, user } => {
-            Ok(to_binary(&query_deposit(deps, lp_token, user)?)?)
-        }
-        QueryMsg::PendingToken { lp_token, user } => {
-            Ok(to_binary(&pending_token(deps, env, lp_token, user)?)?)
-        }
-        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
-        QueryMsg::RewardInfo { lp_token } => Ok(to_binary(&query_reward_info(deps, lp_token)?)?),
-        QueryMsg::OrphanProxyRewards { lp_token } => {
-            Ok(to_binary(&query_orphan_proxy_rewards(deps, lp_token)?)?)
-        }
-    }
+/// * **env** is the object of type [`Env`].
+fn current_epoch(deps: Deps, env: &Env) -> Result<u64, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+    let epoch_length = cfg
+        .gauge_epoch_length
+        .ok_or(ContractError::GaugeVotingDisabled {})?;
+
+    Ok(env.block.time.seconds() / epoch_length)
 }
 
 /// ## Description
-/// Returns an [`ContractError`] on failure, otherwise returns information about the pool length
-/// in a [`PoolLengthResponse`] object.
+/// Returns an [`ContractError`] on failure, otherwise returns the amount of xASTRO locked
+/// by the user for gauge voting.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
-pub fn pool_length(deps: Deps) -> Result<PoolLengthResponse, ContractError> {
-    let length = POOL_INFO
-        .keys(deps.storage, None, None, cosmwasm_std::Order::Ascending)
-        .count();
-    Ok(PoolLengthResponse { length })
+///
+/// * **user** is the object of type [`Addr`].
+fn query_voting_power(deps: Deps, user: Addr) -> Result<Uint128, ContractError> {
+    let user = addr_validate_to_lower(deps.api, user.as_str())?;
+
+    Ok(VOTING_POWER
+        .may_load(deps.storage, &user)?
+        .unwrap_or_default())
 }
 
 /// ## Description
-/// Returns an [`ContractError`] on failure, otherwise returns information about the user deposit amount.
+/// Returns an [`ContractError`] on failure, otherwise returns the latest gauge votes of the user
+/// in a [`UserVotesResponse`] object.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
 ///
-/// * **lp_token** is the object of type [`Addr`].
-///
 /// * **user** is the object of type [`Addr`].
-pub fn query_deposit(deps: Deps, lp_token: Addr, user: Addr) -> Result<Uint128, ContractError> {
-    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+fn query_user_votes(deps: Deps, user: Addr) -> Result<UserVotesResponse, ContractError> {
     let user = addr_validate_to_lower(deps.api, user.as_str())?;
 
-    let user_info = USER_INFO
-        .load(deps.storage, (&lp_token, &user))
-        .unwrap_or_default();
-    Ok(user_info.amount)
+    let votes = USER_VOTES.load(deps.storage, &user)?;
+
+    Ok(UserVotesResponse {
+        epoch: votes.epoch,
+        weight: votes.weight,
+        votes: votes.votes,
+    })
 }
 
 /// ## Description
-/// Calculates pending token. Returns an [`ContractError`] on failure, otherwise returns
-/// information in a [`PendingTokenResponse`] object.
+/// Returns an [`ContractError`] on failure, otherwise returns the vote tally of the epoch
+/// in a [`EpochVotesResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **epoch** is an [`Option`] field object of type [`u64`]. The current epoch is used if not set.
+fn query_epoch_votes(
+    deps: Deps,
+    env: Env,
+    epoch: Option<u64>,
+) -> Result<EpochVotesResponse, ContractError> {
+    let epoch = match epoch {
+        Some(epoch) => epoch,
+        None => current_epoch(deps, &env)?,
+    };
+
+    Ok(EpochVotesResponse {
+        epoch,
+        votes: read_epoch_votes(deps.storage, epoch)?,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the allocation points according to
+/// the votes of the current epoch in a [`ProjectedAllocationsResponse`] object.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+fn query_projected_allocations(
+    deps: Deps,
+    env: Env,
+) -> Result<ProjectedAllocationsResponse, ContractError> {
+    let epoch = current_epoch(deps, &env)?;
+
+    Ok(ProjectedAllocationsResponse {
+        epoch,
+        allocations: epoch_allocations(deps, epoch)?,
+    })
+}
+
+/// ## Description
+/// Returns an [`ContractError`] on failure, otherwise returns the boosted amount of the user's deposit
+/// in a [`UserBoostResponse`] object.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
 ///
@@ -1188,13 +4318,12 @@ pub fn query_deposit(deps: Deps, lp_token: Addr, user: Addr) -> Result<Uint128,
 /// * **lp_token** is the object of type [`Addr`].
 ///
 /// * **user** is the object of type [`Addr`].
-// View function to see pending ASTRO on frontend.
-pub fn pending_token(
+fn query_user_boost(
     deps: Deps,
     env: Env,
     lp_token: Addr,
     user: Addr,
-) -> Result<PendingTokenResponse, ContractError> {
+) -> Result<UserBoostResponse, ContractError> {
     let cfg = CONFIG.load(deps.storage)?;
 
     let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
@@ -1205,126 +4334,171 @@ pub fn pending_token(
         .load(deps.storage, (&lp_token, &user))
         .unwrap_or_default();
 
-    let mut pending_on_proxy = None;
-
-    let lp_supply: Uint128;
-
-    match &pool.reward_proxy {
-        Some(proxy) => {
-            lp_supply = deps
-                .querier
-                .query_wasm_smart(proxy, &ProxyQueryMsg::Deposit {})?;
-
-            if !lp_supply.is_zero() {
-                let res: Option<Uint128> = deps
-                    .querier
-                    .query_wasm_smart(proxy, &ProxyQueryMsg::PendingToken {})?;
-
-                let mut acc_per_share_on_proxy = pool.accumulated_proxy_rewards_per_share;
-                if let Some(token_rewards) = res {
-                    let share = Decimal::from_ratio(token_rewards, lp_supply);
-                    acc_per_share_on_proxy = pool
-                        .accumulated_proxy_rewards_per_share
-                        .checked_add(share)?;
-                }
-
-                pending_on_proxy = Some(
-                    acc_per_share_on_proxy
-                        .checked_mul(user_info.amount)?
-                        .checked_sub(user_info.reward_debt_proxy)?,
-                );
-            }
-        }
-        None => {
-            lp_supply = query_token_balance(&deps.querier, lp_token, env.contract.address.clone())?;
-        }
-    }
-
-    let mut acc_per_share = pool.accumulated_rewards_per_share;
-    if env.block.height > pool.last_reward_block.u64() && !lp_supply.is_zero() {
-        let token_rewards = calculate_rewards(&env, &pool, &cfg)?;
-        let share = Decimal::from_ratio(token_rewards, lp_supply);
-        acc_per_share = pool.accumulated_rewards_per_share.checked_add(share)?;
-    }
+    let virtual_amount = user_info.virtual_amount();
+    let boost_factor = if user_info.amount.is_zero() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(
+            virtual_amount.checked_mul(Uint128::from(100u128))?,
+            user_info
+                .amount
+                .checked_mul(Uint128::from(BOOST_BASE_PERCENT))?,
+        )
+    };
 
-    let pending = acc_per_share
-        .checked_mul(user_info.amount)?
-        .checked_sub(user_info.reward_debt)?;
+    let lp_supply = query_lp_supply(deps, &env, &lp_token, &pool, None)?;
 
-    Ok(PendingTokenResponse {
-        pending,
-        pending_on_proxy,
+    Ok(UserBoostResponse {
+        amount: user_info.amount,
+        virtual_amount,
+        boost_factor,
+        checkpoint_virtual_amount: calculate_virtual_amount(
+            deps,
+            &cfg,
+            &user,
+            &user_info,
+            user_info.amount,
+            lp_supply,
+        )?,
     })
 }
 
 /// ## Description
-/// Returns an [`ContractError`] on failure, otherwise returns information about the generator
-/// configs in a [`ConfigResponse`] object .
+/// Returns an [`ContractError`] on failure, otherwise returns the external reward streams of the pool.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
-fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+///
+/// * **lp_token** is the object of type [`Addr`].
+fn query_reward_streams(deps: Deps, lp_token: Addr) -> Result<Vec<RewardStream>, ContractError> {
+    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
 
-    Ok(ConfigResponse {
-        allowed_reward_proxies: config.allowed_reward_proxies,
-        astro_token: config.astro_token,
-        owner: config.owner,
-        start_block: config.start_block,
-        tokens_per_block: config.tokens_per_block,
-        total_alloc_point: config.total_alloc_point,
-        vesting_contract: config.vesting_contract,
-    })
+    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
+
+    Ok(pool.reward_streams)
 }
 
 /// ## Description
-/// Returns an [`ContractError`] on failure, otherwise returns information about the reward of pool
-/// in a [`RewardInfoResponse`] object.
+/// Returns an [`ContractError`] on failure, otherwise returns the incentive schedules of the pool
+/// which haven't ended yet.
 /// ## Params
 /// * **deps** is the object of type [`Deps`].
 ///
+/// * **env** is the object of type [`Env`].
+///
 /// * **lp_token** is the object of type [`Addr`].
-fn query_reward_info(deps: Deps, lp_token: Addr) -> Result<RewardInfoResponse, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
-
+fn query_incentives(
+    deps: Deps,
+    env: Env,
+    lp_token: Addr,
+) -> Result<Vec<IncentiveSchedule>, ContractError> {
     let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
 
     let pool = POOL_INFO.load(deps.storage, &lp_token)?;
 
-    let proxy_reward_token = match pool.reward_proxy {
-        Some(proxy) => {
-            let res: Addr = deps
-                .querier
-                .query_wasm_smart(&proxy, &ProxyQueryMsg::RewardInfo {})?;
-            Some(res)
-        }
-        None => None,
+    Ok(pool
+        .incentives
+        .into_iter()
+        .filter(|incentive| incentive.end_time > env.block.time.seconds())
+        .collect())
+}
+
+/// ## Description
+/// Returns the boosted amount of the user's deposit used for ASTRO rewards:
+/// `min(0.4 * amount + 0.6 * lp_supply * user_xastro / total_xastro, amount) + lock_bonus`.
+/// The deposit is not boosted by xASTRO if the xASTRO token is not set.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **user** is the object of type [`Addr`].
+///
+/// * **user_info** is the object of type [`UserInfo`]. Its locks add the lock-up bonus.
+///
+/// * **amount** is the object of type [`Uint128`]. The deposit of the user.
+///
+/// * **lp_supply** is the object of type [`Uint128`]. The amount of LP tokens staked in the pool.
+fn calculate_virtual_amount(
+    deps: Deps,
+    cfg: &Config,
+    user: &Addr,
+    user_info: &UserInfo,
+    amount: Uint128,
+    lp_supply: Uint128,
+) -> StdResult<Uint128> {
+    let lock_bonus = user_info.lock_bonus()?;
+
+    let xastro_token = match &cfg.xastro_token {
+        Some(xastro_token) => xastro_token,
+        None => return Ok(amount.checked_add(lock_bonus)?),
     };
 
-    Ok(RewardInfoResponse {
-        base_reward_token: config.astro_token,
-        proxy_reward_token,
-    })
+    let mut virtual_amount = amount.multiply_ratio(BOOST_BASE_PERCENT, 100u128);
+
+    let total_xastro = query_supply(&deps.querier, xastro_token.clone())?;
+    if !total_xastro.is_zero() {
+        let user_xastro = query_token_balance(&deps.querier, xastro_token.clone(), user.clone())?
+            .checked_add(
+            VOTING_POWER
+                .may_load(deps.storage, user)?
+                .unwrap_or_default(),
+        )?;
+        virtual_amount = virtual_amount.checked_add(
+            lp_supply
+                .multiply_ratio(user_xastro, total_xastro)
+                .multiply_ratio(100 - BOOST_BASE_PERCENT, 100u128),
+        )?;
+    }
+
+    Ok(virtual_amount.min(amount).checked_add(lock_bonus)?)
 }
 
 /// ## Description
-/// Returns an [`ContractError`] on failure, otherwise returns information about the orphan proxy rewards.
+/// Returns `true` if the pool has not been updated since the current block, or since the current
+/// time if emissions are time-based.
 /// ## Params
-/// * **deps** is the object of type [`Deps`].
+/// * **env** is the object of type [`Env`].
 ///
-/// * **lp_token** is the object of type [`Addr`].
-fn query_orphan_proxy_rewards(deps: Deps, lp_token: Addr) -> Result<Uint128, ContractError> {
-    let lp_token = addr_validate_to_lower(deps.api, lp_token.as_str())?;
+/// * **pool** is the object of type [`PoolInfo`].
+///
+/// * **cfg** is the object of type [`Config`].
+fn is_reward_due(env: &Env, pool: &PoolInfo, cfg: &Config) -> bool {
+    match cfg.tokens_per_second {
+        Some(_) => env.block.time.seconds() > pool.last_reward_time,
+        None => env.block.height > pool.last_reward_block.u64(),
+    }
+}
 
-    let pool = POOL_INFO.load(deps.storage, &lp_token)?;
-    if pool.reward_proxy.is_none() {
-        return Err(ContractError::PoolDoesNotHaveAdditionalRewards {});
+/// ## Description
+/// Returns the amount of tokens emitted according to the emission schedule between `from` and `to`.
+/// ## Params
+/// * **periods** is a slice that contains objects of type [`EmissionPeriod`].
+///
+/// * **from** is the object of type [`u64`].
+///
+/// * **to** is the object of type [`u64`].
+pub fn scheduled_emissions(periods: &[EmissionPeriod], from: u64, to: u64) -> StdResult<Uint128> {
+    let mut emissions = Uint128::zero();
+
+    for period in periods {
+        let start = period.start_time.max(from);
+        let end = period.end_time.min(to);
+        if end > start {
+            emissions = emissions.checked_add(
+                period
+                    .tokens_per_second
+                    .checked_mul(Uint128::from(end - start))?,
+            )?;
+        }
     }
 
-    Ok(pool.orphan_proxy_rewards)
+    Ok(emissions)
 }
 
 /// ## Description
-/// Calculates rewards in the pool.
+/// Calculates rewards in the pool. Emissions are counted per second since the last reward time
+/// if they are time-based, following the emission schedule if it is set, otherwise per block
+/// since the last reward block.
 /// ## Params
 /// * **env** is the object of type [`Env`].
 ///
@@ -1332,12 +4506,23 @@ fn query_orphan_proxy_rewards(deps: Deps, lp_token: Addr) -> Result<Uint128, Con
 ///
 /// * **cfg** is the object of type [`Config`].
 pub fn calculate_rewards(env: &Env, pool: &PoolInfo, cfg: &Config) -> StdResult<Uint128> {
-    let n_blocks = Uint128::from(env.block.height).checked_sub(pool.last_reward_block.into())?;
+    let emitted = match cfg.tokens_per_second {
+        Some(_) if !cfg.emission_schedule.is_empty() => scheduled_emissions(
+            &cfg.emission_schedule,
+            pool.last_reward_time,
+            env.block.time.seconds(),
+        )?,
+        Some(tokens_per_second) => Uint128::from(env.block.time.seconds())
+            .checked_sub(pool.last_reward_time.into())?
+            .checked_mul(tokens_per_second)?,
+        None => Uint128::from(env.block.height)
+            .checked_sub(pool.last_reward_block.into())?
+            .checked_mul(cfg.tokens_per_block)?,
+    };
 
     let r;
     if !cfg.total_alloc_point.is_zero() {
-        r = n_blocks
-            .checked_mul(cfg.tokens_per_block)?
+        r = emitted
             .checked_mul(Uint128::from(pool.alloc_point.u64()))?
             .checked_div(Uint128::from(cfg.total_alloc_point.u64()))?;
     } else {
@@ -1348,14 +4533,46 @@ pub fn calculate_rewards(env: &Env, pool: &PoolInfo, cfg: &Config) -> StdResult<
 }
 
 /// ## Description
-/// Used for migration of contract. Returns the default object of type [`Response`].
+/// Used for migration of contract. The pools which are not deactivated are added to the [`ACTIVE_POOLS`]
+/// first, so they are updated before the emissions change. If `tokens_per_second` is specified, switches
+/// block-based emissions to time-based ones: all active pools are updated up to the current block and start
+/// accumulating rewards per second from the current time. If the deposit snapshots were introduced by the migration, the current
+/// deposits must be seeded with [`ExecuteMsg::SeedSnapshots`] before the snapshots are available.
+/// Returns the default object of type [`Response`].
 /// ## Params
-/// * **_deps** is the object of type [`DepsMut`].
+/// * **deps** is the object of type [`DepsMut`].
 ///
-/// * **_env** is the object of type [`Env`].
+/// * **env** is the object of type [`Env`].
 ///
-/// * **_msg** is the object of type [`MigrateMsg`].
+/// * **msg** is the object of type [`MigrateMsg`].
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(mut deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    for (lp_token, pool) in get_pools(deps.storage) {
+        if !pool.deactivated {
+            ACTIVE_POOLS.save(deps.storage, &lp_token, &Empty {})?;
+        }
+    }
+
+    if let Some(tokens_per_second) = msg.tokens_per_second {
+        let mut cfg = CONFIG.load(deps.storage)?;
+        if cfg.tokens_per_second.is_some() {
+            return Err(ContractError::TimeBasedEmissions {});
+        }
+
+        mass_update_pools(deps.branch(), env.clone())?;
+
+        let now = env.block.time.seconds();
+        for (lp_token, mut pool) in get_pools(deps.storage) {
+            pool.last_reward_time = now;
+            POOL_INFO.save(deps.storage, &lp_token, &pool)?;
+        }
+
+        cfg.tokens_per_second = Some(tokens_per_second);
+        cfg.start_time = now;
+        CONFIG.save(deps.storage, &cfg)?;
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::{OverflowError, StdError};
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use thiserror::Error;
 
 /// ## Description
@@ -25,6 +25,113 @@ pub enum ContractError {
 
     #[error("Insufficient amount of orphan rewards!")]
     OrphanRewardsTooSmall {},
+
+    #[error("Reward stream must be funded with a non-zero amount!")]
+    RewardStreamAmountIsZero {},
+
+    #[error("Reward stream end time must be in the future and can't be decreased!")]
+    InvalidRewardStreamEndTime {},
+
+    #[error("LP tokens can't be used as reward stream assets!")]
+    InvalidRewardStreamAsset {},
+
+    #[error("Pool can't have more than {0} active reward streams!")]
+    RewardStreamLimitExceeded(usize),
+
+    #[error("The asset isn't allowed in reward streams!")]
+    RewardStreamAssetNotAllowed {},
+
+    #[error("Reward stream must be funded with at least {0} tokens!")]
+    RewardStreamAmountTooLow(Uint128),
+
+    #[error("Incentive duration must be positive and at most {0} seconds!")]
+    InvalidIncentiveDuration(u64),
+
+    #[error("Pool can't have more than {0} active incentives!")]
+    IncentiveLimitExceeded(usize),
+
+    #[error("Incentive fee can't exceed {0}%!")]
+    IncorrectIncentiveFee(u64),
+
+    #[error("Emissions are time-based!")]
+    TimeBasedEmissions {},
+
+    #[error("Emissions are block-based!")]
+    BlockBasedEmissions {},
+
+    #[error("Emission periods must be sorted, must not overlap and must end after they start!")]
+    InvalidEmissionSchedule {},
+
+    #[error("Lock-up tiers must have unique non-zero durations and multipliers of at least 1!")]
+    InvalidLockTiers {},
+
+    #[error("Pool can't have more than {0} lock-up tiers!")]
+    LockTierLimitExceeded(usize),
+
+    #[error("Pool doesn't have a lock-up tier with the duration {0}!")]
+    LockTierNotFound(u64),
+
+    #[error("User can't have more than {0} active locks in the pool!")]
+    LockLimitExceeded(usize),
+
+    #[error("Amount {locked} is locked and can't be withdrawn yet!")]
+    AmountIsLocked { locked: Uint128 },
+
+    #[error("Reward proxy is not disabled!")]
+    RewardProxyIsNotDisabled {},
+
+    #[error("Reward proxy of the pool is disabled!")]
+    RewardProxyIsDisabled {},
+
+    #[error("No unpaid proxy rewards!")]
+    NoUnpaidProxyRewards {},
+
+    #[error("The pool already uses the reward proxy!")]
+    SameRewardProxy {},
+
+    #[error("Can't transfer the position to the same account!")]
+    TransferToSelf {},
+
+    #[error("Price source for {0} is not set!")]
+    PriceSourceNotFound(String),
+
+    #[error("LP token {0} is not minted by a pair!")]
+    PairNotFound(String),
+
+    #[error("Deposits are tracked since the block {0}!")]
+    SnapshotUnavailable(u64),
+
+    #[error("Deposit snapshots are not seeded yet!")]
+    SnapshotsNotSeeded {},
+
+    #[error("Deposit snapshots are already seeded!")]
+    SnapshotsAlreadySeeded {},
+
+    #[error("Pool is deactivated!")]
+    PoolIsDeactivated {},
+
+    #[error("Gauge voting is disabled!")]
+    GaugeVotingDisabled {},
+
+    #[error("Votes must be for different existing pools and sum to at most 10000 basis points!")]
+    InvalidVotes {},
+
+    #[error("No voting power!")]
+    NoVotingPower {},
+
+    #[error("Voting power can't be unlocked in the epoch of the votes!")]
+    VotingPowerIsLocked {},
+
+    #[error("Allocations are already tuned for the epoch {0}!")]
+    AllocationsAlreadyTuned(u64),
+
+    #[error(
+        "Scheduled emissions {emissions} exceed the remaining vesting allocation {allocation}!"
+    )]
+    EmissionsExceedAllocation {
+        emissions: Uint128,
+        allocation: Uint128,
+    },
 }
 
 impl From<OverflowError> for ContractError {
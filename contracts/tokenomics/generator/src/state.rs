@@ -1,7 +1,11 @@
+use astroport::asset::AssetInfo;
 use astroport::common::OwnershipProposal;
+use astroport::generator::{
+    EmissionPeriod, IncentiveSchedule, LockTier, LockedPosition, PriceSource, RewardStream,
+};
 use astroport::DecimalCheckedOps;
-use cosmwasm_std::{Addr, Decimal, StdResult, Storage, Uint128, Uint64};
-use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal, Empty, Order, StdError, StdResult, Storage, Uint128, Uint64};
+use cw_storage_plus::{Bound, Item, Map, SnapshotMap, Strategy, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -15,6 +19,48 @@ pub struct UserInfo {
     pub reward_debt: Uint128,
     /// Proxy reward amount user already received or is not eligible for, used for proper reward calculation
     pub reward_debt_proxy: Uint128,
+    /// External stream reward amounts user already received or is not eligible for
+    #[serde(default)]
+    pub reward_debt_streams: Vec<(AssetInfo, Uint128)>,
+    /// The boosted amount used for ASTRO rewards, equals to the amount if not set
+    #[serde(default)]
+    pub virtual_amount: Option<Uint128>,
+    /// The locked deposits which are included in the amount
+    #[serde(default)]
+    pub locks: Vec<LockedPosition>,
+    /// The number of the retired reward proxies of the pool which rewards are settled for the user
+    #[serde(default)]
+    pub settled_proxies: usize,
+}
+
+impl UserInfo {
+    /// ## Description
+    /// Returns the boosted amount used for ASTRO rewards.
+    pub fn virtual_amount(&self) -> Uint128 {
+        self.virtual_amount.unwrap_or(self.amount)
+    }
+
+    /// ## Description
+    /// Returns the amount which can't be withdrawn at the given time.
+    pub fn locked_amount(&self, time: u64) -> StdResult<Uint128> {
+        self.locks
+            .iter()
+            .filter(|lock| lock.unlock_time > time)
+            .try_fold(Uint128::zero(), |acc, lock| acc.checked_add(lock.amount))
+            .map_err(|e| e.into())
+    }
+
+    /// ## Description
+    /// Returns the extra weight of the locked deposits added to the boosted amount.
+    pub fn lock_bonus(&self) -> StdResult<Uint128> {
+        self.locks.iter().try_fold(Uint128::zero(), |acc, lock| {
+            Ok(acc.checked_add(
+                lock.multiplier
+                    .checked_mul(lock.amount)?
+                    .checked_sub(lock.amount)?,
+            )?)
+        })
+    }
 }
 
 /// ## Description
@@ -26,15 +72,90 @@ pub struct PoolInfo {
     /// Accumulated amount of reward per share unit. Used for reward calculations
     pub last_reward_block: Uint64,
     pub accumulated_rewards_per_share: Decimal,
+    /// the time of the last reward update when emissions are time-based
+    #[serde(default)]
+    pub last_reward_time: u64,
+    /// the sum of boosted amounts of all users. Initialized with the staked amount on the first
+    /// update for pools added before boosting
+    #[serde(default)]
+    pub total_virtual_amount: Option<Uint128>,
     /// the reward proxy contract
     pub reward_proxy: Option<Addr>,
+    /// the reward proxy is disabled after a failed call, its rewards are not updated until it is reattached
+    #[serde(default)]
+    pub reward_proxy_disabled: bool,
+    /// the amount of LP tokens deposited to the reward proxy, used as the staked amount
+    /// while the proxy is disabled
+    #[serde(default)]
+    pub proxy_deposit: Option<Uint128>,
     pub accumulated_proxy_rewards_per_share: Decimal,
     /// for calculation of new proxy rewards
     pub proxy_reward_balance_before_update: Uint128,
     /// the orphan proxy rewards which are left by emergency withdrawals
     pub orphan_proxy_rewards: Uint128,
+    /// the external reward streams funded for the pool
+    #[serde(default)]
+    pub reward_streams: Vec<RewardStream>,
+    /// the lock-up tiers available for deposits
+    #[serde(default)]
+    pub lock_tiers: Vec<LockTier>,
+    /// the reward proxies which were replaced or detached, in the order of retirement
+    #[serde(default)]
+    pub retired_proxies: Vec<RetiredProxy>,
+    /// the active incentive schedules, their rewards are accumulated in the reward streams
+    #[serde(default)]
+    pub incentives: Vec<IncentiveSchedule>,
+    /// the pool doesn't receive ASTRO and is excluded from mass updates, users can only withdraw and claim
+    #[serde(default)]
+    pub deactivated: bool,
+}
+
+/// ## Description
+/// This structure describes a reward proxy which was replaced or detached. The rewards which are left
+/// on the proxy are paid out with the unpaid proxy rewards.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RetiredProxy {
+    /// the reward proxy contract
+    pub reward_proxy: Addr,
+    /// the accumulated amount of proxy rewards per share when the proxy was retired
+    pub accumulated_proxy_rewards_per_share: Decimal,
+    /// the orphan proxy rewards which are left on the proxy
+    pub orphan_proxy_rewards: Uint128,
 }
 
+/// ## Description
+/// The maximum number of external reward streams per pool.
+pub const MAX_REWARD_STREAMS: usize = 5;
+
+/// ## Description
+/// The maximum number of active incentive schedules per pool.
+pub const MAX_INCENTIVES: usize = 10;
+
+/// ## Description
+/// The maximum duration of an incentive schedule in seconds.
+pub const MAX_INCENTIVE_DURATION: u64 = 365 * 86400;
+
+/// ## Description
+/// The maximum incentive fee in percents.
+pub const MAX_INCENTIVE_FEE: u64 = 10;
+
+/// ## Description
+/// The number of seconds in a year used to calculate the APR of the pools.
+pub const SECONDS_PER_YEAR: u64 = 365 * 86400;
+
+/// ## Description
+/// The approximate number of blocks in a year used to calculate the APR of the pools when emissions
+/// are block-based, assuming a block every 6.5 seconds.
+pub const BLOCKS_PER_YEAR: u64 = SECONDS_PER_YEAR * 10 / 65;
+
+/// ## Description
+/// The maximum number of lock-up tiers per pool.
+pub const MAX_LOCK_TIERS: usize = 5;
+
+/// ## Description
+/// The maximum number of active locks of a user per pool.
+pub const MAX_USER_LOCKS: usize = 10;
+
 /// ## Description
 /// This structure describes the main control config of generator.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -53,6 +174,47 @@ pub struct Config {
     pub allowed_reward_proxies: Vec<Addr>,
     /// The vesting contract from which rewards are distributed
     pub vesting_contract: Addr,
+    /// The factory contract that is allowed to add and set pools along with the owner
+    pub factory: Option<Addr>,
+    /// Total amount of ASTRO rewards per second. Emissions are time-based if set
+    #[serde(default)]
+    pub tokens_per_second: Option<Uint128>,
+    /// the time when time-based ASTRO mining starts.
+    #[serde(default)]
+    pub start_time: u64,
+    /// the emission schedule that overrides `tokens_per_second` if set
+    #[serde(default)]
+    pub emission_schedule: Vec<EmissionPeriod>,
+    /// the xASTRO token whose balances boost ASTRO rewards
+    #[serde(default)]
+    pub xastro_token: Option<Addr>,
+    /// the length of gauge voting epochs in seconds. Gauge voting is enabled if set
+    #[serde(default)]
+    pub gauge_epoch_length: Option<u64>,
+    /// the maker contract which receives the incentive fee
+    #[serde(default)]
+    pub maker: Option<Addr>,
+    /// the share of incentives which is sent to the maker if it is set
+    #[serde(default)]
+    pub incentive_fee: Decimal,
+    /// the price sources of the assets used to calculate the APR of the pools
+    #[serde(default)]
+    pub price_sources: Vec<(AssetInfo, PriceSource)>,
+    /// the assets allowed in external reward streams along with the minimum funding amounts
+    #[serde(default)]
+    pub reward_stream_assets: Vec<(AssetInfo, Uint128)>,
+}
+
+/// ## Description
+/// This structure describes the gauge votes of a user.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserVotes {
+    /// the epoch of the votes
+    pub epoch: u64,
+    /// the xASTRO locked by the user at the time of voting
+    pub weight: Uint128,
+    /// the LP token contracts along with the share of the weight in basis points
+    pub votes: Vec<(Addr, u16)>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -75,6 +237,11 @@ pub enum ExecuteOnReply {
         /// the allocation point for LP token contract
         alloc_point: Uint64,
     },
+    /// Stops ASTRO emissions of the given pool and excludes it from mass updates
+    DeactivatePool {
+        /// the LP token contract
+        lp_token: Addr,
+    },
     /// Updates reward variables of the given pool to be up-to-date
     UpdatePool {
         /// the LP token contract
@@ -88,6 +255,8 @@ pub enum ExecuteOnReply {
         account: Addr,
         /// the deposit amount
         amount: Uint128,
+        /// the lock-up duration of the deposit
+        lock_duration: Option<u64>,
     },
     /// Withdraw LP tokens from Generator
     Withdraw {
@@ -103,6 +272,84 @@ pub enum ExecuteOnReply {
         /// A new count of tokens per block
         amount: Uint128,
     },
+    /// Sets a new count of tokens per second.
+    SetTokensPerSecond {
+        /// A new count of tokens per second
+        amount: Uint128,
+    },
+    /// Sends pending rewards of the given pools
+    ClaimRewards {
+        /// the LP token contracts
+        lp_tokens: Vec<Addr>,
+        /// the depositor whose rewards are claimed
+        account: Addr,
+        /// the rewards recipient
+        recipient: Addr,
+    },
+    /// Moves a part of the deposit to the recipient
+    TransferPosition {
+        /// the LP token contract
+        lp_token: Addr,
+        /// the depositor
+        account: Addr,
+        /// the recipient of the deposit
+        recipient: Addr,
+        /// the transfer amount
+        amount: Uint128,
+    },
+    /// Sets the emission schedule.
+    SetEmissionSchedule {
+        /// the emission periods
+        periods: Vec<EmissionPeriod>,
+    },
+    /// Sets the allocation points according to the votes of the epoch.
+    TuneAllocations {
+        /// the epoch of the votes
+        epoch: u64,
+    },
+    /// Moves the LP tokens of the pool to the new reward proxy, or to the generator if not set
+    MoveToProxy {
+        /// the LP token contract
+        lp_token: Addr,
+        /// the new reward proxy contract
+        new_proxy: Option<Addr>,
+    },
+}
+
+/// ## Description
+/// This enum describes a call to a reward proxy which is dispatched as a sub-message, so that
+/// a failure of the proxy doesn't revert the user action.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum ProxyCall {
+    /// Claims the proxy rewards before the pools are updated
+    UpdateRewards {
+        /// the LP token contract of the pool
+        lp_token: Addr,
+        /// the reward proxy contract
+        reward_proxy: Addr,
+    },
+    /// Sends the proxy rewards to the user
+    SendRewards {
+        /// the LP token contract of the pool, not set for the unpaid rewards
+        lp_token: Option<Addr>,
+        /// the reward proxy contract
+        reward_proxy: Addr,
+        /// the rewards recipient
+        account: Addr,
+        /// the rewards amount
+        amount: Uint128,
+    },
+    /// Withdraws the LP tokens of the user from the proxy, the deposit is restored on failure
+    Withdraw {
+        /// the LP token contract of the pool
+        lp_token: Addr,
+        /// the reward proxy contract
+        reward_proxy: Addr,
+        /// the owner of the deposit
+        account: Addr,
+        /// the withdrawn amount
+        amount: Uint128,
+    },
 }
 
 /// ## Description
@@ -115,16 +362,191 @@ pub const CONFIG: Item<Config> = Item::new("config");
 pub const POOL_INFO: Map<&Addr, PoolInfo> = Map::new("pool_info");
 pub const TMP_USER_ACTION: Item<Option<ExecuteOnReply>> = Item::new("tmp_user_action");
 
+/// ## Description
+/// This is a map that contains the LP tokens of the pools which are not deactivated.
+/// Only these pools are updated by the mass updates.
+pub const ACTIVE_POOLS: Map<&Addr, Empty> = Map::new("active_pools");
+
+/// ## Description
+/// Contains the proxy calls of the current transaction, the index of a call is the id of its reply.
+pub const TMP_PROXY_CALLS: Item<Vec<ProxyCall>> = Item::new("tmp_proxy_calls");
+
+/// ## Description
+/// This is a map that contains the proxy rewards which were not paid because of a failure of the proxy.
+///
+/// The first key part is reward proxy, the second key part is user.
+pub const UNPAID_PROXY_REWARDS: Map<(&Addr, &Addr), Uint128> = Map::new("unpaid_proxy_rewards");
+
 /// ## Description
 /// This is a map that contains information about all users.
 ///
 /// The first key part is token, the second key part is depositor.
 pub const USER_INFO: Map<(&Addr, &Addr), UserInfo> = Map::new("user_info");
 
+/// ## Description
+/// This is a map that contains the snapshots of the deposits of the users.
+///
+/// The first key part is liquidity pool token, the second key part is depositor.
+pub const USER_DEPOSITS: SnapshotMap<(&Addr, &Addr), Uint128> = SnapshotMap::new(
+    "user_deposits",
+    "user_deposits__checkpoints",
+    "user_deposits__changelog",
+    Strategy::EveryBlock,
+);
+
+/// ## Description
+/// This is a map that contains the snapshots of the total deposits of the pools.
+pub const TOTAL_DEPOSITS: SnapshotMap<&Addr, Uint128> = SnapshotMap::new(
+    "total_deposits",
+    "total_deposits__checkpoints",
+    "total_deposits__changelog",
+    Strategy::EveryBlock,
+);
+
+/// ## Description
+/// Stores the block since which the deposit snapshots are available.
+pub const SNAPSHOTS_START: Item<u64> = Item::new("snapshots_start");
+
+/// ## Description
+/// Stores the LP token and the user of the last deposit written to the snapshots while they are seeded.
+pub const SNAPSHOTS_SEED_CURSOR: Item<(Addr, Addr)> = Item::new("snapshots_seed_cursor");
+
+/// ## Description
+/// This is a map that contains the operators which are allowed to claim the rewards of the users
+/// along with the recipients of the claimed rewards.
+///
+/// The first key part is user, the second key part is operator.
+pub const CLAIM_OPERATORS: Map<(&Addr, &Addr), Addr> = Map::new("claim_operators");
+
+/// ## Description
+/// This is a map that contains the latest gauge votes of the users.
+pub const USER_VOTES: Map<&Addr, UserVotes> = Map::new("user_votes");
+
+/// ## Description
+/// This is a map that contains the amount of xASTRO locked by the users for gauge voting.
+pub const VOTING_POWER: Map<&Addr, Uint128> = Map::new("voting_power");
+
+/// ## Description
+/// This is a map that contains the vote tally of each epoch.
+///
+/// The first key part is epoch, the second key part is liquidity pool token.
+pub const EPOCH_VOTES: Map<(U64Key, &Addr), Uint128> = Map::new("epoch_votes");
+
+/// ## Description
+/// Stores the last epoch whose votes were applied to the allocation points.
+pub const LAST_TUNED_EPOCH: Item<u64> = Item::new("last_tuned_epoch");
+
 /// ## Description
 /// Contains proposal for change ownership.
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
+//settings for pagination
+/// The maximum limit for reading pools from a [`POOL_INFO`]
+const MAX_LIMIT: u32 = 30;
+
+/// The default limit for reading pools from a [`POOL_INFO`]
+const DEFAULT_LIMIT: u32 = 10;
+
+/// ## Description
+/// Reads pools from the [`POOL_INFO`] according to the specified parameters in `start_after` and `limit` variables.
+/// Otherwise, it returns the default number of pools.
+/// ## Params
+/// `start_after` is a [`Option`] type. Sets the LP token to start reading from.
+///
+/// `limit` is a [`Option`] type. Sets the number of items to be read.
+pub fn read_pools(
+    store: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, PoolInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|lp_token| Bound::exclusive(lp_token.as_bytes()));
+
+    POOL_INFO
+        .range(store, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (lp_token, pool) = item?;
+            Ok((Addr::unchecked(String::from_utf8(lp_token)?), pool))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Reads the pools where the user has a deposit, starting after `start_after`.
+/// Returns up to `limit` LP tokens along with the user's information.
+/// ## Params
+/// `user` is the object of type [`Addr`].
+///
+/// `start_after` is a [`Option`] type. Sets the LP token to start reading from.
+///
+/// `limit` is a [`Option`] type. Sets the number of items to be read.
+pub fn read_user_positions(
+    store: &dyn Storage,
+    user: &Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(Addr, UserInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|lp_token| Bound::exclusive(lp_token.as_bytes()));
+
+    let mut positions = vec![];
+    for lp_token in POOL_INFO.keys(store, start, None, Order::Ascending) {
+        let lp_token = Addr::unchecked(String::from_utf8(lp_token)?);
+        if let Some(user_info) = USER_INFO.may_load(store, (&lp_token, user))? {
+            positions.push((lp_token, user_info));
+            if positions.len() == limit {
+                break;
+            }
+        }
+    }
+
+    Ok(positions)
+}
+
+/// ## Description
+/// Returns the LP tokens along with their voting weight in the given epoch.
+/// ## Params
+/// `epoch` is the object of type [`u64`].
+pub fn read_epoch_votes(store: &dyn Storage, epoch: u64) -> StdResult<Vec<(Addr, Uint128)>> {
+    EPOCH_VOTES
+        .prefix(U64Key::new(epoch))
+        .range(store, None, None, Order::Ascending)
+        .map(|item| {
+            let (lp_token, votes) = item?;
+            Ok((Addr::unchecked(String::from_utf8(lp_token)?), votes))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the operators which are allowed to claim the rewards of the user along with the recipients.
+/// ## Params
+/// `user` is the object of type [`Addr`].
+pub fn read_claim_operators(store: &dyn Storage, user: &Addr) -> StdResult<Vec<(Addr, Addr)>> {
+    CLAIM_OPERATORS
+        .prefix(user)
+        .range(store, None, None, Order::Ascending)
+        .map(|item| {
+            let (operator, recipient) = item?;
+            Ok((Addr::unchecked(String::from_utf8(operator)?), recipient))
+        })
+        .collect()
+}
+
+/// ## Description
+/// Returns the pools which are not deactivated.
+pub fn get_active_pools(store: &dyn Storage) -> StdResult<Vec<(Addr, PoolInfo)>> {
+    ACTIVE_POOLS
+        .keys(store, None, None, Order::Ascending)
+        .map(|lp_token| {
+            let lp_token = Addr::unchecked(String::from_utf8(lp_token)?);
+            let pool = POOL_INFO.load(store, &lp_token)?;
+            Ok((lp_token, pool))
+        })
+        .collect()
+}
+
 pub fn get_pools(store: &dyn Storage) -> Vec<(Addr, PoolInfo)> {
     POOL_INFO
         .range(store, None, None, cosmwasm_std::Order::Ascending)
@@ -135,24 +557,174 @@ pub fn get_pools(store: &dyn Storage) -> Vec<(Addr, PoolInfo)> {
         .collect()
 }
 
+/// ## Description
+/// Records the change of the user's deposit and the total deposit of the pool in the snapshots
+/// at the given height.
+pub fn checkpoint_deposit(
+    store: &mut dyn Storage,
+    height: u64,
+    lp_token: &Addr,
+    user: &Addr,
+    old_amount: Uint128,
+    new_amount: Uint128,
+) -> StdResult<()> {
+    // the deposit isn't counted in the total deposit of the pool until it is seeded
+    let counted_amount = if USER_DEPOSITS.may_load(store, (lp_token, user))?.is_some() {
+        old_amount
+    } else {
+        Uint128::zero()
+    };
+
+    USER_DEPOSITS.save(store, (lp_token, user), &new_amount, height)?;
+    TOTAL_DEPOSITS.update::<_, StdError>(store, lp_token, height, |total| {
+        Ok(total
+            .unwrap_or_default()
+            .checked_sub(counted_amount)?
+            .checked_add(new_amount)?)
+    })?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Writes the current deposits of up to `limit` users which are not in the snapshots yet to the snapshots
+/// at the given height, continuing after the [`SNAPSHOTS_SEED_CURSOR`]. Returns `true` if the deposits
+/// of all pools are seeded.
+pub fn seed_deposit_snapshots(
+    store: &mut dyn Storage,
+    height: u64,
+    limit: Option<u32>,
+) -> StdResult<bool> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let cursor = SNAPSHOTS_SEED_CURSOR.may_load(store)?;
+
+    let mut seeded = 0;
+    for (lp_token, _) in get_pools(store) {
+        let start = match &cursor {
+            Some((last_lp_token, _)) if lp_token < *last_lp_token => continue,
+            Some((last_lp_token, last_user)) if lp_token == *last_lp_token => {
+                Some(Bound::exclusive(last_user.as_bytes()))
+            }
+            _ => None,
+        };
+
+        let deposits = USER_INFO
+            .prefix(&lp_token)
+            .range(store, start, None, Order::Ascending)
+            .take(limit - seeded)
+            .map(|item| {
+                let (user, user_info) = item?;
+                Ok((Addr::unchecked(String::from_utf8(user)?), user_info.amount))
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
+        for (user, amount) in deposits {
+            // deposits changed since the seeding started are already in the snapshots
+            if USER_DEPOSITS.may_load(store, (&lp_token, &user))?.is_none() {
+                USER_DEPOSITS.save(store, (&lp_token, &user), &amount, height)?;
+                TOTAL_DEPOSITS.update::<_, StdError>(store, &lp_token, height, |total| {
+                    Ok(total.unwrap_or_default().checked_add(amount)?)
+                })?;
+            }
+            SNAPSHOTS_SEED_CURSOR.save(store, &(lp_token.clone(), user))?;
+            seeded += 1;
+        }
+
+        if seeded == limit {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// ## Description
+/// Sets the amount and the boosted amount of the user, updates the total boosted amount of the pool
+/// and the reward debts of the user.
 pub fn update_user_balance(
     mut user: UserInfo,
-    pool: &PoolInfo,
+    pool: &mut PoolInfo,
     amount: Uint128,
+    virtual_amount: Uint128,
 ) -> StdResult<UserInfo> {
+    if let Some(total_virtual_amount) = pool.total_virtual_amount {
+        pool.total_virtual_amount = Some(
+            total_virtual_amount
+                .checked_sub(user.virtual_amount())?
+                .checked_add(virtual_amount)?,
+        );
+    }
+
     user.amount = amount;
+    user.virtual_amount = Some(virtual_amount);
 
     if !pool.accumulated_rewards_per_share.is_zero() {
         user.reward_debt = pool
             .accumulated_rewards_per_share
-            .checked_mul(user.amount)?;
+            .checked_mul(virtual_amount)?;
     };
 
-    if !pool.accumulated_proxy_rewards_per_share.is_zero() {
-        user.reward_debt_proxy = pool
-            .accumulated_proxy_rewards_per_share
-            .checked_mul(user.amount)?;
-    };
+    // The proxy reward debt is reset along with the index when the reward proxy is changed
+    user.reward_debt_proxy = pool
+        .accumulated_proxy_rewards_per_share
+        .checked_mul(user.amount)?;
+    user.settled_proxies = pool.retired_proxies.len();
+
+    user.reward_debt_streams = pool
+        .reward_streams
+        .iter()
+        .map(|stream| {
+            Ok((
+                stream.info.clone(),
+                stream
+                    .accumulated_rewards_per_share
+                    .checked_mul(user.amount)?,
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
 
     Ok(user)
 }
+
+/// ## Description
+/// Returns the proxy reward debt of the user for the current reward proxy of the pool. The debt of
+/// a user whose rewards on the retired proxies are not settled yet belongs to the retired proxy.
+pub fn proxy_reward_debt(pool: &PoolInfo, user: &UserInfo) -> Uint128 {
+    if user.settled_proxies < pool.retired_proxies.len() {
+        Uint128::zero()
+    } else {
+        user.reward_debt_proxy
+    }
+}
+
+/// ## Description
+/// Returns the rewards of the user on the retired reward proxies of the pool which are not settled yet.
+pub fn retired_proxy_rewards(pool: &PoolInfo, user: &UserInfo) -> StdResult<Vec<(Addr, Uint128)>> {
+    let mut reward_debt = user.reward_debt_proxy;
+    let mut rewards = vec![];
+
+    for retired in pool.retired_proxies.iter().skip(user.settled_proxies) {
+        let amount = retired
+            .accumulated_proxy_rewards_per_share
+            .checked_mul(user.amount)?
+            .checked_sub(reward_debt)?;
+        if !amount.is_zero() {
+            rewards.push((retired.reward_proxy.clone(), amount));
+        }
+
+        // The index of the next proxy starts from zero
+        reward_debt = Uint128::zero();
+    }
+
+    Ok(rewards)
+}
+
+/// ## Description
+/// Returns the reward debt of the user for the external reward stream with the given asset.
+pub fn stream_reward_debt(user: &UserInfo, info: &AssetInfo) -> Uint128 {
+    user.reward_debt_streams
+        .iter()
+        .find(|(debt_info, _)| debt_info.equal(info))
+        .map(|(_, debt)| *debt)
+        .unwrap_or_default()
+}
@@ -1,11 +1,16 @@
+use astroport::asset::{Asset, AssetInfo};
 use astroport::generator::{ExecuteMsg, QueryMsg};
 use astroport::{
     generator::{
-        ConfigResponse, Cw20HookMsg as GeneratorHookMsg, ExecuteMsg as GeneratorExecuteMsg,
-        InstantiateMsg as GeneratorInstantiateMsg, PendingTokenResponse,
-        QueryMsg as GeneratorQueryMsg,
+        ClaimOperatorResponse, ConfigResponse, Cw20HookMsg as GeneratorHookMsg, EmissionPeriod,
+        EmissionScheduleResponse, EpochVotesResponse, ExecuteMsg as GeneratorExecuteMsg,
+        IncentiveSchedule, InstantiateMsg as GeneratorInstantiateMsg, LockTier, LockedPosition,
+        MigrateMsg as GeneratorMigrateMsg, PendingTokenResponse, PoolAprResponse, PoolInfoResponse,
+        PriceSource, ProjectedAllocationsResponse, QueryMsg as GeneratorQueryMsg,
+        UserBoostResponse, UserPositionResponse,
     },
     generator_proxy::InstantiateMsg as ProxyInstantiateMsg,
+    pair::PoolResponse,
     token::InstantiateMsg as TokenInstantiateMsg,
     vesting::{
         Cw20HookMsg as VestingHookMsg, InstantiateMsg as VestingInstantiateMsg, VestingAccount,
@@ -14,9 +19,9 @@ use astroport::{
 };
 use cosmwasm_std::{
     testing::{mock_env, MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR},
-    to_binary, Addr, StdResult, Uint128, Uint64,
+    to_binary, Addr, Decimal, Empty, StdResult, Uint128, Uint64,
 };
-use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 use mirror_protocol::staking::{
     Cw20HookMsg as MirrorStakingHookMsg, ExecuteMsg as MirrorExecuteMsg,
     InstantiateMsg as MirrorInstantiateMsg,
@@ -189,6 +194,237 @@ fn disabling_pool() {
     );
 }
 
+#[test]
+fn deactivating_pool() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[&lp_cny_eur_instance, &lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 20);
+    mint_tokens(&mut app, &lp_eur_usd_instance, &user1, 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10), (&lp_eur_usd_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = GeneratorExecuteMsg::DeactivatePool {
+        lp_token: lp_cny_eur_instance.clone(),
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Pool is deactivated!");
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.total_alloc_point, Uint64::new(100));
+
+    let pools: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    let deactivated = pools
+        .iter()
+        .find(|pool| pool.lp_token == lp_cny_eur_instance)
+        .unwrap();
+    assert!(deactivated.deactivated);
+    assert_eq!(deactivated.alloc_point, Uint64::zero());
+
+    // The deactivated pool doesn't receive ASTRO anymore, the rest goes to the active pool
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_eur_usd_instance,
+        USER1,
+        (15_000000, None),
+    );
+
+    // Deactivated pools can't be updated or receive deposits
+    let msg = GeneratorExecuteMsg::Set {
+        lp_token: lp_cny_eur_instance.clone(),
+        alloc_point: Uint64::new(100),
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Pool is deactivated!");
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Pool is deactivated!");
+
+    let msg = GeneratorExecuteMsg::MassUpdatePools {};
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // Users can still withdraw and claim the remaining rewards
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user1, 20);
+    check_token_balance(&mut app, &astro_token_instance, &user1, 5_000000);
+}
+
+#[test]
+fn migration_updates_active_pools() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[&lp_cny_eur_instance, &lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &Addr::unchecked(USER1), 10);
+    mint_tokens(&mut app, &lp_eur_usd_instance, &Addr::unchecked(USER1), 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10), (&lp_eur_usd_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = GeneratorExecuteMsg::DeactivatePool {
+        lp_token: lp_cny_eur_instance.clone(),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    app.update_block(|bi| next_block(bi));
+
+    // The active pool accrued ASTRO per block which wasn't written to the pool yet
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_eur_usd_instance,
+        USER1,
+        (15_000000, None),
+    );
+
+    let generator_code_id = store_generator_code(&mut app);
+    app.migrate_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &GeneratorMigrateMsg {
+            tokens_per_second: Some(Uint128::new(1_000000)),
+        },
+        generator_code_id,
+    )
+    .unwrap();
+
+    // The rewards accrued before the migration are kept
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_eur_usd_instance,
+        USER1,
+        (15_000000, None),
+    );
+
+    // Only the active pool receives ASTRO per second after the migration
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(10);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_eur_usd_instance,
+        USER1,
+        (25_000000, None),
+    );
+}
+
 #[test]
 fn set_tokens_per_block() {
     let mut app = mock_app();
@@ -250,9 +486,15 @@ fn update_config() {
     assert_eq!(res.vesting_contract.to_string(), "contract #1");
 
     let new_vesting = Addr::unchecked("new_vesting");
+    let factory = Addr::unchecked("factory");
 
     let msg = ExecuteMsg::UpdateConfig {
         vesting_contract: Some(new_vesting.to_string()),
+        factory: Some(factory.to_string()),
+        xastro_token: None,
+        gauge_epoch_length: None,
+        maker: None,
+        incentive_fee: None,
     };
 
     // Assert cannot update with improper owner
@@ -282,6 +524,42 @@ fn update_config() {
         .unwrap();
 
     assert_eq!(res.vesting_contract, new_vesting);
+    assert_eq!(res.factory, Some(factory.clone()));
+
+    // The factory is allowed to add and set pools
+    let lp_token = instantiate_token(&mut app, token_code_id, "LP", None);
+
+    let msg = ExecuteMsg::Add {
+        lp_token: lp_token.clone(),
+        alloc_point: Uint64::from(100u64),
+        reward_proxy: None,
+    };
+
+    let e = app
+        .execute_contract(
+            Addr::unchecked("not_owner"),
+            generator_instance.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(e.to_string(), "Unauthorized");
+
+    app.execute_contract(factory.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = ExecuteMsg::Set {
+        lp_token: lp_token.clone(),
+        alloc_point: Uint64::zero(),
+    };
+    app.execute_contract(factory, generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.total_alloc_point, Uint64::zero());
 }
 
 #[test]
@@ -1215,57 +1493,2678 @@ fn generator_with_mirror_reward_proxy() {
     );
 }
 
-fn mock_app() -> App {
-    let api = MockApi::default();
-    let env = mock_env();
-    let bank = BankKeeper::new();
-    let storage = MockStorage::new();
-    let terra_mock_querier = TerraMockQuerier::new(MockQuerier::new(&[]));
-
-    App::new(api, env.block, bank, storage, terra_mock_querier)
-}
+#[test]
+fn generator_with_reward_streams() {
+    let mut app = mock_app();
 
-fn store_token_code(app: &mut App) -> u64 {
-    let astro_token_contract = Box::new(ContractWrapper::new(
-        astroport_token::contract::execute,
-        astroport_token::contract::instantiate,
-        astroport_token::contract::query,
-    ));
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
 
-    app.store_code(astro_token_contract)
-}
+    let token_code_id = store_token_code(&mut app);
 
-fn instantiate_token(app: &mut App, token_code_id: u64, name: &str, cap: Option<u128>) -> Addr {
-    let name = String::from(name);
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let partner_token_instance = instantiate_token(&mut app, token_code_id, "PARTNER", None);
 
-    let msg = TokenInstantiateMsg {
-        name: name.clone(),
-        symbol: name.clone(),
-        decimals: 6,
-        initial_balances: vec![],
-        mint: Some(MinterResponse {
-            minter: String::from(OWNER),
-            cap: cap.map(|v| Uint128::from(v)),
-        }),
-    };
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
 
-    app.instantiate_contract(token_code_id, Addr::unchecked(OWNER), &msg, &[], name, None)
-        .unwrap()
-}
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
 
-fn instantiate_generator(mut app: &mut App, astro_token_instance: &Addr) -> Addr {
-    // Vesting
-    let vesting_contract = Box::new(ContractWrapper::new(
-        astroport_vesting::contract::execute,
-        astroport_vesting::contract::instantiate,
-        astroport_vesting::contract::query,
-    ));
-    let owner = Addr::unchecked(OWNER);
-    let vesting_code_id = app.store_code(vesting_contract);
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
 
-    let init_msg = VestingInstantiateMsg {
-        token_addr: astro_token_instance.to_string(),
-    };
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user2, 10);
+    mint_tokens(&mut app, &partner_token_instance, &owner, 1000);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    let end_time = app.block_info().time.seconds() + 100;
+
+    // Cw20 streams can't be funded without sending the tokens
+    let msg = GeneratorExecuteMsg::FundRewardStream {
+        lp_token: lp_cny_eur_instance.to_string(),
+        reward: Asset {
+            info: AssetInfo::Token {
+                contract_addr: partner_token_instance.clone(),
+            },
+            amount: Uint128::new(1000),
+        },
+        end_time,
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    // LP tokens can't be used as rewards
+    mint_tokens(&mut app, &lp_cny_eur_instance, &owner, 10);
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::FundRewardStream {
+            lp_token: lp_cny_eur_instance.to_string(),
+            end_time,
+        })
+        .unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(owner.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "LP tokens can't be used as reward stream assets!"
+    );
+
+    // Only the assets allowed by the owner can be distributed
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::FundRewardStream {
+            lp_token: lp_cny_eur_instance.to_string(),
+            end_time,
+        })
+        .unwrap(),
+        amount: Uint128::new(1000),
+    };
+    let err = app
+        .execute_contract(owner.clone(), partner_token_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The asset isn't allowed in reward streams!"
+    );
+
+    let msg = GeneratorExecuteMsg::SetRewardStreamAssets {
+        assets: vec![(
+            AssetInfo::Token {
+                contract_addr: partner_token_instance.clone(),
+            },
+            Uint128::new(100),
+        )],
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // Dust can't take a stream slot
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::FundRewardStream {
+            lp_token: lp_cny_eur_instance.to_string(),
+            end_time,
+        })
+        .unwrap(),
+        amount: Uint128::new(99),
+    };
+    let err = app
+        .execute_contract(owner.clone(), partner_token_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Reward stream must be funded with at least 100 tokens!"
+    );
+
+    // Distribute 1000 PARTNER tokens over 100 seconds
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::FundRewardStream {
+            lp_token: lp_cny_eur_instance.to_string(),
+            end_time,
+        })
+        .unwrap(),
+        amount: Uint128::new(1000),
+    };
+    app.execute_contract(owner.clone(), partner_token_instance.clone(), &msg, &[])
+        .unwrap();
+
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(50);
+    });
+
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        &partner_token_instance,
+        500,
+    );
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER2,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    // The stream ends in 50 seconds, nothing is distributed afterwards
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        &partner_token_instance,
+        750,
+    );
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        &partner_token_instance,
+        250,
+    );
+
+    // Stream rewards are claimed alongside ASTRO
+    for user in [&user1, &user2] {
+        let msg = GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur_instance.clone(),
+            amount: Uint128::new(10),
+        };
+        app.execute_contract(user.clone(), generator_instance.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    check_token_balance(&mut app, &partner_token_instance, &user1, 750);
+    check_token_balance(&mut app, &partner_token_instance, &user2, 250);
+    check_token_balance(&mut app, &partner_token_instance, &generator_instance, 0);
+}
+
+#[test]
+fn generator_incentives() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let maker = Addr::unchecked("maker");
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let partner_token_instance = instantiate_token(&mut app, token_code_id, "PARTNER", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user2, 10);
+    mint_tokens(&mut app, &partner_token_instance, &owner, 1500);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    // Cw20 incentives can't be funded without sending the tokens
+    let msg = GeneratorExecuteMsg::Incentivize {
+        lp_token: lp_cny_eur_instance.to_string(),
+        reward: Asset {
+            info: AssetInfo::Token {
+                contract_addr: partner_token_instance.clone(),
+            },
+            amount: Uint128::new(1000),
+        },
+        duration: 100,
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let incentivize_msg = |amount: u128, duration: u64| Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::Incentivize {
+            lp_token: lp_cny_eur_instance.to_string(),
+            duration,
+        })
+        .unwrap(),
+        amount: Uint128::new(amount),
+    };
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            partner_token_instance.clone(),
+            &incentivize_msg(1000, 0),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Incentive duration must be positive and at most 31536000 seconds!"
+    );
+
+    // Only the assets allowed by the owner can be distributed
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            partner_token_instance.clone(),
+            &incentivize_msg(1000, 100),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The asset isn't allowed in reward streams!"
+    );
+
+    let msg = GeneratorExecuteMsg::SetRewardStreamAssets {
+        assets: vec![(
+            AssetInfo::Token {
+                contract_addr: partner_token_instance.clone(),
+            },
+            Uint128::new(100),
+        )],
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // Dust can't take an incentive slot
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            partner_token_instance.clone(),
+            &incentivize_msg(99, 100),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Reward stream must be funded with at least 100 tokens!"
+    );
+
+    // Distribute 1000 PARTNER tokens over 100 seconds
+    app.execute_contract(
+        owner.clone(),
+        partner_token_instance.clone(),
+        &incentivize_msg(1000, 100),
+        &[],
+    )
+    .unwrap();
+
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(50);
+    });
+
+    let msg = ExecuteMsg::UpdateConfig {
+        vesting_contract: None,
+        factory: None,
+        xastro_token: None,
+        gauge_epoch_length: None,
+        maker: Some(maker.to_string()),
+        incentive_fee: Some(Decimal::percent(11)),
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Incentive fee can't exceed 10%!");
+
+    let msg = ExecuteMsg::UpdateConfig {
+        vesting_contract: None,
+        factory: None,
+        xastro_token: None,
+        gauge_epoch_length: None,
+        maker: Some(maker.to_string()),
+        incentive_fee: Some(Decimal::percent(10)),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.maker, Some(maker.clone()));
+    assert_eq!(res.incentive_fee, Decimal::percent(10));
+
+    // The overlapping incentive distributes 450 PARTNER tokens over 50 seconds after the fee
+    app.execute_contract(
+        owner.clone(),
+        partner_token_instance.clone(),
+        &incentivize_msg(500, 50),
+        &[],
+    )
+    .unwrap();
+    check_token_balance(&mut app, &partner_token_instance, &maker, 50);
+
+    let res: Vec<IncentiveSchedule> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Incentives {
+                lp_token: lp_cny_eur_instance.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|incentive| (incentive.amount.u128(), incentive.reward_per_second))
+            .collect::<Vec<_>>(),
+        vec![
+            (1000, Decimal::from_ratio(10u128, 1u128)),
+            (450, Decimal::from_ratio(9u128, 1u128))
+        ]
+    );
+    assert_eq!(res[0].incentivizer, owner);
+
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        &partner_token_instance,
+        500,
+    );
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER2,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    // Both incentives end in 50 seconds, nothing is distributed afterwards
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        &partner_token_instance,
+        975,
+    );
+    check_pending_stream_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        &partner_token_instance,
+        475,
+    );
+
+    let res: Vec<IncentiveSchedule> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Incentives {
+                lp_token: lp_cny_eur_instance.clone(),
+            },
+        )
+        .unwrap();
+    assert!(res.is_empty());
+
+    for user in [&user1, &user2] {
+        let msg = GeneratorExecuteMsg::Withdraw {
+            lp_token: lp_cny_eur_instance.clone(),
+            amount: Uint128::new(10),
+        };
+        app.execute_contract(user.clone(), generator_instance.clone(), &msg, &[])
+            .unwrap();
+    }
+
+    check_token_balance(&mut app, &partner_token_instance, &user1, 975);
+    check_token_balance(&mut app, &partner_token_instance, &user2, 475);
+    check_token_balance(&mut app, &partner_token_instance, &generator_instance, 0);
+}
+
+#[test]
+fn generator_with_time_based_emissions() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator_with_emissions(
+        &mut app,
+        &astro_token_instance,
+        Some(Uint128::new(1_000000)),
+    );
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.tokens_per_second, Some(Uint128::new(1_000000)));
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    // Rewards depend on the elapsed time rather than the number of blocks
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(10);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (10_000000, None),
+    );
+
+    let msg = ExecuteMsg::SetTokensPerBlock {
+        amount: Uint128::new(100),
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Emissions are time-based!");
+
+    let msg = ExecuteMsg::SetTokensPerSecond {
+        amount: Uint128::new(2_000000),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(5);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (20_000000, None),
+    );
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &astro_token_instance, &user1, 20_000000);
+}
+
+#[test]
+fn migrate_to_time_based_emissions() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user2, 30);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER2,
+        &[(&lp_cny_eur_instance, 30)],
+    );
+
+    // The block-based deployment accrues 10 ASTRO per block
+    app.update_block(|bi| next_block(bi));
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (15_000000, None),
+    );
+
+    let generator_code_id = store_generator_code(&mut app);
+    let msg = GeneratorMigrateMsg {
+        tokens_per_second: Some(Uint128::new(2_000000)),
+    };
+    app.migrate_contract(
+        owner.clone(),
+        generator_instance.clone(),
+        &msg,
+        generator_code_id,
+    )
+    .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.tokens_per_second, Some(Uint128::new(2_000000)));
+
+    // The rewards accrued per block survive the migration
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (15_000000, None),
+    );
+
+    let err = app
+        .migrate_contract(
+            owner.clone(),
+            generator_instance.clone(),
+            &msg,
+            generator_code_id,
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Emissions are time-based!");
+
+    // Rewards depend on the elapsed time after the migration
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(10);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (10_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (30_000000, None),
+    );
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &astro_token_instance, &user1, 10_000000);
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user1, 10);
+}
+
+#[test]
+fn generator_emission_schedule() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator_with_emissions(
+        &mut app,
+        &astro_token_instance,
+        Some(Uint128::new(1_000000)),
+    );
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    let now = app.block_info().time.seconds();
+
+    // Overlapping periods are not allowed
+    let msg = ExecuteMsg::SetEmissionSchedule {
+        periods: vec![
+            EmissionPeriod {
+                start_time: now,
+                end_time: now + 10,
+                tokens_per_second: Uint128::new(2_000000),
+            },
+            EmissionPeriod {
+                start_time: now + 5,
+                end_time: now + 30,
+                tokens_per_second: Uint128::new(1_000000),
+            },
+        ],
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Emission periods must be sorted, must not overlap and must end after they start!"
+    );
+
+    // Emissions can't exceed the vesting allocation of the generator
+    let msg = ExecuteMsg::SetEmissionSchedule {
+        periods: vec![EmissionPeriod {
+            start_time: now,
+            end_time: now + 2,
+            tokens_per_second: Uint128::new(63072000_000000),
+        }],
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Scheduled emissions 126144000000000 exceed the remaining vesting allocation 63072000000000!"
+    );
+
+    let msg = ExecuteMsg::SetEmissionSchedule {
+        periods: vec![
+            EmissionPeriod {
+                start_time: now,
+                end_time: now + 10,
+                tokens_per_second: Uint128::new(2_000000),
+            },
+            EmissionPeriod {
+                start_time: now + 20,
+                end_time: now + 30,
+                tokens_per_second: Uint128::new(1_000000),
+            },
+        ],
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: EmissionScheduleResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::EmissionSchedule {})
+        .unwrap();
+    assert_eq!(res.tokens_per_second, Some(Uint128::new(2_000000)));
+    assert_eq!(res.scheduled_emissions, Uint128::new(30_000000));
+
+    // Nothing is emitted between the periods
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(15);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (20_000000, None),
+    );
+
+    let res: EmissionScheduleResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::EmissionSchedule {})
+        .unwrap();
+    assert_eq!(res.tokens_per_second, Some(Uint128::zero()));
+    assert_eq!(res.scheduled_emissions, Uint128::new(10_000000));
+
+    // Nothing is emitted after the schedule ends
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (30_000000, None),
+    );
+}
+
+#[test]
+fn generator_boosted_rewards() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let xastro_token_instance = instantiate_token(&mut app, token_code_id, "xASTRO", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    let msg = ExecuteMsg::UpdateConfig {
+        vesting_contract: None,
+        factory: None,
+        xastro_token: Some(xastro_token_instance.to_string()),
+        gauge_epoch_length: None,
+        maker: None,
+        incentive_fee: None,
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user2, 10);
+    mint_tokens(&mut app, &xastro_token_instance, &user2, 100);
+
+    // User1 has no xASTRO, so only 40% of the deposit is counted: 4
+    // User2 has all xASTRO: min(4 + 20 * 0.6, 10) = 10
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER2,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (2857142, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (7142857, None),
+    );
+
+    // User1 stakes xASTRO, the boost is applied after a checkpoint
+    mint_tokens(&mut app, &xastro_token_instance, &user1, 100);
+
+    let msg = QueryMsg::UserBoost {
+        lp_token: lp_cny_eur_instance.clone(),
+        user: user1.clone(),
+    };
+    let res: UserBoostResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res.virtual_amount, Uint128::new(4));
+    assert_eq!(res.checkpoint_virtual_amount, Uint128::new(10));
+    assert_eq!(res.boost_factor, Decimal::one());
+
+    let msg = ExecuteMsg::Checkpoint {
+        lp_token: lp_cny_eur_instance.clone(),
+        user: user1.to_string(),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // Pending rewards are sent on checkpoint
+    check_token_balance(&mut app, &astro_token_instance, &user1, 2857142);
+
+    let msg = QueryMsg::UserBoost {
+        lp_token: lp_cny_eur_instance.clone(),
+        user: user1.clone(),
+    };
+    let res: UserBoostResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res.virtual_amount, Uint128::new(10));
+    assert_eq!(res.boost_factor, Decimal::from_ratio(5u128, 2u128));
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (5_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (12142857, None),
+    );
+}
+
+#[test]
+fn claim_rewards_from_many_pools() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[&lp_cny_eur_instance, &lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_eur_usd_instance, &user1, 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10), (&lp_eur_usd_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    // Can't claim from a pool without a deposit
+    let msg = ExecuteMsg::ClaimRewards {
+        lp_tokens: vec![lp_cny_eur_instance.to_string()],
+    };
+    app.execute_contract(
+        Addr::unchecked(USER2),
+        generator_instance.clone(),
+        &msg,
+        &[],
+    )
+    .unwrap_err();
+
+    let msg = ExecuteMsg::ClaimRewards {
+        lp_tokens: vec![
+            lp_cny_eur_instance.to_string(),
+            lp_eur_usd_instance.to_string(),
+            lp_cny_eur_instance.to_string(),
+        ],
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &astro_token_instance, &user1, 10_000000);
+
+    for lp_token in [&lp_cny_eur_instance, &lp_eur_usd_instance] {
+        check_pending_rewards(&mut app, &generator_instance, lp_token, USER1, (0, None));
+    }
+
+    // Deposits stay in the generator
+    check_token_balance(&mut app, &lp_cny_eur_instance, &generator_instance, 10);
+    check_token_balance(&mut app, &lp_eur_usd_instance, &generator_instance, 10);
+}
+
+#[test]
+fn query_user_positions_and_pools() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+    let lp_usd_jpy_instance = instantiate_token(&mut app, token_code_id, "USD-JPY", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[
+            &lp_cny_eur_instance,
+            &lp_eur_usd_instance,
+            &lp_usd_jpy_instance,
+        ],
+    );
+
+    let pools: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        pools.iter().map(|p| p.lp_token.clone()).collect::<Vec<_>>(),
+        vec![
+            lp_cny_eur_instance.clone(),
+            lp_eur_usd_instance.clone(),
+            lp_usd_jpy_instance.clone()
+        ]
+    );
+
+    let pools: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Pools {
+                start_after: Some(lp_cny_eur_instance.clone()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(pools.len(), 1);
+    assert_eq!(pools[0].lp_token, lp_eur_usd_instance);
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_usd_jpy_instance, &user1, 20);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10), (&lp_usd_jpy_instance, 20)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let positions: Vec<UserPositionResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::UserPositions {
+                user: user1.clone(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        positions
+            .iter()
+            .map(|p| (p.lp_token.clone(), p.amount.u128()))
+            .collect::<Vec<_>>(),
+        vec![
+            (lp_cny_eur_instance.clone(), 10),
+            (lp_usd_jpy_instance.clone(), 20)
+        ]
+    );
+    for position in &positions {
+        assert!(!position.pending.is_zero());
+        check_pending_rewards(
+            &mut app,
+            &generator_instance,
+            &position.lp_token,
+            USER1,
+            (position.pending.u128(), None),
+        );
+    }
+
+    // The pool without a deposit is skipped when paginating
+    let positions: Vec<UserPositionResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::UserPositions {
+                user: user1.clone(),
+                start_after: Some(lp_cny_eur_instance.clone()),
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].lp_token, lp_usd_jpy_instance);
+
+    let positions: Vec<UserPositionResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::UserPositions {
+                user: Addr::unchecked(USER2),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert!(positions.is_empty());
+}
+
+#[test]
+fn generator_lock_tiers() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_cny_eur_instance]);
+
+    let msg = ExecuteMsg::SetLockTiers {
+        lp_token: lp_cny_eur_instance.clone(),
+        tiers: vec![LockTier {
+            duration: 100,
+            multiplier: Decimal::from_ratio(2u128, 1u128),
+        }],
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let invalid_msg = ExecuteMsg::SetLockTiers {
+        lp_token: lp_cny_eur_instance.clone(),
+        tiers: vec![LockTier {
+            duration: 100,
+            multiplier: Decimal::from_ratio(1u128, 2u128),
+        }],
+    };
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &invalid_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Lock-up tiers must have unique non-zero durations and multipliers of at least 1!"
+    );
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user2, 20);
+
+    // The duration must match one of the tiers
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::DepositLocked { duration: 50 }).unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Pool doesn't have a lock-up tier with the duration 50!"
+    );
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::DepositLocked { duration: 100 }).unwrap(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let unlock_time = app.block_info().time.seconds() + 100;
+
+    let res: Vec<LockedPosition> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::UserLocks {
+                lp_token: lp_cny_eur_instance.clone(),
+                user: user1.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![LockedPosition {
+            amount: Uint128::new(10),
+            multiplier: Decimal::from_ratio(2u128, 1u128),
+            unlock_time,
+        }]
+    );
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER2,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    // The locked deposit of user1 weighs 20 against 10 of user2
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (6666666, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (3333333, None),
+    );
+
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(1),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Amount 10 is locked and can't be withdrawn yet!"
+    );
+
+    // The lock expires, so user1 can withdraw and loses the bonus
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(5),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user1, 5);
+    check_token_balance(&mut app, &astro_token_instance, &user1, 13333333);
+
+    // User2 locks the rest of the tokens and withdraws them in an emergency
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::DepositLocked { duration: 100 }).unwrap(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user2.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = ExecuteMsg::EmergencyWithdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user2, 20);
+
+    app.update_block(|bi| next_block(bi));
+
+    // User1 is the only staker left
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (10_000000, None),
+    );
+}
+
+#[test]
+fn generator_gauge_voting() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+    let xastro_token_instance = instantiate_token(&mut app, token_code_id, "xASTRO", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[&lp_cny_eur_instance, &lp_eur_usd_instance],
+    );
+
+    let vote_msg = ExecuteMsg::Vote {
+        votes: vec![(lp_cny_eur_instance.to_string(), 10000)],
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &vote_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Gauge voting is disabled!");
+
+    let msg = ExecuteMsg::UpdateConfig {
+        vesting_contract: None,
+        factory: None,
+        xastro_token: Some(xastro_token_instance.to_string()),
+        gauge_epoch_length: Some(86400),
+        maker: None,
+        incentive_fee: None,
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    mint_tokens(&mut app, &xastro_token_instance, &user1, 300);
+    mint_tokens(&mut app, &xastro_token_instance, &user2, 100);
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 100);
+
+    // The xASTRO balance alone gives no voting power
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &vote_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No voting power!");
+
+    let lock_msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::LockVotingPower {}).unwrap(),
+        amount: Uint128::new(100),
+    };
+
+    // Only xASTRO can be locked
+    let err = app
+        .execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &lock_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(user2.clone(), xastro_token_instance.clone(), &lock_msg, &[])
+        .unwrap();
+
+    let lock_msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::LockVotingPower {}).unwrap(),
+        amount: Uint128::new(300),
+    };
+    app.execute_contract(user1.clone(), xastro_token_instance.clone(), &lock_msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &xastro_token_instance, &generator_instance, 400);
+
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::VotingPower {
+                user: user1.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Uint128::new(300));
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &vote_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No voting power!");
+
+    let msg = ExecuteMsg::Vote {
+        votes: vec![
+            (lp_cny_eur_instance.to_string(), 5000),
+            (lp_eur_usd_instance.to_string(), 5001),
+        ],
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Votes must be for different existing pools and sum to at most 10000 basis points!"
+    );
+
+    app.execute_contract(user1.clone(), generator_instance.clone(), &vote_msg, &[])
+        .unwrap();
+
+    let msg = ExecuteMsg::Vote {
+        votes: vec![(lp_eur_usd_instance.to_string(), 10000)],
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // User1 changes the votes, the previous ones are revoked
+    let msg = ExecuteMsg::Vote {
+        votes: vec![
+            (lp_cny_eur_instance.to_string(), 5000),
+            (lp_eur_usd_instance.to_string(), 5000),
+        ],
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    // The voted xASTRO can't be moved to vote again in the same epoch
+    let unlock_msg = ExecuteMsg::UnlockVotingPower {
+        amount: Uint128::new(300),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &unlock_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Voting power can't be unlocked in the epoch of the votes!"
+    );
+
+    let res: EpochVotesResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::EpochVotes { epoch: None })
+        .unwrap();
+    assert_eq!(
+        res.votes,
+        vec![
+            (lp_cny_eur_instance.clone(), Uint128::new(150)),
+            (lp_eur_usd_instance.clone(), Uint128::new(250))
+        ]
+    );
+
+    let res: ProjectedAllocationsResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::ProjectedAllocations {})
+        .unwrap();
+    assert_eq!(
+        res.allocations,
+        vec![
+            (lp_cny_eur_instance.clone(), Uint64::new(3750)),
+            (lp_eur_usd_instance.clone(), Uint64::new(6250))
+        ]
+    );
+
+    // Nobody voted in the previous epoch, so the allocations are not changed
+    let msg = ExecuteMsg::TuneAllocations {};
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .starts_with("Allocations are already tuned for the epoch"));
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.total_alloc_point, Uint64::new(200));
+
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(86400);
+    });
+
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.total_alloc_point, Uint64::new(10000));
+
+    let res: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res.iter()
+            .map(|p| (p.lp_token.clone(), p.alloc_point))
+            .collect::<Vec<_>>(),
+        vec![
+            (lp_cny_eur_instance.clone(), Uint64::new(3750)),
+            (lp_eur_usd_instance.clone(), Uint64::new(6250))
+        ]
+    );
+
+    // The votes of the new epoch are empty until users vote again
+    let res: ProjectedAllocationsResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::ProjectedAllocations {})
+        .unwrap();
+    assert!(res.allocations.is_empty());
+
+    // The xASTRO can be unlocked in the next epoch
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            generator_instance.clone(),
+            &ExecuteMsg::UnlockVotingPower {
+                amount: Uint128::new(301),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No voting power!");
+
+    app.execute_contract(user1.clone(), generator_instance.clone(), &unlock_msg, &[])
+        .unwrap();
+    check_token_balance(&mut app, &xastro_token_instance, &user1, 300);
+    check_token_balance(&mut app, &xastro_token_instance, &generator_instance, 100);
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &vote_msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No voting power!");
+}
+
+#[test]
+fn generator_with_failing_reward_proxies() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    // The first proxy fails to send rewards, the second one fails to update them after a migration
+    let broken_payout_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_proxy::execute_broken_payout,
+        mock_proxy::instantiate,
+        mock_proxy::query,
+    )));
+    let proxy_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            mock_proxy::execute,
+            mock_proxy::instantiate,
+            mock_proxy::query,
+        )
+        .with_migrate(mock_proxy::migrate),
+    ));
+    let broken_update_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            mock_proxy::execute_broken_update,
+            mock_proxy::instantiate,
+            mock_proxy::query,
+        )
+        .with_migrate(mock_proxy::migrate),
+    ));
+
+    let mut proxies = vec![];
+    for (code_id, lp_token) in [
+        (broken_payout_code_id, &lp_cny_eur_instance),
+        (proxy_code_id, &lp_eur_usd_instance),
+    ] {
+        let msg = ProxyInstantiateMsg {
+            generator_contract_addr: generator_instance.to_string(),
+            pair_addr: "pair".to_string(),
+            lp_token_addr: lp_token.to_string(),
+            reward_contract_addr: "reward".to_string(),
+            reward_token_addr: "reward_token".to_string(),
+        };
+        proxies.push(
+            app.instantiate_contract(
+                code_id,
+                owner.clone(),
+                &msg,
+                &[],
+                "Proxy",
+                Some(OWNER.to_string()),
+            )
+            .unwrap(),
+        );
+    }
+
+    let msg = ExecuteMsg::SetAllowedRewardProxies {
+        proxies: proxies.iter().map(|p| p.to_string()).collect(),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        Some(&proxies[0]),
+        &[&lp_cny_eur_instance],
+    );
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        Some(&proxies[1]),
+        &[&lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    mint_tokens(&mut app, &lp_eur_usd_instance, &user1, 10);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10), (&lp_eur_usd_instance, 10)],
+    );
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &proxies[0], 10);
+    check_token_balance(&mut app, &lp_eur_usd_instance, &proxies[1], 10);
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [false, false]
+    );
+
+    app.migrate_contract(
+        owner.clone(),
+        proxies[1].clone(),
+        &Empty {},
+        broken_update_code_id,
+    )
+    .unwrap();
+
+    app.update_block(|bi| next_block(bi));
+
+    // The proxy fails to send rewards, so they are kept for a later claim
+    let msg = ExecuteMsg::ClaimRewards {
+        lp_tokens: vec![lp_cny_eur_instance.to_string()],
+    };
+    let res = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.ty.ends_with("reward_proxy_disabled")));
+
+    check_token_balance(&mut app, &astro_token_instance, &user1, 5_000000);
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [true, false]
+    );
+
+    let msg = QueryMsg::UnpaidProxyRewards {
+        reward_proxy: proxies[0].clone(),
+        user: user1.clone(),
+    };
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res, Uint128::new(100));
+
+    // The claim fails again, the rewards are still kept
+    let claim_msg = ExecuteMsg::ClaimUnpaidProxyRewards {
+        reward_proxy: proxies[0].to_string(),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &claim_msg, &[])
+        .unwrap();
+
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res, Uint128::new(100));
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(USER2),
+            generator_instance.clone(),
+            &claim_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "No unpaid proxy rewards!");
+
+    // LP tokens can still be withdrawn although the proxy fails to update rewards
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_eur_usd_instance.clone(),
+        amount: Uint128::new(10),
+    };
+    let res = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.ty.ends_with("reward_proxy_disabled")));
+
+    check_token_balance(&mut app, &lp_eur_usd_instance, &user1, 10);
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [true, true]
+    );
+
+    // The LP tokens are not sent to the disabled proxy
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_eur_usd_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Reward proxy of the pool is disabled!");
+
+    let msg = ExecuteMsg::ReattachRewardProxy {
+        lp_token: lp_eur_usd_instance.clone(),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [true, false]
+    );
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Reward proxy is not disabled!");
+}
+
+#[test]
+fn generator_with_failing_reward_proxy_queries() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    let proxy_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            mock_proxy::execute,
+            mock_proxy::instantiate,
+            mock_proxy::query,
+        )
+        .with_migrate(mock_proxy::migrate),
+    ));
+
+    // The broken proxy fails to report its deposit and rewards and to withdraw the LP tokens
+    let broken_proxy_code_id = app.store_code(Box::new(
+        ContractWrapper::new(
+            mock_proxy::execute_broken_withdraw,
+            mock_proxy::instantiate,
+            mock_proxy::query_broken,
+        )
+        .with_migrate(mock_proxy::migrate),
+    ));
+
+    let msg = ProxyInstantiateMsg {
+        generator_contract_addr: generator_instance.to_string(),
+        pair_addr: "pair".to_string(),
+        lp_token_addr: lp_cny_eur_instance.to_string(),
+        reward_contract_addr: "reward".to_string(),
+        reward_token_addr: "reward_token".to_string(),
+    };
+    let proxy = app
+        .instantiate_contract(
+            proxy_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            "Proxy",
+            Some(OWNER.to_string()),
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::SetAllowedRewardProxies {
+        proxies: vec![proxy.to_string()],
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        Some(&proxy),
+        &[&lp_cny_eur_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 20);
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &proxy, 10);
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [false]
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    app.migrate_contract(
+        owner.clone(),
+        proxy.clone(),
+        &Empty {},
+        broken_proxy_code_id,
+    )
+    .unwrap();
+
+    // The LP tokens are not sent to the failing proxy
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Reward proxy of the pool is disabled!");
+
+    // The proxy fails to withdraw, so the deposit is restored instead of reverting the rewards
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(5),
+    };
+    let res = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+    assert!(res
+        .events
+        .iter()
+        .any(|e| e.ty.ends_with("reward_proxy_disabled")));
+    assert_eq!(
+        query_disabled_proxies(&mut app, &generator_instance),
+        [true]
+    );
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user1, 10);
+    check_token_balance(&mut app, &lp_cny_eur_instance, &proxy, 10);
+    check_token_balance(&mut app, &astro_token_instance, &user1, 10_000000);
+
+    let msg = QueryMsg::Deposit {
+        lp_token: lp_cny_eur_instance.clone(),
+        user: user1.clone(),
+    };
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &msg)
+        .unwrap();
+    assert_eq!(res, Uint128::new(10));
+
+    // The pool keeps using the deposit tracked by the generator
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (10_000000, Some(0)),
+    );
+
+    let msg = Cw20ExecuteMsg::Send {
+        contract: generator_instance.to_string(),
+        msg: to_binary(&GeneratorHookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(10),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_cny_eur_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Reward proxy of the pool is disabled!");
+}
+
+#[test]
+fn generator_move_to_proxy() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+    let reward_token_instance = instantiate_token(&mut app, token_code_id, "REWARD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    let proxy_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_proxy::execute,
+        mock_proxy::instantiate,
+        mock_proxy::query,
+    )));
+
+    let mut proxies = vec![];
+    for _ in 0..2 {
+        let msg = ProxyInstantiateMsg {
+            generator_contract_addr: generator_instance.to_string(),
+            pair_addr: "pair".to_string(),
+            lp_token_addr: lp_eur_usd_instance.to_string(),
+            reward_contract_addr: "reward".to_string(),
+            reward_token_addr: reward_token_instance.to_string(),
+        };
+        let proxy = app
+            .instantiate_contract(proxy_code_id, owner.clone(), &msg, &[], "Proxy", None)
+            .unwrap();
+        mint_tokens(&mut app, &reward_token_instance, &proxy, 1000);
+        proxies.push(proxy);
+    }
+
+    let msg = ExecuteMsg::SetAllowedRewardProxies {
+        proxies: vec![proxies[0].to_string()],
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        Some(&proxies[0]),
+        &[&lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_eur_usd_instance, &user1, 10);
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_eur_usd_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = ExecuteMsg::MoveToProxy {
+        lp_token: lp_eur_usd_instance.clone(),
+        new_proxy: proxies[1].to_string(),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Reward proxy not allowed!");
+
+    let allow_msg = ExecuteMsg::SetAllowedRewardProxies {
+        proxies: proxies.iter().map(|p| p.to_string()).collect(),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &allow_msg, &[])
+        .unwrap();
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_eur_usd_instance, &proxies[0], 0);
+    check_token_balance(&mut app, &lp_eur_usd_instance, &proxies[1], 10);
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "The pool already uses the reward proxy!");
+
+    // The rewards accumulated on the first proxy are kept for the user
+    let unpaid_msg = QueryMsg::UnpaidProxyRewards {
+        reward_proxy: proxies[0].clone(),
+        user: user1.clone(),
+    };
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &unpaid_msg)
+        .unwrap();
+    assert_eq!(res, Uint128::new(100));
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = ExecuteMsg::ClaimRewards {
+        lp_tokens: vec![lp_eur_usd_instance.to_string()],
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &reward_token_instance, &user1, 100);
+
+    let msg = ExecuteMsg::ClaimUnpaidProxyRewards {
+        reward_proxy: proxies[0].to_string(),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &reward_token_instance, &user1, 200);
+
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &unpaid_msg)
+        .unwrap();
+    assert_eq!(res, Uint128::zero());
+
+    // Detach the proxy, LP tokens are kept by the generator
+    let msg = ExecuteMsg::DetachProxy {
+        lp_token: lp_eur_usd_instance.clone(),
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_eur_usd_instance, &proxies[1], 0);
+    check_token_balance(&mut app, &lp_eur_usd_instance, &generator_instance, 10);
+
+    let res: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res[0].reward_proxy, None);
+
+    let err = app
+        .execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Pool doesn't have additional rewards!");
+
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_eur_usd_instance.clone(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_eur_usd_instance, &user1, 10);
+
+    let res: Uint128 = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::UnpaidProxyRewards {
+                reward_proxy: proxies[1].clone(),
+                user: user1.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res, Uint128::new(100));
+}
+
+#[test]
+fn transfer_position_and_claim_operators() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+    let operator = Addr::unchecked("operator");
+    let recipient = Addr::unchecked("recipient");
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_cny_eur_instance = instantiate_token(&mut app, token_code_id, "CNY-EUR", None);
+    let lp_eur_usd_instance = instantiate_token(&mut app, token_code_id, "EUR-USD", None);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        None,
+        &[&lp_cny_eur_instance, &lp_eur_usd_instance],
+    );
+
+    mint_tokens(&mut app, &lp_cny_eur_instance, &user1, 10);
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_cny_eur_instance, 10)],
+    );
+
+    app.update_block(|bi| next_block(bi));
+
+    let msg = ExecuteMsg::TransferPosition {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(4),
+        recipient: USER1.to_string(),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Can't transfer the position to the same account!"
+    );
+
+    let msg = ExecuteMsg::TransferPosition {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(11),
+        recipient: USER2.to_string(),
+    };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Insufficient balance in contract to process claim"
+    );
+
+    // The pending rewards are sent to the sender before the transfer
+    let msg = ExecuteMsg::TransferPosition {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(4),
+        recipient: USER2.to_string(),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &astro_token_instance, &user1, 5_000000);
+    check_token_balance(&mut app, &astro_token_instance, &user2, 0);
+
+    for (user, expected) in [(&user1, 6u128), (&user2, 4u128)] {
+        let msg = QueryMsg::Deposit {
+            lp_token: lp_cny_eur_instance.clone(),
+            user: user.clone(),
+        };
+        let res: Uint128 = app
+            .wrap()
+            .query_wasm_smart(&generator_instance, &msg)
+            .unwrap();
+        assert_eq!(res, Uint128::new(expected));
+    }
+
+    app.update_block(|bi| next_block(bi));
+
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER1,
+        (3_000000, None),
+    );
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (2_000000, None),
+    );
+
+    let claim_msg = ExecuteMsg::ClaimRewardsFor {
+        lp_tokens: vec![lp_cny_eur_instance.to_string()],
+        user: USER2.to_string(),
+    };
+    let err = app
+        .execute_contract(
+            operator.clone(),
+            generator_instance.clone(),
+            &claim_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let msg = ExecuteMsg::SetClaimOperator {
+        operator: operator.to_string(),
+        recipient: recipient.to_string(),
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: Vec<ClaimOperatorResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            &generator_instance,
+            &QueryMsg::ClaimOperators {
+                user: user2.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(
+        res,
+        vec![ClaimOperatorResponse {
+            operator: operator.clone(),
+            recipient: recipient.clone(),
+        }]
+    );
+
+    // The operator of another user can't claim
+    let err = app
+        .execute_contract(
+            operator.clone(),
+            generator_instance.clone(),
+            &ExecuteMsg::ClaimRewardsFor {
+                lp_tokens: vec![lp_cny_eur_instance.to_string()],
+                user: USER1.to_string(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(
+        operator.clone(),
+        generator_instance.clone(),
+        &claim_msg,
+        &[],
+    )
+    .unwrap();
+
+    check_token_balance(&mut app, &astro_token_instance, &recipient, 2_000000);
+    check_token_balance(&mut app, &astro_token_instance, &user2, 0);
+    check_pending_rewards(
+        &mut app,
+        &generator_instance,
+        &lp_cny_eur_instance,
+        USER2,
+        (0, None),
+    );
+
+    let msg = ExecuteMsg::RemoveClaimOperator {
+        operator: operator.to_string(),
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let err = app
+        .execute_contract(
+            operator.clone(),
+            generator_instance.clone(),
+            &claim_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    // The transferred deposit can be withdrawn by the recipient
+    let msg = ExecuteMsg::Withdraw {
+        lp_token: lp_cny_eur_instance.clone(),
+        amount: Uint128::new(4),
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    check_token_balance(&mut app, &lp_cny_eur_instance, &user2, 4);
+    check_token_balance(&mut app, &lp_cny_eur_instance, &generator_instance, 6);
+}
+
+#[test]
+fn query_deposit_snapshots() {
+    let mut app = mock_app();
+
+    let user1 = Addr::unchecked(USER1);
+    let user2 = Addr::unchecked(USER2);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let lp_token = instantiate_token(&mut app, token_code_id, "LP", None);
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+
+    let generator_instance = instantiate_generator(&mut app, &astro_token_instance);
+    let start_height = app.block_info().height;
+
+    register_lp_tokens_in_generator(&mut app, &generator_instance, None, &[&lp_token]);
+
+    mint_tokens(&mut app, &lp_token, &user1, 100);
+    mint_tokens(&mut app, &lp_token, &user2, 100);
+
+    let query_deposit = |app: &App, user: &Addr, height: u64| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(
+                &generator_instance,
+                &GeneratorQueryMsg::DepositAt {
+                    lp_token: lp_token.clone(),
+                    user: user.clone(),
+                    height,
+                },
+            )
+            .unwrap()
+    };
+    let query_total = |app: &App, height: u64| -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(
+                &generator_instance,
+                &GeneratorQueryMsg::TotalDepositAt {
+                    lp_token: lp_token.clone(),
+                    height,
+                },
+            )
+            .unwrap()
+    };
+
+    // Heights before the generator was instantiated are not tracked
+    let err = app
+        .wrap()
+        .query_wasm_smart::<Uint128>(
+            &generator_instance,
+            &GeneratorQueryMsg::TotalDepositAt {
+                lp_token: lp_token.clone(),
+                height: start_height - 1,
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains(&format!(
+        "Deposits are tracked since the block {}!",
+        start_height
+    )));
+
+    app.update_block(|bi| next_block(bi));
+    let first_height = app.block_info().height;
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_token, 50)]);
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER2, &[(&lp_token, 30)]);
+
+    app.update_block(|bi| next_block(bi));
+    let second_height = app.block_info().height;
+    deposit_lp_tokens_to_generator(&mut app, &generator_instance, USER1, &[(&lp_token, 20)]);
+
+    let msg = GeneratorExecuteMsg::Withdraw {
+        lp_token: lp_token.clone(),
+        amount: Uint128::new(10),
+    };
+    app.execute_contract(user2.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    app.update_block(|bi| next_block(bi));
+    let third_height = app.block_info().height;
+    let msg = GeneratorExecuteMsg::EmergencyWithdraw {
+        lp_token: lp_token.clone(),
+    };
+    app.execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    app.update_block(|bi| next_block(bi));
+    let fourth_height = app.block_info().height;
+
+    // The snapshot at a height holds the deposits at the beginning of the block
+    assert_eq!(query_deposit(&app, &user1, start_height), Uint128::zero());
+    assert_eq!(query_total(&app, start_height), Uint128::zero());
+
+    assert_eq!(query_deposit(&app, &user1, first_height), Uint128::zero());
+    assert_eq!(query_deposit(&app, &user2, first_height), Uint128::zero());
+    assert_eq!(query_total(&app, first_height), Uint128::zero());
+
+    assert_eq!(query_deposit(&app, &user1, second_height), Uint128::new(50));
+    assert_eq!(query_deposit(&app, &user2, second_height), Uint128::new(30));
+    assert_eq!(query_total(&app, second_height), Uint128::new(80));
+
+    assert_eq!(query_deposit(&app, &user1, third_height), Uint128::new(70));
+    assert_eq!(query_deposit(&app, &user2, third_height), Uint128::new(20));
+    assert_eq!(query_total(&app, third_height), Uint128::new(90));
+
+    assert_eq!(query_deposit(&app, &user1, fourth_height), Uint128::zero());
+    assert_eq!(query_deposit(&app, &user2, fourth_height), Uint128::new(20));
+    assert_eq!(query_total(&app, fourth_height), Uint128::new(20));
+
+    // The snapshots of a new generator don't need to be seeded
+    let msg = GeneratorExecuteMsg::SeedSnapshots { limit: None };
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    let err = app
+        .execute_contract(
+            Addr::unchecked(OWNER),
+            generator_instance.clone(),
+            &msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Deposit snapshots are already seeded!");
+}
+
+#[test]
+fn query_pool_apr() {
+    let mut app = mock_app();
+
+    let owner = Addr::unchecked(OWNER);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(&mut app);
+
+    let astro_token_instance =
+        instantiate_token(&mut app, token_code_id, "ASTRO", Some(1_000_000_000_000000));
+    let tkn_token_instance = instantiate_token(&mut app, token_code_id, "TKN", None);
+    let reward_token_instance = instantiate_token(&mut app, token_code_id, "REWARD", None);
+
+    let generator_instance = instantiate_generator_with_emissions(
+        &mut app,
+        &astro_token_instance,
+        Some(Uint128::new(1_000000)),
+    );
+
+    // The pair holds 500k UST and 250k TKN for 1m LP tokens
+    let pair_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_pair::execute,
+        mock_pair::instantiate,
+        mock_pair::query,
+    )));
+    let pool = PoolResponse {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::new(500_000_000000),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: tkn_token_instance.clone(),
+                },
+                amount: Uint128::new(250_000_000000),
+            },
+        ],
+        total_share: Uint128::new(1_000_000_000000),
+    };
+    let pair_instance = app
+        .instantiate_contract(pair_code_id, owner.clone(), &pool, &[], "Pair", None)
+        .unwrap();
+
+    let msg = TokenInstantiateMsg {
+        name: "UST-TKN".to_string(),
+        symbol: "UST-TKN".to_string(),
+        decimals: 6,
+        initial_balances: vec![Cw20Coin {
+            address: user1.to_string(),
+            amount: Uint128::new(100_000_000000),
+        }],
+        mint: Some(MinterResponse {
+            minter: pair_instance.to_string(),
+            cap: None,
+        }),
+    };
+    let lp_ust_tkn_instance = app
+        .instantiate_contract(token_code_id, owner.clone(), &msg, &[], "UST-TKN", None)
+        .unwrap();
+
+    let proxy_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_proxy::execute,
+        mock_proxy::instantiate,
+        mock_proxy::query,
+    )));
+    let msg = ProxyInstantiateMsg {
+        generator_contract_addr: generator_instance.to_string(),
+        pair_addr: pair_instance.to_string(),
+        lp_token_addr: lp_ust_tkn_instance.to_string(),
+        reward_contract_addr: "reward".to_string(),
+        reward_token_addr: reward_token_instance.to_string(),
+    };
+    let proxy = app
+        .instantiate_contract(proxy_code_id, owner.clone(), &msg, &[], "Proxy", None)
+        .unwrap();
+
+    let msg = ExecuteMsg::SetAllowedRewardProxies {
+        proxies: vec![proxy.to_string()],
+    };
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    register_lp_tokens_in_generator(
+        &mut app,
+        &generator_instance,
+        Some(&proxy),
+        &[&lp_ust_tkn_instance],
+    );
+
+    deposit_lp_tokens_to_generator(
+        &mut app,
+        &generator_instance,
+        USER1,
+        &[(&lp_ust_tkn_instance, 100_000_000000)],
+    );
+
+    // The mock proxy accrues 100 reward tokens since the last update of the pool
+    app.update_block(|bi| {
+        bi.height += 1;
+        bi.time = bi.time.plus_seconds(100);
+    });
+
+    let apr_query = QueryMsg::PoolApr {
+        lp_token: lp_ust_tkn_instance.clone(),
+    };
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<PoolAprResponse>(&generator_instance, &apr_query)
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Price source for uusd is not set!"));
+
+    let oracle_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_oracle::execute,
+        mock_oracle::instantiate,
+        mock_oracle::query,
+    )));
+    let oracle_instance = app
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &Decimal::percent(50),
+            &[],
+            "Oracle",
+            None,
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::SetPriceSources {
+        sources: vec![
+            (
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                PriceSource::Fixed {
+                    price: Decimal::one(),
+                },
+            ),
+            (
+                AssetInfo::Token {
+                    contract_addr: tkn_token_instance.clone(),
+                },
+                PriceSource::Fixed {
+                    price: Decimal::from_ratio(2u128, 1u128),
+                },
+            ),
+            (
+                AssetInfo::Token {
+                    contract_addr: astro_token_instance.clone(),
+                },
+                PriceSource::Oracle {
+                    contract: oracle_instance.clone(),
+                },
+            ),
+            (
+                AssetInfo::Token {
+                    contract_addr: reward_token_instance.clone(),
+                },
+                PriceSource::Fixed {
+                    price: Decimal::one(),
+                },
+            ),
+        ],
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(owner.clone(), generator_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.price_sources.len(), 4);
+
+    // The user stakes a tenth of the pool worth 1m UST, the pool receives 1 ASTRO per second
+    // priced at 0.5 UST, and the proxy accrues 100 reward tokens per 100 seconds
+    let res: PoolAprResponse = app
+        .wrap()
+        .query_wasm_smart(&generator_instance, &apr_query)
+        .unwrap();
+    assert_eq!(
+        res,
+        PoolAprResponse {
+            tvl: Uint128::new(100_000_000000),
+            astro_per_year: Uint128::new(31_536_000_000000),
+            reward_apr: Decimal::from_ratio(15768u128, 100u128),
+            proxy_apr: Some(Decimal::from_ratio(31536u128, 100_000_000u128)),
+        }
+    );
+}
+
+fn mock_app() -> App {
+    let api = MockApi::default();
+    let env = mock_env();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let terra_mock_querier = TerraMockQuerier::new(MockQuerier::new(&[]));
+
+    App::new(api, env.block, bank, storage, terra_mock_querier)
+}
+
+fn store_token_code(app: &mut App) -> u64 {
+    let astro_token_contract = Box::new(ContractWrapper::new(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+
+    app.store_code(astro_token_contract)
+}
+
+fn instantiate_token(app: &mut App, token_code_id: u64, name: &str, cap: Option<u128>) -> Addr {
+    let name = String::from(name);
+
+    let msg = TokenInstantiateMsg {
+        name: name.clone(),
+        symbol: name.clone(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: cap.map(|v| Uint128::from(v)),
+        }),
+    };
+
+    app.instantiate_contract(token_code_id, Addr::unchecked(OWNER), &msg, &[], name, None)
+        .unwrap()
+}
+
+fn instantiate_generator(app: &mut App, astro_token_instance: &Addr) -> Addr {
+    instantiate_generator_with_emissions(app, astro_token_instance, None)
+}
+
+fn instantiate_generator_with_emissions(
+    mut app: &mut App,
+    astro_token_instance: &Addr,
+    tokens_per_second: Option<Uint128>,
+) -> Addr {
+    // Vesting
+    let vesting_contract = Box::new(ContractWrapper::new(
+        astroport_vesting::contract::execute,
+        astroport_vesting::contract::instantiate,
+        astroport_vesting::contract::query,
+    ));
+    let owner = Addr::unchecked(OWNER);
+    let vesting_code_id = app.store_code(vesting_contract);
+
+    let init_msg = VestingInstantiateMsg {
+        token_addr: astro_token_instance.to_string(),
+    };
 
     let vesting_instance = app
         .instantiate_contract(
@@ -1286,16 +4185,7 @@ fn instantiate_generator(mut app: &mut App, astro_token_instance: &Addr) -> Addr
     );
 
     // Generator
-    let generator_contract = Box::new(
-        ContractWrapper::new(
-            astroport_generator::contract::execute,
-            astroport_generator::contract::instantiate,
-            astroport_generator::contract::query,
-        )
-        .with_reply(astroport_generator::contract::reply),
-    );
-
-    let generator_code_id = app.store_code(generator_contract);
+    let generator_code_id = store_generator_code(&mut app);
 
     let init_msg = GeneratorInstantiateMsg {
         owner: owner.to_string(),
@@ -1304,6 +4194,8 @@ fn instantiate_generator(mut app: &mut App, astro_token_instance: &Addr) -> Addr
         astro_token: astro_token_instance.to_string(),
         tokens_per_block: Uint128::new(10_000000),
         vesting_contract: vesting_instance.to_string(),
+        tokens_per_second,
+        start_time: None,
     };
 
     let generator_instance = app
@@ -1313,7 +4205,7 @@ fn instantiate_generator(mut app: &mut App, astro_token_instance: &Addr) -> Addr
             &init_msg,
             &[],
             "Guage",
-            None,
+            Some(OWNER.to_string()),
         )
         .unwrap();
 
@@ -1402,6 +4294,20 @@ fn instantiate_mirror_protocol(
     (mirror_token_instance, mirror_staking_instance)
 }
 
+fn store_generator_code(app: &mut App) -> u64 {
+    let generator_contract = Box::new(
+        ContractWrapper::new(
+            astroport_generator::contract::execute,
+            astroport_generator::contract::instantiate,
+            astroport_generator::contract::query,
+        )
+        .with_reply(astroport_generator::contract::reply)
+        .with_migrate(astroport_generator::contract::migrate),
+    );
+
+    app.store_code(generator_contract)
+}
+
 fn store_proxy_code(app: &mut App) -> u64 {
     let generator_proxy_to_mirror_contract = Box::new(ContractWrapper::new(
         astroport_generator_proxy_to_mirror::contract::execute,
@@ -1522,3 +4428,265 @@ fn check_pending_rewards(
         )
     );
 }
+
+fn check_pending_stream_rewards(
+    app: &mut App,
+    generator_instance: &Addr,
+    token: &Addr,
+    depositor: &str,
+    reward_token: &Addr,
+    expected: u128,
+) {
+    let msg = GeneratorQueryMsg::PendingToken {
+        lp_token: token.to_owned(),
+        user: Addr::unchecked(depositor),
+    };
+
+    let res: PendingTokenResponse = app
+        .wrap()
+        .query_wasm_smart(generator_instance.to_owned(), &msg)
+        .unwrap();
+    assert_eq!(
+        res.pending_on_streams,
+        vec![Asset {
+            info: AssetInfo::Token {
+                contract_addr: reward_token.to_owned(),
+            },
+            amount: Uint128::from(expected),
+        }]
+    );
+}
+
+fn query_disabled_proxies(app: &mut App, generator_instance: &Addr) -> Vec<bool> {
+    let res: Vec<PoolInfoResponse> = app
+        .wrap()
+        .query_wasm_smart(
+            generator_instance,
+            &QueryMsg::Pools {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+
+    res.into_iter().map(|p| p.reward_proxy_disabled).collect()
+}
+
+/// A reward proxy which can fail to update or to send rewards, to withdraw or to report its deposit and rewards
+mod mock_proxy {
+    use astroport::generator_proxy::{ExecuteMsg, InstantiateMsg, QueryMsg};
+    use cosmwasm_std::{
+        to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+        StdResult, Uint128, WasmMsg,
+    };
+    use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+    use cw_storage_plus::Item;
+
+    const LP_TOKEN: Item<Addr> = Item::new("lp_token");
+    const REWARD_TOKEN: Item<Addr> = Item::new("reward_token");
+    const REWARDS: Item<Uint128> = Item::new("rewards");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        LP_TOKEN.save(deps.storage, &Addr::unchecked(msg.lp_token_addr))?;
+        REWARD_TOKEN.save(deps.storage, &Addr::unchecked(msg.reward_token_addr))?;
+        REWARDS.save(deps.storage, &Uint128::zero())?;
+
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        execute_with_failures(deps, msg, false, false, false)
+    }
+
+    pub fn execute_broken_update(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        execute_with_failures(deps, msg, true, true, false)
+    }
+
+    pub fn execute_broken_payout(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        execute_with_failures(deps, msg, false, true, false)
+    }
+
+    pub fn execute_broken_withdraw(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: ExecuteMsg,
+    ) -> StdResult<Response> {
+        execute_with_failures(deps, msg, false, false, true)
+    }
+
+    fn execute_with_failures(
+        deps: DepsMut,
+        msg: ExecuteMsg,
+        broken_update: bool,
+        broken_payout: bool,
+        broken_withdraw: bool,
+    ) -> StdResult<Response> {
+        match msg {
+            ExecuteMsg::Receive(_) => Ok(Response::new()),
+            ExecuteMsg::UpdateRewards {} => {
+                if broken_update {
+                    return Err(StdError::generic_err("Rewards are unavailable"));
+                }
+
+                REWARDS.update::<_, StdError>(deps.storage, |v| Ok(v + Uint128::new(100)))?;
+                Ok(Response::new())
+            }
+            ExecuteMsg::SendRewards { account, amount } => {
+                if broken_payout {
+                    return Err(StdError::generic_err("Reward transfer failed"));
+                }
+
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: REWARD_TOKEN.load(deps.storage)?.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: account.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                }))
+            }
+            ExecuteMsg::Withdraw { account, amount }
+            | ExecuteMsg::EmergencyWithdraw { account, amount } => {
+                if broken_withdraw {
+                    return Err(StdError::generic_err("Withdrawal failed"));
+                }
+
+                Ok(Response::new().add_message(WasmMsg::Execute {
+                    contract_addr: LP_TOKEN.load(deps.storage)?.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                        recipient: account.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                }))
+            }
+            ExecuteMsg::Callback(_) => Err(StdError::generic_err("Unsupported")),
+        }
+    }
+
+    pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Deposit {} => {
+                let res: BalanceResponse = deps.querier.query_wasm_smart(
+                    LP_TOKEN.load(deps.storage)?,
+                    &Cw20QueryMsg::Balance {
+                        address: env.contract.address.to_string(),
+                    },
+                )?;
+                to_binary(&res.balance)
+            }
+            QueryMsg::Reward {} => to_binary(&REWARDS.load(deps.storage)?),
+            QueryMsg::PendingToken {} => to_binary(&Uint128::new(100)),
+            QueryMsg::RewardInfo {} => to_binary(&REWARD_TOKEN.load(deps.storage)?),
+            _ => Err(StdError::generic_err("Unsupported")),
+        }
+    }
+
+    pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> StdResult<Response> {
+        Ok(Response::new())
+    }
+
+    pub fn query_broken(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Deposit {} | QueryMsg::Reward {} | QueryMsg::PendingToken {} => {
+                Err(StdError::generic_err("Proxy is unavailable"))
+            }
+            _ => query(deps, env, msg),
+        }
+    }
+}
+
+/// A pair which only returns the configured pool
+mod mock_pair {
+    use astroport::pair::{PoolResponse, QueryMsg};
+    use cosmwasm_std::{
+        to_binary, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError, StdResult,
+    };
+    use cw_storage_plus::Item;
+
+    const POOL: Item<PoolResponse> = Item::new("pool");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: PoolResponse,
+    ) -> StdResult<Response> {
+        POOL.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("Unsupported"))
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Pool {} => to_binary(&POOL.load(deps.storage)?),
+            _ => Err(StdError::generic_err("Unsupported")),
+        }
+    }
+}
+
+/// An oracle which values any amount at the configured price
+mod mock_oracle {
+    use astroport::oracle::QueryMsg;
+    use cosmwasm_std::{
+        to_binary, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdError,
+        StdResult,
+    };
+    use cw_storage_plus::Item;
+
+    const PRICE: Item<Decimal> = Item::new("price");
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: Decimal,
+    ) -> StdResult<Response> {
+        PRICE.save(deps.storage, &msg)?;
+        Ok(Response::new())
+    }
+
+    pub fn execute(
+        _deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        _msg: Empty,
+    ) -> StdResult<Response> {
+        Err(StdError::generic_err("Unsupported"))
+    }
+
+    pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+        match msg {
+            QueryMsg::Consult { amount, .. } => to_binary(&(amount * PRICE.load(deps.storage)?)),
+        }
+    }
+}
@@ -0,0 +1,467 @@
+use cosmwasm_std::{
+    entry_point, from_binary, to_binary, to_vec, Addr, Binary, ContractResult, CosmosMsg, Deps,
+    DepsMut, Empty, Env, MessageInfo, QueryRequest, Response, StdError, StdResult, SubMsg,
+    SystemResult, Uint128, WasmMsg, WasmQuery,
+};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+
+use crate::error::ContractError;
+use crate::state::{Config, CONFIG};
+use crate::template::{extract_amount, render};
+use astroport::asset::addr_validate_to_lower;
+use astroport::generator_proxy::{CallbackMsg, Cw20HookMsg, ExecuteMsg, MigrateMsg, QueryMsg};
+use astroport::generator_proxy_generic::{ConfigResponse, InstantiateMsg};
+use cw2::set_contract_version;
+
+/// Contract name that is used for migration.
+const CONTRACT_NAME: &str = "astroport-generator-proxy-generic";
+/// Contract version that is used for migration.
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// ## Description
+/// Creates a new contract with the specified parameters in the [`InstantiateMsg`].
+/// Each message template is checked to render into a valid JSON object.
+/// Returns the default object of type [`Response`] if the operation was successful,
+/// or a [`ContractError`] if the contract was not created.
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **_info** is the object of type [`MessageInfo`].
+/// * **msg** is a message of type [`InstantiateMsg`] which contains the basic settings for creating a contract
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let config = Config {
+        generator_contract_addr: addr_validate_to_lower(deps.api, &msg.generator_contract_addr)?,
+        pair_addr: addr_validate_to_lower(deps.api, &msg.pair_addr)?,
+        lp_token_addr: addr_validate_to_lower(deps.api, &msg.lp_token_addr)?,
+        reward_contract_addr: addr_validate_to_lower(deps.api, &msg.reward_contract_addr)?,
+        reward_token_addr: addr_validate_to_lower(deps.api, &msg.reward_token_addr)?,
+        templates: msg.templates,
+    };
+
+    let templates = &config.templates;
+    for (name, template) in [
+        ("deposit", &templates.deposit),
+        ("withdraw", &templates.withdraw),
+        ("claim", &templates.claim),
+        ("reward_info", &templates.reward_info),
+    ] {
+        render(template, &config, &env.contract.address, Uint128::zero())
+            .map_err(|_| ContractError::InvalidTemplate(name.to_string()))?;
+    }
+    if !templates.withdraw.contains("{amount}") {
+        return Err(ContractError::MissingAmountPlaceholder {});
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::default())
+}
+
+/// ## Description
+/// Available the execute messages of the contract.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **_info** is the object of type [`MessageInfo`].
+///
+/// * **msg** is the object of type [`ExecuteMsg`].
+///
+/// ## Queries
+/// * **ExecuteMsg::Receive(msg)** Receives a message of type [`Cw20ReceiveMsg`] and processes
+/// it depending on the received template.
+///
+/// * **ExecuteMsg::UpdateRewards {}** Withdrawal pending rewards.
+///
+/// * **ExecuteMsg::SendRewards { account, amount }** Sends rewards to the recipient.
+///
+/// * **ExecuteMsg::Withdraw { account, amount }** Withdrawal the rewards.
+///
+/// * **ExecuteMsg::EmergencyWithdraw { account, amount }** Withdrawal the rewards.
+///
+/// * **ExecuteMsg::Callback(msg)** Handles the callbacks describes in the [`CallbackMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::UpdateRewards {} => update_rewards(deps, env),
+        ExecuteMsg::SendRewards { account, amount } => send_rewards(deps, info, account, amount),
+        ExecuteMsg::Withdraw { account, amount } => withdraw(deps, env, info, account, amount),
+        ExecuteMsg::EmergencyWithdraw { account, amount } => {
+            withdraw(deps, env, info, account, amount)
+        }
+        ExecuteMsg::Callback(msg) => handle_callback(deps, env, info, msg),
+    }
+}
+
+/// ## Description
+/// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received template.
+/// If the template is not found in the received message, then an [`ContractError`] is returned,
+/// otherwise returns the [`Response`] with the specified attributes if the operation was successful
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **cw20_msg** is the object of type [`Cw20ReceiveMsg`].
+fn receive_cw20(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    let cfg = CONFIG.load(deps.storage)?;
+
+    if let Ok(Cw20HookMsg::Deposit {}) = from_binary(&cw20_msg.msg) {
+        if cw20_msg.sender != cfg.generator_contract_addr || info.sender != cfg.lp_token_addr {
+            return Err(ContractError::Unauthorized {});
+        }
+        response
+            .messages
+            .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: cfg.lp_token_addr.to_string(),
+                funds: vec![],
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: cfg.reward_contract_addr.to_string(),
+                    amount: cw20_msg.amount,
+                    msg: render(
+                        &cfg.templates.deposit,
+                        &cfg,
+                        &env.contract.address,
+                        cw20_msg.amount,
+                    )?,
+                })?,
+            })));
+    } else {
+        return Err(ContractError::IncorrectCw20HookMessageVariant {});
+    }
+    Ok(response)
+}
+
+/// ## Description
+/// Withdraw pending rewards. Returns an [`ContractError`] on failure,
+/// otherwise returns the [`Response`] object with the specified submessages.
+///
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+fn update_rewards(deps: DepsMut, env: Env) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    let cfg = CONFIG.load(deps.storage)?;
+
+    response
+        .messages
+        .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cfg.reward_contract_addr.to_string(),
+            funds: vec![],
+            msg: render(
+                &cfg.templates.claim,
+                &cfg,
+                &env.contract.address,
+                Uint128::zero(),
+            )?,
+        })));
+
+    Ok(response)
+}
+
+/// ## Description
+/// Sends rewards to the recipient. Returns an [`ContractError`] on failure,
+/// otherwise returns the [`Response`] object with the specified submessages.
+///
+/// ## Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **account** is the object of type [`Addr`]. Sets the recipient of rewards.
+///
+/// * **amount** is the object of type [`Uint128`].
+///
+/// ## Executor
+/// Only the contract of generator can execute it
+fn send_rewards(
+    deps: DepsMut,
+    info: MessageInfo,
+    account: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.generator_contract_addr {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    response
+        .messages
+        .push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: cfg.reward_token_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: account.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })));
+    Ok(response)
+}
+
+/// # Description
+/// Withdrawal the rewards. Returns an [`ContractError`] on
+/// failure, otherwise returns the [`Response`] object with the specified submessages if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **account** is the object of type [`Addr`]. Sets the recipient for withdrawal.
+///
+/// * **amount** is the object of type [`Uint128`].
+///
+/// ## Executor
+/// Only the contract of generator can execute it
+fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    account: Addr,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    let cfg = CONFIG.load(deps.storage)?;
+    if info.sender != cfg.generator_contract_addr {
+        return Err(ContractError::Unauthorized {});
+    };
+
+    let prev_lp_balance = {
+        let res: BalanceResponse = deps.querier.query_wasm_smart(
+            &cfg.lp_token_addr,
+            &Cw20QueryMsg::Balance {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        res.balance
+    };
+
+    // withdraw from the end reward contract
+    response.messages.push(SubMsg::new(WasmMsg::Execute {
+        contract_addr: cfg.reward_contract_addr.to_string(),
+        funds: vec![],
+        msg: render(&cfg.templates.withdraw, &cfg, &env.contract.address, amount)?,
+    }));
+
+    response.messages.push(SubMsg::new(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::Callback(
+            CallbackMsg::TransferLpTokensAfterWithdraw {
+                account,
+                prev_lp_balance,
+            },
+        ))?,
+    }));
+
+    Ok(response)
+}
+
+/// # Description
+/// Handle the callbacks describes in the [`CallbackMsg`]. Returns an [`ContractError`] on failure, otherwise returns the [`Response`]
+/// object with the specified submessages if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **info** is the object of type [`MessageInfo`].
+///
+/// * **msg** is the object of type [`CallbackMsg`]. Sets the callback action.
+///
+/// ## Executor
+/// Callback functions can only be called this contract itself
+pub fn handle_callback(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: CallbackMsg,
+) -> Result<Response, ContractError> {
+    // Callback functions can only be called this contract itself
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+    match msg {
+        CallbackMsg::TransferLpTokensAfterWithdraw {
+            account,
+            prev_lp_balance,
+        } => transfer_lp_tokens_after_withdraw(deps, env, account, prev_lp_balance),
+    }
+}
+
+/// # Description
+/// Transfers lp tokens after withdrawal to the recipient. Returns an [`ContractError`] on failure,
+/// otherwise returns the [`Response`] object with the specified submessages if the operation was successful.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **account** is the object of type [`MessageInfo`]. Sets the recipient of withdrawal.
+///
+/// * **prev_lp_balance** is the object of type [`CallbackMsg`]. Sets the previous balance for
+/// calculating the withdrawal amount.
+pub fn transfer_lp_tokens_after_withdraw(
+    deps: DepsMut,
+    env: Env,
+    account: Addr,
+    prev_lp_balance: Uint128,
+) -> Result<Response, ContractError> {
+    let cfg = CONFIG.load(deps.storage)?;
+
+    let amount = {
+        let res: BalanceResponse = deps.querier.query_wasm_smart(
+            &cfg.lp_token_addr,
+            &Cw20QueryMsg::Balance {
+                address: env.contract.address.to_string(),
+            },
+        )?;
+        res.balance - prev_lp_balance
+    };
+
+    Ok(Response::new().add_message(WasmMsg::Execute {
+        contract_addr: cfg.lp_token_addr.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: account.to_string(),
+            amount,
+        })?,
+    }))
+}
+
+/// # Description
+/// Describes all query messages.
+/// # Params
+/// * **deps** is the object of type [`DepsMut`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **msg** is the object of type [`QueryMsg`].
+///
+/// ## Queries
+/// * **QueryMsg::Deposit {}** returns the deposit amount.
+///
+/// * **QueryMsg::Reward {}** returns the balance of reward token.
+///
+/// * **QueryMsg::PendingToken {}** returns the pending rewards.
+///
+/// * **QueryMsg::RewardInfo {}** returns the reward token contract address.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    let cfg = CONFIG.load(deps.storage)?;
+    match msg {
+        QueryMsg::Config {} => to_binary(&ConfigResponse {
+            generator_contract_addr: cfg.generator_contract_addr.to_string(),
+            pair_addr: cfg.pair_addr.to_string(),
+            lp_token_addr: cfg.lp_token_addr.to_string(),
+            reward_contract_addr: cfg.reward_contract_addr.to_string(),
+            reward_token_addr: cfg.reward_token_addr.to_string(),
+            templates: cfg.templates,
+        }),
+        QueryMsg::Deposit {} => {
+            let deposit_amount = query_reward_info(deps, &env, &cfg, &cfg.templates.deposit_path)?;
+
+            to_binary(&deposit_amount)
+        }
+        QueryMsg::Reward {} => {
+            let res: Result<BalanceResponse, StdError> = deps.querier.query_wasm_smart(
+                cfg.reward_token_addr,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.into_string(),
+                },
+            );
+            let reward_amount = res?.balance;
+
+            to_binary(&reward_amount)
+        }
+        QueryMsg::PendingToken {} => {
+            let pending_reward =
+                query_reward_info(deps, &env, &cfg, &cfg.templates.pending_reward_path)?;
+
+            to_binary(&pending_reward)
+        }
+        QueryMsg::RewardInfo {} => to_binary(&cfg.reward_token_addr),
+    }
+}
+
+/// ## Description
+/// Sends the reward info query of the template to the reward contract and returns the amount
+/// at the given path of the response.
+/// ## Params
+/// * **deps** is the object of type [`Deps`].
+///
+/// * **env** is the object of type [`Env`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **path** is the object of type [`str`]. This is the path to the amount in the query response.
+fn query_reward_info(deps: Deps, env: &Env, cfg: &Config, path: &str) -> StdResult<Uint128> {
+    let request: QueryRequest<Empty> = QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: cfg.reward_contract_addr.to_string(),
+        msg: render(
+            &cfg.templates.reward_info,
+            cfg,
+            &env.contract.address,
+            Uint128::zero(),
+        )?,
+    });
+
+    let response = match deps.querier.raw_query(&to_vec(&request)?) {
+        SystemResult::Err(system_err) => {
+            return Err(StdError::generic_err(format!(
+                "Querier system error: {}",
+                system_err
+            )))
+        }
+        SystemResult::Ok(ContractResult::Err(contract_err)) => {
+            return Err(StdError::generic_err(format!(
+                "Querier contract error: {}",
+                contract_err
+            )))
+        }
+        SystemResult::Ok(ContractResult::Ok(value)) => value,
+    };
+
+    extract_amount(response.as_slice(), path)
+}
+
+/// ## Description
+/// Used for migration of contract. Returns the default object of type [`Response`].
+/// ## Params
+/// * **_deps** is the object of type [`DepsMut`].
+///
+/// * **_env** is the object of type [`Env`].
+///
+/// * **_msg** is the object of type [`MigrateMsg`].
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    Ok(Response::default())
+}
@@ -0,0 +1,22 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+/// ## Description
+/// This enum describes errors for generator_proxy_generic contract!
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Incorrect CW20 hook message variant!")]
+    IncorrectCw20HookMessageVariant {},
+
+    #[error("The {0} template is not a valid JSON object!")]
+    InvalidTemplate(String),
+
+    #[error("The withdraw template must contain the {{amount}} placeholder!")]
+    MissingAmountPlaceholder {},
+}
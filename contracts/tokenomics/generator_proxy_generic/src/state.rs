@@ -0,0 +1,28 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use astroport::generator_proxy_generic::MessageTemplates;
+use cosmwasm_std::Addr;
+use cw_storage_plus::Item;
+
+/// ## Description
+/// This structure describes the main controls configs of generator_proxy_generic contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// the generator contract address
+    pub generator_contract_addr: Addr,
+    /// the pair contract address
+    pub pair_addr: Addr,
+    /// the contract address for liquidity pool token
+    pub lp_token_addr: Addr,
+    /// the reward contract address
+    pub reward_contract_addr: Addr,
+    /// the reward token contract address
+    pub reward_token_addr: Addr,
+    /// the templates of the messages of the reward contract
+    pub templates: MessageTemplates,
+}
+
+/// ## Description
+/// Stores config at the given key
+pub const CONFIG: Item<Config> = Item::new("config");
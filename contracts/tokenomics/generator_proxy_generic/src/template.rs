@@ -0,0 +1,234 @@
+use cosmwasm_std::{from_slice, Addr, Binary, StdError, StdResult, Uint128};
+use serde::de::IgnoredAny;
+
+use crate::state::Config;
+
+/// ## Description
+/// Replaces the placeholders of the template with the given amount and the addresses from the config.
+/// Returns an [`StdError`] if the result is not a valid JSON object, otherwise returns the message in [`Binary`].
+/// ## Params
+/// * **template** is the object of type [`str`].
+///
+/// * **cfg** is the object of type [`Config`].
+///
+/// * **proxy** is the object of type [`Addr`]. This is the address of the proxy contract.
+///
+/// * **amount** is the object of type [`Uint128`].
+pub fn render(template: &str, cfg: &Config, proxy: &Addr, amount: Uint128) -> StdResult<Binary> {
+    let msg = template
+        .replace("{amount}", &amount.to_string())
+        .replace("{proxy}", proxy.as_str())
+        .replace("{pair}", cfg.pair_addr.as_str())
+        .replace("{lp_token}", cfg.lp_token_addr.as_str())
+        .replace("{reward_token}", cfg.reward_token_addr.as_str());
+
+    if !msg.trim_start().starts_with('{') {
+        return Err(StdError::parse_err(
+            "MessageTemplate",
+            "expected a JSON object",
+        ));
+    }
+    from_slice::<IgnoredAny>(msg.as_bytes())?;
+
+    Ok(Binary::from(msg.into_bytes()))
+}
+
+/// ## Description
+/// Returns the amount at the dot-separated path in the JSON document. Object keys are matched by name and
+/// array elements by index. A missing value or `null` is treated as zero. Returns an [`StdError`] if the
+/// document is malformed or the value is not an integer.
+/// ## Params
+/// * **json** is the object of type [`u8`] slice.
+///
+/// * **path** is the object of type [`str`].
+pub fn extract_amount(json: &[u8], path: &str) -> StdResult<Uint128> {
+    let mut cursor = Cursor { data: json, pos: 0 };
+
+    let value = match cursor.find(path)? {
+        Some(value) if value != b"null" => value,
+        _ => return Ok(Uint128::zero()),
+    };
+
+    std::str::from_utf8(value)
+        .ok()
+        .and_then(|value| value.parse::<u128>().ok())
+        .map(Uint128::new)
+        .ok_or_else(|| StdError::parse_err("Uint128", format!("invalid amount at {}", path)))
+}
+
+/// ## Description
+/// A minimal reader of JSON documents which walks to a value without deserializing the rest.
+struct Cursor<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn find(&mut self, path: &str) -> StdResult<Option<&'a [u8]>> {
+        'segments: for segment in path.split('.') {
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b'{') => {
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.peek() == Some(b'}') {
+                        return Ok(None);
+                    }
+                    loop {
+                        self.skip_whitespace();
+                        let key = self.read_string()?;
+                        self.skip_whitespace();
+                        self.expect(b':')?;
+                        if key == segment.as_bytes() {
+                            continue 'segments;
+                        }
+                        self.skip_value()?;
+                        if !self.next_item(b'}')? {
+                            return Ok(None);
+                        }
+                    }
+                }
+                Some(b'[') => {
+                    let index = match segment.parse::<usize>() {
+                        Ok(index) => index,
+                        Err(_) => return Ok(None),
+                    };
+                    self.pos += 1;
+                    self.skip_whitespace();
+                    if self.peek() == Some(b']') {
+                        return Ok(None);
+                    }
+                    for _ in 0..index {
+                        self.skip_value()?;
+                        if !self.next_item(b']')? {
+                            return Ok(None);
+                        }
+                    }
+                }
+                _ => return Ok(None),
+            }
+        }
+
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => self.read_string().map(Some),
+            Some(b'{') | Some(b'[') | None => Ok(None),
+            Some(_) => Ok(Some(self.read_scalar())),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.data.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\n') | Some(b'\r') | Some(b'\t') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> StdResult<()> {
+        if self.peek() != Some(byte) {
+            return Err(self.error());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Moves past the separator after an item of an object or an array.
+    /// Returns `false` if the container is closed with the `close` byte.
+    fn next_item(&mut self, close: u8) -> StdResult<bool> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(true)
+            }
+            Some(byte) if byte == close => {
+                self.pos += 1;
+                Ok(false)
+            }
+            _ => Err(self.error()),
+        }
+    }
+
+    /// Returns the raw contents of the string, escape sequences are kept as is.
+    fn read_string(&mut self) -> StdResult<&'a [u8]> {
+        self.expect(b'"')?;
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                Some(b'"') => break,
+                Some(b'\\') => self.pos += 2,
+                Some(_) => self.pos += 1,
+                None => return Err(self.error()),
+            }
+        }
+        let value = &self.data[start..self.pos];
+        self.pos += 1;
+        Ok(value)
+    }
+
+    fn read_scalar(&mut self) -> &'a [u8] {
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+            if matches!(byte, b',' | b'}' | b']' | b' ' | b'\n' | b'\r' | b'\t') {
+                break;
+            }
+            self.pos += 1;
+        }
+        &self.data[start..self.pos]
+    }
+
+    fn skip_value(&mut self) -> StdResult<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => {
+                self.read_string()?;
+            }
+            Some(b'{') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    self.read_string()?;
+                    self.skip_whitespace();
+                    self.expect(b':')?;
+                    self.skip_value()?;
+                    if !self.next_item(b'}')? {
+                        break;
+                    }
+                }
+            }
+            Some(b'[') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                loop {
+                    self.skip_value()?;
+                    if !self.next_item(b']')? {
+                        break;
+                    }
+                }
+            }
+            Some(_) => {
+                if self.read_scalar().is_empty() {
+                    return Err(self.error());
+                }
+            }
+            None => return Err(self.error()),
+        }
+        Ok(())
+    }
+
+    fn error(&self) -> StdError {
+        StdError::parse_err("JSON", format!("unexpected input at {}", self.pos))
+    }
+}
@@ -0,0 +1,626 @@
+use astroport::generator_proxy::{Cw20HookMsg, ExecuteMsg, QueryMsg};
+use astroport::generator_proxy_generic::{ConfigResponse, InstantiateMsg, MessageTemplates};
+use astroport::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{to_binary, Addr, Uint128};
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
+use terra_multi_test::{next_block, App, BankKeeper, ContractWrapper, Executor, TerraMockQuerier};
+
+const OWNER: &str = "owner";
+const GENERATOR: &str = "generator";
+const USER1: &str = "user1";
+const PAIR: &str = "pair";
+
+#[test]
+fn proxy_to_mirror_like_staking() {
+    let mut app = mock_app();
+
+    let staking_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_staking::mirror_execute,
+        mock_staking::instantiate,
+        mock_staking::mirror_query,
+    )));
+
+    let templates = MessageTemplates {
+        deposit: r#"{"bond":{"asset_token":"{pair}"}}"#.to_string(),
+        withdraw: r#"{"unbond":{"asset_token":"{pair}","amount":"{amount}"}}"#.to_string(),
+        claim: r#"{"withdraw":{"asset_token":"{pair}"}}"#.to_string(),
+        reward_info: r#"{"reward_info":{"staker_addr":"{proxy}","asset_token":"{pair}"}}"#
+            .to_string(),
+        deposit_path: "reward_infos.0.bond_amount".to_string(),
+        pending_reward_path: "reward_infos.0.pending_reward".to_string(),
+    };
+
+    check_proxy_flow(&mut app, staking_code_id, templates);
+}
+
+#[test]
+fn proxy_to_cw20_staking() {
+    let mut app = mock_app();
+
+    let staking_code_id = app.store_code(Box::new(ContractWrapper::new(
+        mock_staking::cw20_staking_execute,
+        mock_staking::instantiate,
+        mock_staking::cw20_staking_query,
+    )));
+
+    let templates = MessageTemplates {
+        deposit: r#"{"bond":{}}"#.to_string(),
+        withdraw: r#"{"unbond":{"amount":"{amount}"}}"#.to_string(),
+        claim: r#"{"withdraw":{}}"#.to_string(),
+        reward_info: r#"{"staker_info":{"staker":"{proxy}"}}"#.to_string(),
+        deposit_path: "bond_amount".to_string(),
+        pending_reward_path: "pending_reward".to_string(),
+    };
+
+    check_proxy_flow(&mut app, staking_code_id, templates);
+}
+
+#[test]
+fn invalid_templates() {
+    let mut app = mock_app();
+
+    let token_code_id = store_token_code(&mut app);
+    let lp_token = instantiate_token(&mut app, token_code_id, "LP");
+    let reward_token = instantiate_token(&mut app, token_code_id, "REWARD");
+    let proxy_code_id = store_proxy_code(&mut app);
+
+    let templates = MessageTemplates {
+        deposit: r#"{"bond":{}}"#.to_string(),
+        withdraw: r#"{"unbond":{"amount":"{amount}"}}"#.to_string(),
+        claim: r#"{"withdraw":{}}"#.to_string(),
+        reward_info: r#"{"staker_info":{"staker":"{proxy}"}}"#.to_string(),
+        deposit_path: "bond_amount".to_string(),
+        pending_reward_path: "pending_reward".to_string(),
+    };
+
+    let msg = InstantiateMsg {
+        generator_contract_addr: GENERATOR.to_string(),
+        pair_addr: PAIR.to_string(),
+        lp_token_addr: lp_token.to_string(),
+        reward_contract_addr: "staking".to_string(),
+        reward_token_addr: reward_token.to_string(),
+        templates: MessageTemplates {
+            deposit: r#"{"bond":{}"#.to_string(),
+            ..templates.clone()
+        },
+    };
+    let err = app
+        .instantiate_contract(
+            proxy_code_id,
+            Addr::unchecked(OWNER),
+            &msg,
+            &[],
+            "Proxy",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The deposit template is not a valid JSON object!"
+    );
+
+    let msg = InstantiateMsg {
+        templates: MessageTemplates {
+            claim: r#""withdraw""#.to_string(),
+            ..templates.clone()
+        },
+        ..msg
+    };
+    let err = app
+        .instantiate_contract(
+            proxy_code_id,
+            Addr::unchecked(OWNER),
+            &msg,
+            &[],
+            "Proxy",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The claim template is not a valid JSON object!"
+    );
+
+    let msg = InstantiateMsg {
+        templates: MessageTemplates {
+            withdraw: r#"{"unbond":{}}"#.to_string(),
+            ..templates.clone()
+        },
+        ..msg
+    };
+    let err = app
+        .instantiate_contract(
+            proxy_code_id,
+            Addr::unchecked(OWNER),
+            &msg,
+            &[],
+            "Proxy",
+            None,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "The withdraw template must contain the {amount} placeholder!"
+    );
+
+    let msg = InstantiateMsg { templates, ..msg };
+    app.instantiate_contract(
+        proxy_code_id,
+        Addr::unchecked(OWNER),
+        &msg,
+        &[],
+        "Proxy",
+        None,
+    )
+    .unwrap();
+}
+
+/// Deposits, claims and withdraws LP tokens through the proxy on behalf of the generator.
+/// The mock staking contract accrues 10 reward tokens per block to every staker.
+fn check_proxy_flow(app: &mut App, staking_code_id: u64, templates: MessageTemplates) {
+    let owner = Addr::unchecked(OWNER);
+    let generator = Addr::unchecked(GENERATOR);
+    let user1 = Addr::unchecked(USER1);
+
+    let token_code_id = store_token_code(app);
+    let lp_token = instantiate_token(app, token_code_id, "LP");
+    let reward_token = instantiate_token(app, token_code_id, "REWARD");
+
+    let staking_instance = app
+        .instantiate_contract(
+            staking_code_id,
+            owner.clone(),
+            &mock_staking::InstantiateMsg {
+                staking_token: lp_token.to_string(),
+                reward_token: reward_token.to_string(),
+                asset_token: PAIR.to_string(),
+            },
+            &[],
+            "Staking",
+            None,
+        )
+        .unwrap();
+
+    let proxy_code_id = store_proxy_code(app);
+    let msg = InstantiateMsg {
+        generator_contract_addr: generator.to_string(),
+        pair_addr: PAIR.to_string(),
+        lp_token_addr: lp_token.to_string(),
+        reward_contract_addr: staking_instance.to_string(),
+        reward_token_addr: reward_token.to_string(),
+        templates,
+    };
+    let proxy_instance = app
+        .instantiate_contract(proxy_code_id, owner.clone(), &msg, &[], "Proxy", None)
+        .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&proxy_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res, msg);
+
+    mint_tokens(app, &lp_token, &generator, 100);
+    mint_tokens(app, &lp_token, &user1, 100);
+    mint_tokens(app, &reward_token, &staking_instance, 1_000);
+
+    check_proxy_amount(app, &proxy_instance, QueryMsg::Deposit {}, 0);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::PendingToken {}, 0);
+
+    // Only the generator can deposit LP tokens
+    let msg = Cw20ExecuteMsg::Send {
+        contract: proxy_instance.to_string(),
+        msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+        amount: Uint128::new(100),
+    };
+    let err = app
+        .execute_contract(user1.clone(), lp_token.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(generator.clone(), lp_token.clone(), &msg, &[])
+        .unwrap();
+    check_token_balance(app, &lp_token, &staking_instance, 100);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::Deposit {}, 100);
+
+    app.update_block(|bi| next_block(bi));
+    app.update_block(|bi| next_block(bi));
+    check_proxy_amount(app, &proxy_instance, QueryMsg::PendingToken {}, 20);
+
+    app.execute_contract(
+        generator.clone(),
+        proxy_instance.clone(),
+        &ExecuteMsg::UpdateRewards {},
+        &[],
+    )
+    .unwrap();
+    check_token_balance(app, &reward_token, &proxy_instance, 20);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::Reward {}, 20);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::PendingToken {}, 0);
+
+    let msg = ExecuteMsg::SendRewards {
+        account: user1.clone(),
+        amount: Uint128::new(20),
+    };
+    let err = app
+        .execute_contract(user1.clone(), proxy_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(generator.clone(), proxy_instance.clone(), &msg, &[])
+        .unwrap();
+    check_token_balance(app, &reward_token, &user1, 20);
+    check_token_balance(app, &reward_token, &proxy_instance, 0);
+
+    let msg = ExecuteMsg::Withdraw {
+        account: generator.clone(),
+        amount: Uint128::new(40),
+    };
+    let err = app
+        .execute_contract(user1.clone(), proxy_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(err.to_string(), "Unauthorized");
+
+    app.execute_contract(generator.clone(), proxy_instance.clone(), &msg, &[])
+        .unwrap();
+    check_token_balance(app, &lp_token, &generator, 40);
+    check_token_balance(app, &lp_token, &proxy_instance, 0);
+    check_token_balance(app, &lp_token, &staking_instance, 60);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::Deposit {}, 60);
+
+    let msg = ExecuteMsg::EmergencyWithdraw {
+        account: generator.clone(),
+        amount: Uint128::new(60),
+    };
+    app.execute_contract(generator.clone(), proxy_instance.clone(), &msg, &[])
+        .unwrap();
+    check_token_balance(app, &lp_token, &generator, 100);
+    check_proxy_amount(app, &proxy_instance, QueryMsg::Deposit {}, 0);
+}
+
+fn mock_app() -> App {
+    let api = MockApi::default();
+    let env = mock_env();
+    let bank = BankKeeper::new();
+    let storage = MockStorage::new();
+    let terra_mock_querier = TerraMockQuerier::new(MockQuerier::new(&[]));
+
+    App::new(api, env.block, bank, storage, terra_mock_querier)
+}
+
+fn store_token_code(app: &mut App) -> u64 {
+    let token_contract = Box::new(ContractWrapper::new(
+        astroport_token::contract::execute,
+        astroport_token::contract::instantiate,
+        astroport_token::contract::query,
+    ));
+
+    app.store_code(token_contract)
+}
+
+fn store_proxy_code(app: &mut App) -> u64 {
+    let proxy_contract = Box::new(ContractWrapper::new(
+        astroport_generator_proxy_generic::contract::execute,
+        astroport_generator_proxy_generic::contract::instantiate,
+        astroport_generator_proxy_generic::contract::query,
+    ));
+
+    app.store_code(proxy_contract)
+}
+
+fn instantiate_token(app: &mut App, token_code_id: u64, name: &str) -> Addr {
+    let name = String::from(name);
+
+    let msg = TokenInstantiateMsg {
+        name: name.clone(),
+        symbol: name.clone(),
+        decimals: 6,
+        initial_balances: vec![],
+        mint: Some(MinterResponse {
+            minter: String::from(OWNER),
+            cap: None,
+        }),
+    };
+
+    app.instantiate_contract(token_code_id, Addr::unchecked(OWNER), &msg, &[], name, None)
+        .unwrap()
+}
+
+fn mint_tokens(app: &mut App, token: &Addr, recipient: &Addr, amount: u128) {
+    let msg = Cw20ExecuteMsg::Mint {
+        recipient: recipient.to_string(),
+        amount: Uint128::from(amount),
+    };
+
+    app.execute_contract(Addr::unchecked(OWNER), token.to_owned(), &msg, &[])
+        .unwrap();
+}
+
+fn check_token_balance(app: &mut App, token: &Addr, address: &Addr, expected: u128) {
+    let msg = Cw20QueryMsg::Balance {
+        address: address.to_string(),
+    };
+    let res: BalanceResponse = app.wrap().query_wasm_smart(token, &msg).unwrap();
+    assert_eq!(res.balance, Uint128::from(expected));
+}
+
+fn check_proxy_amount(app: &mut App, proxy: &Addr, msg: QueryMsg, expected: u128) {
+    let res: Uint128 = app.wrap().query_wasm_smart(proxy, &msg).unwrap();
+    assert_eq!(res, Uint128::from(expected));
+}
+
+mod mock_staking {
+    use cosmwasm_std::{
+        from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Response,
+        StdError, StdResult, Storage, Uint128, WasmMsg,
+    };
+    use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+    use cw_storage_plus::{Item, Map};
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    const REWARD_PER_BLOCK: u128 = 10;
+
+    const STAKING_TOKEN: Item<Addr> = Item::new("staking_token");
+    const REWARD_TOKEN: Item<Addr> = Item::new("reward_token");
+    const ASSET_TOKEN: Item<String> = Item::new("asset_token");
+    const STAKERS: Map<&Addr, StakerInfo> = Map::new("stakers");
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct InstantiateMsg {
+        pub staking_token: String,
+        pub reward_token: String,
+        pub asset_token: String,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+    struct StakerInfo {
+        bond_amount: Uint128,
+        pending_reward: Uint128,
+        last_height: u64,
+    }
+
+    /// Messages of Mirror's `staking` contract
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum MirrorCw20HookMsg {
+        Bond { asset_token: String },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum MirrorExecuteMsg {
+        Receive(Cw20ReceiveMsg),
+        Unbond {
+            asset_token: String,
+            amount: Uint128,
+        },
+        Withdraw {
+            asset_token: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum MirrorQueryMsg {
+        RewardInfo {
+            staker_addr: String,
+            asset_token: Option<String>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct RewardInfoResponse {
+        pub staker_addr: String,
+        pub reward_infos: Vec<RewardInfoResponseItem>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct RewardInfoResponseItem {
+        pub asset_token: String,
+        pub bond_amount: Uint128,
+        pub pending_reward: Uint128,
+        pub is_short: bool,
+    }
+
+    /// Messages of a cw20 LP staking contract with a single staking token
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum StakingCw20HookMsg {
+        Bond {},
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum StakingExecuteMsg {
+        Receive(Cw20ReceiveMsg),
+        Unbond { amount: Uint128 },
+        Withdraw {},
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum StakingQueryMsg {
+        StakerInfo {
+            staker: String,
+            block_height: Option<u64>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    pub struct StakerInfoResponse {
+        pub staker: String,
+        pub reward_index: Decimal,
+        pub bond_amount: Uint128,
+        pub pending_reward: Uint128,
+    }
+
+    pub fn instantiate(
+        deps: DepsMut,
+        _env: Env,
+        _info: MessageInfo,
+        msg: InstantiateMsg,
+    ) -> StdResult<Response> {
+        STAKING_TOKEN.save(deps.storage, &Addr::unchecked(msg.staking_token))?;
+        REWARD_TOKEN.save(deps.storage, &Addr::unchecked(msg.reward_token))?;
+        ASSET_TOKEN.save(deps.storage, &msg.asset_token)?;
+
+        Ok(Response::new())
+    }
+
+    pub fn mirror_execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: MirrorExecuteMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            MirrorExecuteMsg::Receive(msg) => match from_binary(&msg.msg)? {
+                MirrorCw20HookMsg::Bond { asset_token } => {
+                    assert_asset_token(deps.storage, &asset_token)?;
+                    bond(deps, env, info, msg)
+                }
+            },
+            MirrorExecuteMsg::Unbond {
+                asset_token,
+                amount,
+            } => {
+                assert_asset_token(deps.storage, &asset_token)?;
+                unbond(deps, env, info, amount)
+            }
+            MirrorExecuteMsg::Withdraw { asset_token } => {
+                assert_asset_token(deps.storage, &asset_token.unwrap_or_default())?;
+                withdraw(deps, env, info)
+            }
+        }
+    }
+
+    pub fn mirror_query(deps: Deps, env: Env, msg: MirrorQueryMsg) -> StdResult<Binary> {
+        match msg {
+            MirrorQueryMsg::RewardInfo {
+                staker_addr,
+                asset_token,
+            } => {
+                let asset_token = asset_token.unwrap_or_default();
+                assert_asset_token(deps.storage, &asset_token)?;
+
+                let staker = load_staker(deps.storage, &env, &Addr::unchecked(&staker_addr))?;
+                let reward_infos =
+                    if staker.bond_amount.is_zero() && staker.pending_reward.is_zero() {
+                        vec![]
+                    } else {
+                        vec![RewardInfoResponseItem {
+                            asset_token,
+                            bond_amount: staker.bond_amount,
+                            pending_reward: staker.pending_reward,
+                            is_short: false,
+                        }]
+                    };
+
+                to_binary(&RewardInfoResponse {
+                    staker_addr,
+                    reward_infos,
+                })
+            }
+        }
+    }
+
+    pub fn cw20_staking_execute(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: StakingExecuteMsg,
+    ) -> StdResult<Response> {
+        match msg {
+            StakingExecuteMsg::Receive(msg) => match from_binary(&msg.msg)? {
+                StakingCw20HookMsg::Bond {} => bond(deps, env, info, msg),
+            },
+            StakingExecuteMsg::Unbond { amount } => unbond(deps, env, info, amount),
+            StakingExecuteMsg::Withdraw {} => withdraw(deps, env, info),
+        }
+    }
+
+    pub fn cw20_staking_query(deps: Deps, env: Env, msg: StakingQueryMsg) -> StdResult<Binary> {
+        match msg {
+            StakingQueryMsg::StakerInfo { staker, .. } => {
+                let info = load_staker(deps.storage, &env, &Addr::unchecked(&staker))?;
+
+                to_binary(&StakerInfoResponse {
+                    staker,
+                    reward_index: Decimal::zero(),
+                    bond_amount: info.bond_amount,
+                    pending_reward: info.pending_reward,
+                })
+            }
+        }
+    }
+
+    fn assert_asset_token(storage: &dyn Storage, asset_token: &str) -> StdResult<()> {
+        if ASSET_TOKEN.load(storage)? != asset_token {
+            return Err(StdError::generic_err("Unknown asset token"));
+        }
+
+        Ok(())
+    }
+
+    fn load_staker(storage: &dyn Storage, env: &Env, staker: &Addr) -> StdResult<StakerInfo> {
+        let mut info = STAKERS.may_load(storage, staker)?.unwrap_or_default();
+        if !info.bond_amount.is_zero() {
+            let blocks = env.block.height - info.last_height;
+            info.pending_reward += Uint128::from(REWARD_PER_BLOCK * blocks as u128);
+        }
+        info.last_height = env.block.height;
+
+        Ok(info)
+    }
+
+    fn bond(
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: Cw20ReceiveMsg,
+    ) -> StdResult<Response> {
+        if info.sender != STAKING_TOKEN.load(deps.storage)? {
+            return Err(StdError::generic_err("Unauthorized"));
+        }
+
+        let staker = Addr::unchecked(msg.sender);
+        let mut staker_info = load_staker(deps.storage, &env, &staker)?;
+        staker_info.bond_amount += msg.amount;
+        STAKERS.save(deps.storage, &staker, &staker_info)?;
+
+        Ok(Response::new())
+    }
+
+    fn unbond(deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> StdResult<Response> {
+        let mut staker_info = load_staker(deps.storage, &env, &info.sender)?;
+        staker_info.bond_amount = staker_info.bond_amount.checked_sub(amount)?;
+        STAKERS.save(deps.storage, &info.sender, &staker_info)?;
+
+        Ok(Response::new().add_message(WasmMsg::Execute {
+            contract_addr: STAKING_TOKEN.load(deps.storage)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }))
+    }
+
+    fn withdraw(deps: DepsMut, env: Env, info: MessageInfo) -> StdResult<Response> {
+        let mut staker_info = load_staker(deps.storage, &env, &info.sender)?;
+        let amount = staker_info.pending_reward;
+        staker_info.pending_reward = Uint128::zero();
+        STAKERS.save(deps.storage, &info.sender, &staker_info)?;
+
+        Ok(Response::new().add_message(WasmMsg::Execute {
+            contract_addr: REWARD_TOKEN.load(deps.storage)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        }))
+    }
+}
@@ -255,6 +255,7 @@ fn create_pair(
                 pair_type: PairType::Xyk {},
                 asset_infos: asset_infos.clone(),
                 init_params: None,
+                initial_alloc_point: None,
             },
             &[],
         )
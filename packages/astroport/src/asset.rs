@@ -4,11 +4,14 @@ use std::fmt;
 
 use crate::factory::PairType;
 use crate::querier::{query_balance, query_token_balance, query_token_symbol};
+#[cfg(feature = "terra")]
+use cosmwasm_std::Decimal;
 use cosmwasm_std::{
-    to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, MessageInfo, QuerierWrapper, StdError,
+    to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, MessageInfo, QuerierWrapper, StdError,
     StdResult, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
+#[cfg(feature = "terra")]
 use terra_cosmwasm::TerraQuerier;
 
 /// ## Description
@@ -28,6 +31,7 @@ impl fmt::Display for Asset {
 }
 
 /// the decimal fraction
+#[cfg(feature = "terra")]
 static DECIMAL_FRACTION: Uint128 = Uint128::new(1_000_000_000_000_000_000u128);
 
 impl Asset {
@@ -45,6 +49,7 @@ impl Asset {
     /// * **self** is the type of the caller object.
     ///
     /// * **querier** is the object of type [`QuerierWrapper`]
+    #[cfg(feature = "terra")]
     pub fn compute_tax(&self, querier: &QuerierWrapper) -> StdResult<Uint128> {
         let amount = self.amount;
         if let AssetInfo::NativeToken { denom } = &self.info {
@@ -63,6 +68,17 @@ impl Asset {
         }
     }
 
+    /// ## Description
+    /// Returns zero as no tax is charged without the `terra` feature.
+    /// ## Params
+    /// * **self** is the type of the caller object.
+    ///
+    /// * **_querier** is the object of type [`QuerierWrapper`]
+    #[cfg(not(feature = "terra"))]
+    pub fn compute_tax(&self, _querier: &QuerierWrapper) -> StdResult<Uint128> {
+        Ok(Uint128::zero())
+    }
+
     /// ## Description
     /// Calculates and returns deducted tax for native token. For other tokens returns an [`Err`].
     /// ## Params
@@ -0,0 +1,162 @@
+use crate::asset::AssetInfo;
+use crate::generator::PriceSource;
+use cosmwasm_std::{Addr, Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure describes the basic settings for creating a contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// contract address that used for controls settings
+    pub owner: String,
+    /// the share token code identifier
+    pub token_code_id: u64,
+    /// the generator contract address
+    pub generator: String,
+    /// the router contract address
+    pub router: String,
+    /// the pair contract address whose LP tokens are compounded
+    pub pair: String,
+    /// the assets received from the generator that are compounded
+    pub reward_assets: Vec<AssetInfo>,
+    /// the price sources of the reward and the pair assets which bound the swaps,
+    /// all prices are denominated in the same quote asset
+    pub price_sources: Vec<(AssetInfo, PriceSource)>,
+    /// the share of the compounded rewards paid to the caller of `Compound`
+    pub bounty: Option<Decimal>,
+    /// the maximum spread of the swaps and the slippage tolerance of the provide
+    pub max_spread: Option<Decimal>,
+}
+
+/// ## Description
+/// This structure describes the execute messages of the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received
+    /// template.
+    Receive(Cw20ReceiveMsg),
+    /// ## Description
+    /// Claims the rewards of the vault from the generator, swaps them into the pair assets,
+    /// provides liquidity and stakes the received LP tokens. The caller receives the bounty.
+    /// ## Executor
+    /// Anyone can execute it
+    Compound {},
+    /// ## Description
+    /// Updates general settings that contains in the [`Config`]
+    /// ## Executor
+    /// Only owner can execute it
+    UpdateConfig {
+        /// the assets received from the generator that are compounded
+        reward_assets: Option<Vec<AssetInfo>>,
+        /// the price sources of the reward and the pair assets
+        price_sources: Option<Vec<(AssetInfo, PriceSource)>>,
+        /// the share of the compounded rewards paid to the caller of `Compound`
+        bounty: Option<Decimal>,
+        /// the maximum spread
+        max_spread: Option<Decimal>,
+    },
+    /// ## Description
+    /// Internal steps of the compounding
+    /// ## Executor
+    /// Only the vault itself can execute it
+    Callback(CallbackMsg),
+    /// Creates a request to change ownership.
+    ProposeNewOwner {
+        /// a new owner
+        owner: String,
+        /// the validity period of the offer to change the owner
+        expires_in: u64,
+    },
+    /// Removes a request to change ownership.
+    DropOwnershipProposal {},
+    /// Approves ownership.
+    ClaimOwnership {},
+}
+
+/// ## Description
+/// This enum describes the internal steps of the compounding.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackMsg {
+    /// Pays the bounty to the keeper and swaps the rest of the rewards into the pair assets
+    SwapRewards {
+        /// the caller of `Compound`
+        keeper: Addr,
+    },
+    /// Provides the balances of the pair assets as liquidity
+    ProvideLiquidity {},
+    /// Stakes the LP tokens received by the vault since the provide in the generator
+    Stake {
+        /// the LP token balance of the vault before the provide
+        lp_balance_before: Uint128,
+    },
+}
+
+/// ## Description
+/// This structure describes a CW20 hook message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Deposits LP tokens to get the share token amount
+    Deposit {},
+    /// Burns the share tokens and sends back the LP token amount
+    Withdraw {},
+}
+
+/// ## Description
+/// This structure describes the query messages of the contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    /// Config returns controls settings that specified in custom [`ConfigResponse`] structure
+    Config {},
+    /// State returns the total deposit and shares of the vault in a [`StateResponse`] object
+    State {},
+    /// Deposit returns the amount of LP tokens the user's shares are worth
+    Deposit { user: String },
+}
+
+/// ## Description
+/// A custom struct for each query response that returns controls settings of contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    /// contract address that used for controls settings
+    pub owner: Addr,
+    /// the generator contract address
+    pub generator: Addr,
+    /// the router contract address
+    pub router: Addr,
+    /// the pair contract address
+    pub pair: Addr,
+    /// the LP token contract address
+    pub lp_token: Addr,
+    /// the share token contract address
+    pub share_token: Addr,
+    /// the assets received from the generator that are compounded
+    pub reward_assets: Vec<AssetInfo>,
+    /// the price sources of the reward and the pair assets
+    pub price_sources: Vec<(AssetInfo, PriceSource)>,
+    /// the share of the compounded rewards paid to the caller of `Compound`
+    pub bounty: Decimal,
+    /// the maximum spread
+    pub max_spread: Decimal,
+}
+
+/// ## Description
+/// A custom struct for each query response that returns the totals of the vault.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StateResponse {
+    /// the amount of LP tokens staked by the vault in the generator
+    pub total_deposit: Uint128,
+    /// the total supply of the share token
+    pub total_shares: Uint128,
+}
+
+/// ## Description
+/// This structure describes a migration message.
+/// We currently take no arguments for migrations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
@@ -1,5 +1,5 @@
 use crate::asset::{AssetInfo, PairInfo};
-use cosmwasm_std::{Addr, Binary};
+use cosmwasm_std::{Addr, Binary, Uint64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result};
@@ -105,6 +105,9 @@ pub enum ExecuteMsg {
         asset_infos: [AssetInfo; 2],
         /// Optional binary serialised parameters for custom pool types
         init_params: Option<Binary>,
+        /// Optional allocation point used to register the LP token in the generator.
+        /// Only the owner can set a non-zero value
+        initial_alloc_point: Option<Uint64>,
     },
     /// Deregister removes a previously created pair
     Deregister {
@@ -1,4 +1,5 @@
-use cosmwasm_std::{Addr, Uint128, Uint64};
+use crate::asset::{Asset, AssetInfo};
+use cosmwasm_std::{Addr, Decimal, Uint128, Uint64};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -19,23 +20,37 @@ pub struct InstantiateMsg {
     pub allowed_reward_proxies: Vec<String>,
     /// a vesting contract
     pub vesting_contract: String,
+    /// tokens per second. If set, emissions are time-based and `tokens_per_block` is not used
+    pub tokens_per_second: Option<Uint128>,
+    /// start time of time-based emissions, the current block time is used if not set
+    pub start_time: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// ## Description
-    /// Update current vesting contract
+    /// Update current vesting contract and factory
     /// ## Executor
     /// Only owner can execute it
     UpdateConfig {
         /// the vesting contract
         vesting_contract: Option<String>,
+        /// the factory contract that is allowed to add and set pools
+        factory: Option<String>,
+        /// the xASTRO token whose balances boost ASTRO rewards
+        xastro_token: Option<String>,
+        /// the length of gauge voting epochs in seconds, enables gauge voting
+        gauge_epoch_length: Option<u64>,
+        /// the maker contract which receives the incentive fee
+        maker: Option<String>,
+        /// the share of incentives which is sent to the maker
+        incentive_fee: Option<Decimal>,
     },
     /// ## Description
     /// Add a new liquidity pool token:
     /// ## Executor
-    /// Only owner can execute it
+    /// Only owner or factory can execute it
     Add {
         /// the LP token contract address
         lp_token: Addr,
@@ -47,7 +62,7 @@ pub enum ExecuteMsg {
     /// ## Description
     /// Update the given pool's ASTRO allocation point
     /// ## Executor
-    /// Only owner can execute it
+    /// Only owner or factory can execute it
     Set {
         /// the LP token contract address
         lp_token: Addr,
@@ -55,6 +70,15 @@ pub enum ExecuteMsg {
         alloc_point: Uint64,
     },
     /// ## Description
+    /// Stops ASTRO emissions of the given pool and excludes it from mass updates.
+    /// Users can still withdraw and claim the remaining rewards
+    /// ## Executor
+    /// Only owner or factory can execute it
+    DeactivatePool {
+        /// the LP token contract address
+        lp_token: Addr,
+    },
+    /// ## Description
     /// Updates reward variables for all pools
     MassUpdatePools {},
     /// ## Description
@@ -72,6 +96,24 @@ pub enum ExecuteMsg {
         amount: Uint128,
     },
     /// ## Description
+    /// Sends pending rewards of the given pools in one transaction.
+    ClaimRewards {
+        /// the LP token contract addresses
+        lp_tokens: Vec<String>,
+    },
+    /// ## Description
+    /// Recalculates the boosted amount of the user's deposit from the current xASTRO balances and
+    /// removes the expired locks of the user, whose bonus is accrued until then.
+    /// Pending rewards are sent to the user.
+    /// ## Executor
+    /// Anyone can execute it
+    Checkpoint {
+        /// the LP token contract address
+        lp_token: Addr,
+        /// the depositor whose boost is updated
+        user: String,
+    },
+    /// ## Description
     /// Withdraw LP tokens from Generator without caring about rewards.
     EmergencyWithdraw {
         /// the LP token contract address
@@ -84,6 +126,24 @@ pub enum ExecuteMsg {
         proxies: Vec<String>,
     },
     /// ## Description
+    /// Sets the price sources of the assets used to calculate the APR and TVL of the pools.
+    /// All prices are denominated in the same quote asset
+    /// ## Executor
+    /// Only owner can execute it
+    SetPriceSources {
+        /// the assets along with their price sources
+        sources: Vec<(AssetInfo, PriceSource)>,
+    },
+    /// ## Description
+    /// Sets the assets which can be distributed by external reward streams along with the minimum
+    /// amount of a single funding
+    /// ## Executor
+    /// Only owner can execute it
+    SetRewardStreamAssets {
+        /// the assets along with the minimum funding amounts
+        assets: Vec<(AssetInfo, Uint128)>,
+    },
+    /// ## Description
     /// Sends the orphan proxy rewards which are left by emergency withdrawals
     SendOrphanProxyReward {
         /// the recipient of withdraw
@@ -95,6 +155,36 @@ pub enum ExecuteMsg {
     /// Receives a message of type [`Cw20ReceiveMsg`]
     Receive(Cw20ReceiveMsg),
     /// ## Description
+    /// Funds an external reward stream of the pool with native tokens. Cw20 tokens are funded
+    /// with the [`Cw20HookMsg::FundRewardStream`] hook instead. The asset must be allowed by the
+    /// owner and the amount can't be less than its minimum. While nothing is staked in the pool,
+    /// the end time of the stream is postponed.
+    /// ## Executor
+    /// Anyone can execute it
+    FundRewardStream {
+        /// the LP token contract address
+        lp_token: String,
+        /// the reward asset and amount to distribute
+        reward: Asset,
+        /// the time when the distribution ends
+        end_time: u64,
+    },
+    /// ## Description
+    /// Streams the native tokens to the stakers of the pool over the duration, in addition to the other
+    /// incentives of the pool. The incentive fee is sent to the maker. Cw20 tokens are sent with the
+    /// [`Cw20HookMsg::Incentivize`] hook instead. The asset must be allowed in reward streams and the
+    /// amount can't be less than its minimum.
+    /// ## Executor
+    /// Anyone can execute it
+    Incentivize {
+        /// the LP token contract address
+        lp_token: String,
+        /// the reward asset and amount to distribute
+        reward: Asset,
+        /// the duration of the distribution in seconds
+        duration: u64,
+    },
+    /// ## Description
     /// a new count of tokens per block
     /// ## Executor
     /// Only owner can execute it
@@ -103,6 +193,144 @@ pub enum ExecuteMsg {
         amount: Uint128,
     },
     /// ## Description
+    /// a new count of tokens per second for time-based emissions
+    /// ## Executor
+    /// Only owner can execute it
+    SetTokensPerSecond {
+        /// the amount
+        amount: Uint128,
+    },
+    /// ## Description
+    /// Sets the emission schedule for time-based emissions. No ASTRO is emitted outside the periods
+    /// of the schedule, an empty list removes the schedule.
+    /// ## Executor
+    /// Only owner can execute it
+    SetEmissionSchedule {
+        /// the emission periods sorted by time
+        periods: Vec<EmissionPeriod>,
+    },
+    /// ## Description
+    /// Sets the lock-up tiers of the pool. Existing locks keep their multipliers, an empty list
+    /// disables locked deposits.
+    /// ## Executor
+    /// Only owner can execute it
+    SetLockTiers {
+        /// the LP token contract address
+        lp_token: Addr,
+        /// the lock-up tiers
+        tiers: Vec<LockTier>,
+    },
+    /// ## Description
+    /// Enables the disabled reward proxy of the pool after its failure was fixed.
+    /// ## Executor
+    /// Only owner can execute it
+    ReattachRewardProxy {
+        /// the LP token contract address
+        lp_token: Addr,
+    },
+    /// ## Description
+    /// Sends the proxy rewards of the sender which were not paid because of a failure of the proxy.
+    /// ## Executor
+    /// Anyone can execute it
+    ClaimUnpaidProxyRewards {
+        /// the reward proxy contract address
+        reward_proxy: String,
+    },
+    /// ## Description
+    /// Moves the LP tokens of the pool to the new reward proxy. The rewards of the current proxy
+    /// are settled and can be claimed with [`ExecuteMsg::ClaimUnpaidProxyRewards`].
+    /// ## Executor
+    /// Only owner can execute it
+    MoveToProxy {
+        /// the LP token contract address
+        lp_token: Addr,
+        /// the new reward proxy contract address
+        new_proxy: String,
+    },
+    /// ## Description
+    /// Withdraws the LP tokens of the pool from the reward proxy to the generator. The rewards of
+    /// the proxy are settled and can be claimed with [`ExecuteMsg::ClaimUnpaidProxyRewards`].
+    /// ## Executor
+    /// Only owner can execute it
+    DetachProxy {
+        /// the LP token contract address
+        lp_token: Addr,
+    },
+    /// ## Description
+    /// Moves a part of the sender's deposit to the recipient. The pending rewards of both accounts
+    /// are sent to them. Locked deposits can't be transferred.
+    /// ## Executor
+    /// Any depositor can execute it
+    TransferPosition {
+        /// the LP token contract address
+        lp_token: Addr,
+        /// the amount of LP tokens to transfer
+        amount: Uint128,
+        /// the recipient of the deposit
+        recipient: String,
+    },
+    /// ## Description
+    /// Allows the operator to claim the rewards of the sender to the recipient. Replaces the recipient
+    /// if the operator is already allowed.
+    /// ## Executor
+    /// Anyone can execute it
+    SetClaimOperator {
+        /// the operator contract address
+        operator: String,
+        /// the recipient of the claimed rewards
+        recipient: String,
+    },
+    /// ## Description
+    /// Revokes the claim allowance of the operator.
+    /// ## Executor
+    /// Anyone can execute it
+    RemoveClaimOperator {
+        /// the operator contract address
+        operator: String,
+    },
+    /// ## Description
+    /// Claims the rewards of the user from the given pools to the recipient set by the user.
+    /// ## Executor
+    /// Only an operator allowed by the user can execute it
+    ClaimRewardsFor {
+        /// the LP token contract addresses
+        lp_tokens: Vec<String>,
+        /// the user whose rewards are claimed
+        user: String,
+    },
+    /// ## Description
+    /// Votes for the pools with the xASTRO locked by the sender in the current epoch. Replaces
+    /// the previous votes of the sender in the epoch.
+    /// ## Executor
+    /// Anyone who locked xASTRO with the [`Cw20HookMsg::LockVotingPower`] hook can execute it
+    Vote {
+        /// the LP token contract addresses along with the share of the voting weight in basis points
+        votes: Vec<(String, u16)>,
+    },
+    /// ## Description
+    /// Sends the locked xASTRO back to the sender. The xASTRO can't be unlocked in the epoch in
+    /// which the sender voted.
+    /// ## Executor
+    /// Anyone who locked xASTRO can execute it
+    UnlockVotingPower {
+        /// the amount of xASTRO to unlock
+        amount: Uint128,
+    },
+    /// ## Description
+    /// Sets the allocation points of the pools according to the votes of the previous epoch.
+    /// ## Executor
+    /// Anyone can execute it once per epoch
+    TuneAllocations {},
+    /// ## Description
+    /// Writes the current deposits of the next users to the deposit snapshots. The snapshots are
+    /// available since the block after all deposits are seeded.
+    /// ## Executor
+    /// Only owner can execute it
+    SeedSnapshots {
+        /// the maximum number of deposits to seed
+        limit: Option<u32>,
+    },
+    /// ## Description
     /// Creates a request to change ownership
     /// ## Executor
     /// Only owner can execute it
@@ -139,6 +367,227 @@ pub enum QueryMsg {
     RewardInfo { lp_token: Addr },
     /// OrphanProxyRewards returns reward information for the specified token.
     OrphanProxyRewards { lp_token: Addr },
+    /// RewardStreams returns the external reward streams of the specified pool.
+    RewardStreams { lp_token: Addr },
+    /// Incentives returns the active incentive schedules of the specified pool.
+    Incentives { lp_token: Addr },
+    /// EmissionSchedule returns the emission schedule and the current emission rate.
+    EmissionSchedule {},
+    /// UserBoost returns the boosted amount of the user's deposit.
+    UserBoost { lp_token: Addr, user: Addr },
+    /// UserPositions returns the deposits and pending rewards of the user in all pools.
+    UserPositions {
+        user: Addr,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// Pools returns information about the pools.
+    Pools {
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    /// UserLocks returns the locked deposits of the user in the specified pool.
+    UserLocks { lp_token: Addr, user: Addr },
+    /// UserVotes returns the latest gauge votes of the user.
+    UserVotes { user: Addr },
+    /// VotingPower returns the amount of xASTRO locked by the user for gauge voting.
+    VotingPower { user: Addr },
+    /// EpochVotes returns the vote tally of the specified epoch, or of the current one if not set.
+    EpochVotes { epoch: Option<u64> },
+    /// ProjectedAllocations returns the allocation points according to the votes of the current epoch.
+    ProjectedAllocations {},
+    /// UnpaidProxyRewards returns the proxy rewards of the user which were not paid because of a failure of the proxy
+    /// or were left on the proxy after it was replaced.
+    UnpaidProxyRewards { reward_proxy: Addr, user: Addr },
+    /// ClaimOperators returns the operators which are allowed to claim the rewards of the user.
+    ClaimOperators { user: Addr },
+    /// PoolApr returns the APR of the ASTRO and proxy rewards and the value of the LP tokens
+    /// staked in the specified pool.
+    PoolApr { lp_token: Addr },
+    /// DepositAt returns the deposit of the user in the specified pool at the beginning of the block.
+    DepositAt {
+        lp_token: Addr,
+        user: Addr,
+        height: u64,
+    },
+    /// TotalDepositAt returns the total deposit of the specified pool at the beginning of the block.
+    TotalDepositAt { lp_token: Addr, height: u64 },
+}
+
+/// ## Description
+/// This enum describes the sources of the asset prices.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// the fixed price of one unit of the asset, e.g. one for the quote asset itself
+    Fixed { price: Decimal },
+    /// the Astroport oracle of the pair of the asset and the quote asset
+    Oracle { contract: Addr },
+}
+
+/// ## Description
+/// This structure describes the response to the APR of a pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolAprResponse {
+    /// the value of the LP tokens staked in the pool in the quote asset
+    pub tvl: Uint128,
+    /// the amount of ASTRO emitted to the pool per year at the current rate
+    pub astro_per_year: Uint128,
+    /// the APR of the ASTRO rewards
+    pub reward_apr: Decimal,
+    /// the APR of the proxy rewards estimated from the rewards accrued since the last update of the pool,
+    /// set if the pool has an enabled reward proxy
+    pub proxy_apr: Option<Decimal>,
+}
+
+/// ## Description
+/// This structure describes the response to the claim operator of a user.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ClaimOperatorResponse {
+    /// the operator contract address
+    pub operator: Addr,
+    /// the recipient of the rewards claimed by the operator
+    pub recipient: Addr,
+}
+
+/// ## Description
+/// This structure describes the response to the gauge votes of the user.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserVotesResponse {
+    /// the epoch of the votes
+    pub epoch: u64,
+    /// the voting weight of the user
+    pub weight: Uint128,
+    /// the LP token contracts along with the share of the voting weight in basis points
+    pub votes: Vec<(Addr, u16)>,
+}
+
+/// ## Description
+/// This structure describes the response to the vote tally of an epoch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EpochVotesResponse {
+    /// the epoch
+    pub epoch: u64,
+    /// the LP token contracts along with their voting weight
+    pub votes: Vec<(Addr, Uint128)>,
+}
+
+/// ## Description
+/// This structure describes the response to the projected allocation points.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProjectedAllocationsResponse {
+    /// the epoch of the votes
+    pub epoch: u64,
+    /// the LP token contracts along with their allocation points, empty if there are no votes
+    pub allocations: Vec<(Addr, Uint64)>,
+}
+
+/// ## Description
+/// This structure describes the response to the user position in a pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserPositionResponse {
+    /// the LP token contract address
+    pub lp_token: Addr,
+    /// the deposited amount of LP tokens
+    pub amount: Uint128,
+    /// a pending token
+    pub pending: Uint128,
+    /// a pending token on proxy
+    pub pending_on_proxy: Option<Uint128>,
+    /// pending tokens of the external reward streams
+    pub pending_on_streams: Vec<Asset>,
+}
+
+/// ## Description
+/// This structure describes the response to the pool information.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PoolInfoResponse {
+    /// the LP token contract address
+    pub lp_token: Addr,
+    /// the allocation point of the pool
+    pub alloc_point: Uint64,
+    /// the block of the last reward update
+    pub last_reward_block: Uint64,
+    /// the time of the last reward update
+    pub last_reward_time: u64,
+    /// accumulated amount of ASTRO per share unit
+    pub accumulated_rewards_per_share: Decimal,
+    /// the reward proxy contract
+    pub reward_proxy: Option<Addr>,
+    /// whether the reward proxy is disabled after a failure
+    pub reward_proxy_disabled: bool,
+    /// accumulated amount of proxy rewards per share unit
+    pub accumulated_proxy_rewards_per_share: Decimal,
+    /// the orphan proxy rewards which are left by emergency withdrawals
+    pub orphan_proxy_rewards: Uint128,
+    /// the external reward streams of the pool
+    pub reward_streams: Vec<RewardStream>,
+    /// the sum of boosted amounts of all users
+    pub total_virtual_amount: Option<Uint128>,
+    /// the lock-up tiers of the pool
+    pub lock_tiers: Vec<LockTier>,
+    /// whether the pool is deactivated
+    pub deactivated: bool,
+}
+
+/// ## Description
+/// This structure describes a lock-up tier of a pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockTier {
+    /// the lock-up duration in seconds
+    pub duration: u64,
+    /// the multiplier applied to the weight of the locked amount, at least 1
+    pub multiplier: Decimal,
+}
+
+/// ## Description
+/// This structure describes a locked deposit of a user.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedPosition {
+    /// the locked amount of LP tokens
+    pub amount: Uint128,
+    /// the multiplier applied to the weight of the locked amount
+    pub multiplier: Decimal,
+    /// the time when the amount can be withdrawn
+    pub unlock_time: u64,
+}
+
+/// ## Description
+/// This structure describes the response to the user boost.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UserBoostResponse {
+    /// the deposited amount of LP tokens
+    pub amount: Uint128,
+    /// the boosted amount used for ASTRO rewards
+    pub virtual_amount: Uint128,
+    /// the ratio of the boosted amount to the minimum one, from 1 to 2.5 without lock-up multipliers
+    pub boost_factor: Decimal,
+    /// the boosted amount after a checkpoint with the current xASTRO balances
+    pub checkpoint_virtual_amount: Uint128,
+}
+
+/// ## Description
+/// This structure describes a period of the emission schedule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionPeriod {
+    /// the time when the period starts
+    pub start_time: u64,
+    /// the time when the period ends
+    pub end_time: u64,
+    /// tokens per second during the period
+    pub tokens_per_second: Uint128,
+}
+
+/// ## Description
+/// This structure describes the response to the emission schedule.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EmissionScheduleResponse {
+    /// the emission periods, empty if the schedule is not set
+    pub periods: Vec<EmissionPeriod>,
+    /// current tokens per second, not set if emissions are block-based
+    pub tokens_per_second: Option<Uint128>,
+    /// the amount of tokens left to emit according to the schedule
+    pub scheduled_emissions: Uint128,
 }
 
 /// ## Description
@@ -156,6 +605,45 @@ pub struct PendingTokenResponse {
     pub pending: Uint128,
     /// a pending token on proxy
     pub pending_on_proxy: Option<Uint128>,
+    /// pending tokens of the external reward streams
+    pub pending_on_streams: Vec<Asset>,
+}
+
+/// ## Description
+/// This structure describes an external reward stream of a pool.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RewardStream {
+    /// the reward asset
+    pub info: AssetInfo,
+    /// the amount of reward tokens distributed per second
+    pub reward_per_second: Decimal,
+    /// the time when the stream was last updated
+    pub last_update_time: u64,
+    /// the time when the distribution ends
+    pub end_time: u64,
+    /// accumulated amount of reward per share unit
+    pub accumulated_rewards_per_share: Decimal,
+}
+
+/// ## Description
+/// This structure describes an incentive schedule of a pool. The rewards are accumulated in the
+/// reward stream of the same asset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct IncentiveSchedule {
+    /// the account which funded the incentive
+    pub incentivizer: Addr,
+    /// the reward asset
+    pub info: AssetInfo,
+    /// the amount distributed after the incentive fee
+    pub amount: Uint128,
+    /// the amount of reward tokens distributed per second
+    pub reward_per_second: Decimal,
+    /// the time when the distribution started
+    pub start_time: u64,
+    /// the time when the schedule was last updated
+    pub last_update_time: u64,
+    /// the time when the distribution ends
+    pub end_time: u64,
 }
 
 /// ## Description
@@ -166,6 +654,9 @@ pub struct RewardInfoResponse {
     pub base_reward_token: Addr,
     /// a proxy reward token
     pub proxy_reward_token: Option<Addr>,
+    /// whether the pool is deactivated
+    #[serde(default)]
+    pub deactivated: bool,
 }
 
 /// ## Description
@@ -186,12 +677,34 @@ pub struct ConfigResponse {
     pub allowed_reward_proxies: Vec<Addr>,
     /// a vesting contract
     pub vesting_contract: Addr,
+    /// the factory contract that is allowed to add and set pools
+    pub factory: Option<Addr>,
+    /// tokens per second, set if emissions are time-based
+    pub tokens_per_second: Option<Uint128>,
+    /// start time of time-based emissions
+    pub start_time: u64,
+    /// the xASTRO token whose balances boost ASTRO rewards
+    pub xastro_token: Option<Addr>,
+    /// the length of gauge voting epochs in seconds, set if gauge voting is enabled
+    pub gauge_epoch_length: Option<u64>,
+    /// the maker contract which receives the incentive fee
+    pub maker: Option<Addr>,
+    /// the share of incentives which is sent to the maker
+    pub incentive_fee: Decimal,
+    /// the price sources of the assets used to calculate the APR of the pools
+    pub price_sources: Vec<(AssetInfo, PriceSource)>,
+    /// the assets allowed in external reward streams along with the minimum funding amounts
+    pub reward_stream_assets: Vec<(AssetInfo, Uint128)>,
 }
 
 /// ## Description
 /// This structure describes a migration message.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// switches a block-based deployment to time-based emissions with the given tokens per second
+    #[serde(default)]
+    pub tokens_per_second: Option<Uint128>,
+}
 
 /// ## Description
 /// This structure describes the custom hooks for the CW20.
@@ -202,4 +715,25 @@ pub enum Cw20HookMsg {
     Deposit {},
     /// DepositFor performs performs the operation of depositing to the recipient.
     DepositFor(Addr),
+    /// FundRewardStream funds an external reward stream of the pool with the received tokens.
+    FundRewardStream {
+        /// the LP token contract address
+        lp_token: String,
+        /// the time when the distribution ends
+        end_time: u64,
+    },
+    /// DepositLocked deposits to the sender and locks the amount for the duration of a pool's lock-up tier.
+    DepositLocked {
+        /// the lock-up duration in seconds, must match one of the pool's tiers
+        duration: u64,
+    },
+    /// Incentivize streams the received tokens to the stakers of the pool over the duration.
+    Incentivize {
+        /// the LP token contract address
+        lp_token: String,
+        /// the duration of the distribution in seconds
+        duration: u64,
+    },
+    /// LockVotingPower locks the received xASTRO as the gauge voting weight of the sender.
+    LockVotingPower {},
 }
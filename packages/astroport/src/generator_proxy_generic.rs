@@ -0,0 +1,42 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// ## Description
+/// This structure describes the basic settings for creating a contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// the generator contract address
+    pub generator_contract_addr: String,
+    /// the pair contract address
+    pub pair_addr: String,
+    /// the liquidity pool token contract address
+    pub lp_token_addr: String,
+    /// the reward contract address
+    pub reward_contract_addr: String,
+    /// the reward token contract address
+    pub reward_token_addr: String,
+    /// the templates of the messages of the reward contract
+    pub templates: MessageTemplates,
+}
+
+/// ## Description
+/// This structure describes the JSON templates of the messages of the reward contract.
+/// The placeholders `{amount}`, `{proxy}`, `{pair}`, `{lp_token}` and `{reward_token}` are replaced
+/// with the amount of LP tokens and the addresses of the proxy, the pair, the LP token and the reward token.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MessageTemplates {
+    /// the hook message sent along with the LP tokens to deposit them
+    pub deposit: String,
+    /// the message which withdraws `{amount}` of LP tokens
+    pub withdraw: String,
+    /// the message which claims the pending rewards
+    pub claim: String,
+    /// the query which returns the deposit and the pending rewards of the proxy
+    pub reward_info: String,
+    /// the dot-separated path to the deposit in the query response, e.g. `reward_infos.0.bond_amount`
+    pub deposit_path: String,
+    /// the dot-separated path to the pending rewards in the query response
+    pub pending_reward_path: String,
+}
+
+pub type ConfigResponse = InstantiateMsg;
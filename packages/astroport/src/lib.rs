@@ -1,8 +1,10 @@
 pub mod asset;
+pub mod autocompounder;
 pub mod common;
 pub mod factory;
 pub mod generator;
 pub mod generator_proxy;
+pub mod generator_proxy_generic;
 pub mod maker;
 pub mod oracle;
 pub mod pair;
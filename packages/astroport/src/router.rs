@@ -1,12 +1,19 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
+use crate::factory::PairType;
 
 pub const MAX_SWAP_OPERATIONS: usize = 50;
+/// The upper limit for the number of hops in a route found by the `FindBestRoute` query
+pub const MAX_ROUTE_HOPS: u32 = 4;
+/// The upper limit for the number of factory pairs considered by the `FindBestRoute` query
+pub const MAX_ROUTE_CANDIDATES: u32 = 100;
+/// The upper limit for the maximum referral commission in basis points
+pub const MAX_REFERRAL_COMMISSION_BPS: u64 = 1000;
 
 /// ## Description
 /// This structure describes the basic settings for creating a contract.
@@ -14,6 +21,24 @@ pub const MAX_SWAP_OPERATIONS: usize = 50;
 pub struct InstantiateMsg {
     /// the astroport factory contract address
     pub astroport_factory: String,
+    /// contract address that used for controls settings
+    pub owner: String,
+    /// the maximum number of hops in a route found by the `FindBestRoute` query
+    pub max_hops: Option<u32>,
+    /// the maximum number of factory pairs considered by the `FindBestRoute` query
+    pub max_route_candidates: Option<u32>,
+    /// the maximum referral commission in basis points
+    pub max_referral_commission_bps: Option<u64>,
+}
+
+/// ## Description
+/// This structure describes the referral which receives a commission from the output of the swap.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Referral {
+    /// the referral address
+    pub address: String,
+    /// the commission in basis points, bounded by the config
+    pub commission_bps: u64,
 }
 
 /// ## Description
@@ -21,7 +46,8 @@ pub struct InstantiateMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SwapOperation {
-    /// Native swap
+    /// Native swap, available only with the `terra` feature
+    #[cfg(feature = "terra")]
     NativeSwap {
         /// the offer denom
         offer_denom: String,
@@ -38,8 +64,21 @@ pub enum SwapOperation {
 }
 
 impl SwapOperation {
+    pub fn get_offer_asset_info(&self) -> AssetInfo {
+        match self {
+            #[cfg(feature = "terra")]
+            SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            SwapOperation::AstroSwap {
+                offer_asset_info, ..
+            } => offer_asset_info.clone(),
+        }
+    }
+
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
+            #[cfg(feature = "terra")]
             SwapOperation::NativeSwap { ask_denom, .. } => AssetInfo::NativeToken {
                 denom: ask_denom.clone(),
             },
@@ -56,18 +95,98 @@ pub enum ExecuteMsg {
     /// Receives a message of type [`Cw20ReceiveMsg`] and processes it depending on the received
     /// template.
     Receive(Cw20ReceiveMsg),
+    /// Updates the limits of the route search and the maximum referral commission
+    /// ## Executor
+    /// Only owner can execute it
+    UpdateConfig {
+        /// the maximum number of hops in a route found by the `FindBestRoute` query
+        max_hops: Option<u32>,
+        /// the maximum number of factory pairs considered by the `FindBestRoute` query
+        max_route_candidates: Option<u32>,
+        /// the maximum referral commission in basis points
+        max_referral_commission_bps: Option<u64>,
+    },
     /// Execute multiple BuyOperation
     ExecuteSwapOperations {
         operations: Vec<SwapOperation>,
         minimum_receive: Option<Uint128>,
         to: Option<Addr>,
+        /// the referral which receives a commission from the output of the swap
+        referral: Option<Referral>,
+    },
+    /// Splits the offer asset across several routes that end in the same asset
+    ExecuteSplitSwap {
+        /// the routes for swap with the amount of the offer asset for each of them
+        routes: Vec<(Vec<SwapOperation>, Uint128)>,
+        /// the minimum receive for all routes together
+        minimum_receive: Option<Uint128>,
+        /// the recipient
+        to: Option<Addr>,
+    },
+    /// Buys the exact amount of the ask asset and refunds the unused offer amount to the sender
+    ExecuteSwapOperationsExactOut {
+        /// operations for swap
+        operations: Vec<SwapOperation>,
+        /// the exact amount of the ask asset to receive
+        ask_amount: Uint128,
+        /// the maximum amount of the offer asset to spend, must match the sent funds
+        max_offer: Uint128,
+        /// the recipient
+        to: Option<Addr>,
+    },
+    /// Swaps the offer asset into both assets of the pair and provides them as liquidity
+    SwapAndProvide {
+        /// the operations for swap with the offer amount for each asset of the pair, in the order
+        /// of the pair assets. The operations are empty for the side which is the offer asset itself
+        operations_per_side: [(Vec<SwapOperation>, Uint128); 2],
+        /// the pair contract address
+        pair: String,
+        /// the minimum amount of LP tokens to receive
+        min_lp_out: Option<Uint128>,
+        /// determines whether the LP tokens are staked in the generator
+        auto_stake: Option<bool>,
     },
 
     /// Internal use
-    /// Swap all offer tokens to ask token
+    /// Swap all offer tokens (or the specified amount of them) to ask token
     ExecuteSwapOperation {
         operation: SwapOperation,
         to: Option<String>,
+        amount: Option<Uint128>,
+    },
+    /// Internal use
+    /// Sends the referral commission from the swap output and the rest of it to the receiver
+    DistributeReferralCommission {
+        asset_info: AssetInfo,
+        prev_balance: Uint128,
+        referral: Referral,
+        receiver: String,
+    },
+    /// Internal use
+    /// Provides the assets received by the router since the previous balances as liquidity
+    ProvideSwappedLiquidity {
+        pair: String,
+        prev_balances: [Asset; 2],
+        min_lp_out: Option<Uint128>,
+        auto_stake: bool,
+        receiver: String,
+    },
+    /// Internal use
+    /// Sends the LP tokens received by the router to the receiver or stakes them in the generator
+    SendLiquidity {
+        liquidity_token: String,
+        prev_balance: Uint128,
+        min_lp_out: Option<Uint128>,
+        auto_stake: bool,
+        receiver: String,
+    },
+    /// Internal use
+    /// Swaps the assets withdrawn from the pair since the previous balances into one asset
+    SwapWithdrawnLiquidity {
+        prev_balances: [Asset; 2],
+        operations: [Vec<SwapOperation>; 2],
+        minimum_receive: Option<Uint128>,
+        receiver: String,
     },
     /// Internal use
     /// Check the swap amount is exceed minimum_receive
@@ -89,6 +208,45 @@ pub enum Cw20HookMsg {
         minimum_receive: Option<Uint128>,
         /// the recipient
         to: Option<String>,
+        /// the referral which receives a commission from the output of the swap
+        referral: Option<Referral>,
+    },
+    ExecuteSplitSwap {
+        /// the routes for swap with the amount of the offer asset for each of them
+        routes: Vec<(Vec<SwapOperation>, Uint128)>,
+        /// the minimum receive for all routes together
+        minimum_receive: Option<Uint128>,
+        /// the recipient
+        to: Option<String>,
+    },
+    ExecuteSwapOperationsExactOut {
+        /// operations for swap
+        operations: Vec<SwapOperation>,
+        /// the exact amount of the ask asset to receive
+        ask_amount: Uint128,
+        /// the recipient
+        to: Option<String>,
+    },
+    SwapAndProvide {
+        /// the operations for swap with the offer amount for each asset of the pair, in the order
+        /// of the pair assets. The operations are empty for the side which is the offer asset itself
+        operations_per_side: [(Vec<SwapOperation>, Uint128); 2],
+        /// the pair contract address
+        pair: String,
+        /// the minimum amount of LP tokens to receive
+        min_lp_out: Option<Uint128>,
+        /// determines whether the LP tokens are staked in the generator
+        auto_stake: Option<bool>,
+    },
+    /// Withdraws the sent LP tokens from the pair and swaps both assets into one asset
+    WithdrawAndSwap {
+        /// the pair contract address
+        pair: String,
+        /// the operations for swap of each asset of the pair, in the order of the pair assets.
+        /// The operations are empty for the asset which is the target asset itself
+        operations: [Vec<SwapOperation>; 2],
+        /// the minimum receive for both assets together
+        minimum_receive: Option<Uint128>,
     },
 }
 
@@ -105,6 +263,36 @@ pub enum QueryMsg {
         offer_amount: Uint128,
         /// operations for swap
         operations: Vec<SwapOperation>,
+        /// the referral which receives a commission from the output of the swap
+        referral: Option<Referral>,
+    },
+    /// Simulates multi-hop swap operations and returns the breakdown for each operation
+    SimulateSwapOperationsDetailed {
+        /// the offer amount
+        offer_amount: Uint128,
+        /// operations for swap
+        operations: Vec<SwapOperation>,
+    },
+    /// Simulates multi-hop swap operations backwards from the ask amount
+    SimulateReverseSwapOperations {
+        /// the ask amount
+        ask_amount: Uint128,
+        /// operations for swap
+        operations: Vec<SwapOperation>,
+    },
+    /// Simulates swaps split across several routes
+    SimulateSplitSwap {
+        /// the routes for swap with the offer amount for each of them
+        routes: Vec<(Vec<SwapOperation>, Uint128)>,
+    },
+    /// Finds the route through the factory pairs with the best output
+    FindBestRoute {
+        /// the offer asset
+        offer_asset: Asset,
+        /// the asks asset info
+        ask_asset_info: AssetInfo,
+        /// the maximum number of hops, bounded by the config
+        max_hops: Option<u32>,
     },
 }
 
@@ -114,18 +302,98 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     /// the astroport factory contract address
     pub astroport_factory: String,
+    /// contract address that used for controls settings
+    pub owner: String,
+    /// the maximum number of hops in a route found by the `FindBestRoute` query
+    pub max_hops: u32,
+    /// the maximum number of factory pairs considered by the `FindBestRoute` query
+    pub max_route_candidates: u32,
+    /// the maximum referral commission in basis points
+    pub max_referral_commission_bps: u64,
 }
 
 /// ## Description
 /// This structure describes the custom struct for each query response.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct SimulateSwapOperationsResponse {
+    /// the amount of swap, the referral commission is already deducted
+    pub amount: Uint128,
+    /// the referral commission
+    pub referral_amount: Uint128,
+}
+
+/// ## Description
+/// This structure describes the custom struct for each query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSwapOperationsDetailedResponse {
     /// the amount of swap
     pub amount: Uint128,
+    /// the amount of swap at the spot prices of the pools, without spread, commission and tax
+    pub spot_amount: Uint128,
+    /// the price impact of the whole swap versus the spot prices of the pools
+    pub price_impact: Decimal,
+    /// the spread amount of all operations in terms of the ask asset
+    pub total_spread_amount: Uint128,
+    /// the commission amount of all operations in terms of the ask asset
+    pub total_commission_amount: Uint128,
+    /// the simulation of each operation
+    pub operations: Vec<SwapOperationSimulation>,
+}
+
+/// ## Description
+/// This structure describes the simulation of a single swap operation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SwapOperationSimulation {
+    /// the simulated operation
+    pub operation: SwapOperation,
+    /// the pair contract address, empty for native swaps
+    pub pair_contract: Option<Addr>,
+    /// the pair type, empty for native swaps
+    pub pair_type: Option<PairType>,
+    /// the amount offered by the router
+    pub offer_amount: Uint128,
+    /// the amount received by the router
+    pub return_amount: Uint128,
+    /// the spread amount of the pair
+    pub spread_amount: Uint128,
+    /// the commission amount of the pair
+    pub commission_amount: Uint128,
+    /// the price impact versus the spot price of the pool, including the tax
+    pub price_impact: Decimal,
+}
+
+/// ## Description
+/// This structure describes the custom struct for each query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateReverseSwapOperationsResponse {
+    /// the offer amount required for swap
+    pub amount: Uint128,
+}
+
+/// ## Description
+/// This structure describes the custom struct for each query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateSplitSwapResponse {
+    /// the total amount of swap
+    pub amount: Uint128,
+    /// the amount of swap for each route
+    pub route_amounts: Vec<Uint128>,
+}
+
+/// ## Description
+/// This structure describes the custom struct for each query response.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FindBestRouteResponse {
+    /// operations for swap
+    pub operations: Vec<SwapOperation>,
+    /// the expected amount of swap
+    pub amount: Uint128,
 }
 
 /// ## Description
 /// This structure describes a migration message.
-/// We currently take no arguments for migrations.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// The owner of the router. Required if the stored config has no owner yet
+    pub owner: Option<String>,
+}